@@ -0,0 +1,22 @@
+// Copies the CSRF token from the csrf_token cookie into the X-CSRF-Token header of every
+// same-origin request that can change something, which includes every server function
+// call. The server rejects those requests without it, see src/auth/csrf.rs.
+(function () {
+    const originalFetch = window.fetch;
+    const safeMethods = ["GET", "HEAD", "OPTIONS"];
+
+    function csrfToken() {
+        const match = document.cookie.match(/(?:^|;\s*)csrf_token=([0-9a-f]+)/);
+        return match ? match[1] : null;
+    }
+
+    window.fetch = function (input, init) {
+        const request = new Request(input, init);
+        const token = csrfToken();
+        const isSameOrigin = new URL(request.url, window.location.href).origin === window.location.origin;
+        if (token && isSameOrigin && !safeMethods.includes(request.method)) {
+            request.headers.set("X-CSRF-Token", token);
+        }
+        return originalFetch.call(window, request);
+    };
+})();
@@ -0,0 +1,3 @@
+-- Posts written before Markdown support were authored as raw HTML, so
+-- existing rows keep the 'html' format and the editor defaults to 'markdown'
+ALTER TABLE blog_posts ADD COLUMN content_format TEXT NOT NULL DEFAULT 'html';
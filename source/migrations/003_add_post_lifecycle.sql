@@ -0,0 +1,17 @@
+-- Blog post lifecycle: draft -> scheduled/published -> archived
+-- Existing posts were already public, so they are treated as published.
+ALTER TABLE blog_posts ADD COLUMN status TEXT NOT NULL DEFAULT 'published';
+ALTER TABLE blog_posts ADD COLUMN publish_at DATETIME;
+-- SQLite cannot add a column with a non-constant default, so the timestamps
+-- start at a fixed value and are backfilled below
+ALTER TABLE blog_posts ADD COLUMN created_at DATETIME NOT NULL DEFAULT '1970-01-01 00:00:00';
+ALTER TABLE blog_posts ADD COLUMN updated_at DATETIME NOT NULL DEFAULT '1970-01-01 00:00:00';
+ALTER TABLE blog_posts ADD COLUMN published_at DATETIME;
+
+UPDATE blog_posts
+SET created_at = CURRENT_TIMESTAMP,
+    updated_at = CURRENT_TIMESTAMP,
+    published_at = CURRENT_TIMESTAMP;
+
+-- Index for filtering the posts visible to anonymous readers
+CREATE INDEX IF NOT EXISTS idx_blog_posts_visibility ON blog_posts(status, publish_at);
@@ -0,0 +1,18 @@
+-- Human readable permalinks for blog posts
+ALTER TABLE blog_posts ADD COLUMN slug TEXT;
+
+-- Existing posts are given slugs generated from their titles when the server
+-- starts, see `backfill_post_slugs`, since SQL can't generate them the way
+-- `slugify` does or pick free suffixes. Posts without one yet are NULL, which
+-- the unique index allows any number of.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_blog_posts_slug ON blog_posts(slug);
+
+-- Previous slugs of renamed posts so old permalinks can be redirected
+CREATE TABLE IF NOT EXISTS blog_post_slug_history (
+    slug TEXT PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(post_id) REFERENCES blog_posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_blog_post_slug_history_post_id ON blog_post_slug_history(post_id);
@@ -0,0 +1,19 @@
+-- Tags that blog posts can be filed under
+CREATE TABLE IF NOT EXISTS tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    -- URL segment used by /tags/:tag
+    slug TEXT NOT NULL UNIQUE
+);
+
+-- Many-to-many link between blog posts and tags
+CREATE TABLE IF NOT EXISTS blog_post_tags (
+    post_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY (post_id, tag_id),
+    FOREIGN KEY(post_id) REFERENCES blog_posts(id) ON DELETE CASCADE,
+    FOREIGN KEY(tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+-- Index for listing the posts of a tag
+CREATE INDEX IF NOT EXISTS idx_blog_post_tags_tag_id ON blog_post_tags(tag_id);
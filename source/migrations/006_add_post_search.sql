@@ -0,0 +1,26 @@
+-- Full-text index over blog post titles and content, backed by blog_posts
+-- as an external content table so the text isn't stored twice
+CREATE VIRTUAL TABLE IF NOT EXISTS blog_posts_fts USING fts5(
+    title,
+    content,
+    content = 'blog_posts',
+    content_rowid = 'id',
+    tokenize = 'porter unicode61'
+);
+
+-- Keep the index in sync with blog_posts
+CREATE TRIGGER IF NOT EXISTS blog_posts_fts_insert AFTER INSERT ON blog_posts BEGIN
+    INSERT INTO blog_posts_fts (rowid, title, content) VALUES (new.id, new.title, new.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS blog_posts_fts_delete AFTER DELETE ON blog_posts BEGIN
+    INSERT INTO blog_posts_fts (blog_posts_fts, rowid, title, content) VALUES ('delete', old.id, old.title, old.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS blog_posts_fts_update AFTER UPDATE OF title, content ON blog_posts BEGIN
+    INSERT INTO blog_posts_fts (blog_posts_fts, rowid, title, content) VALUES ('delete', old.id, old.title, old.content);
+    INSERT INTO blog_posts_fts (rowid, title, content) VALUES (new.id, new.title, new.content);
+END;
+
+-- Index the posts that already exist
+INSERT INTO blog_posts_fts (blog_posts_fts) VALUES ('rebuild');
@@ -0,0 +1,19 @@
+-- Every saved version of a blog post, newest has the highest id
+CREATE TABLE IF NOT EXISTS blog_post_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    post_id INTEGER NOT NULL,
+    -- The admin who saved this version, NULL for versions saved before revisions were recorded
+    account_id INTEGER,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    content_format TEXT NOT NULL DEFAULT 'html',
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(post_id) REFERENCES blog_posts(id) ON DELETE CASCADE,
+    FOREIGN KEY(account_id) REFERENCES accounts(account_id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_blog_post_revisions_post_id ON blog_post_revisions(post_id, id);
+
+-- Start the history of existing posts with their current version
+INSERT INTO blog_post_revisions (post_id, title, content, content_format, created_at)
+SELECT id, title, content, content_format, updated_at FROM blog_posts;
@@ -0,0 +1,23 @@
+-- Reader comments on blog posts, only approved comments are shown under a post
+CREATE TABLE IF NOT EXISTS comments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    post_id INTEGER NOT NULL,
+    -- The comment this is a reply to, NULL for top level comments
+    parent_id INTEGER,
+    author_name TEXT NOT NULL,
+    author_email TEXT NOT NULL,
+    content TEXT NOT NULL,
+    -- pending, approved, rejected or spam
+    status TEXT NOT NULL DEFAULT 'pending',
+    ip_address TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    moderated_at DATETIME,
+    FOREIGN KEY(post_id) REFERENCES blog_posts(id) ON DELETE CASCADE,
+    FOREIGN KEY(parent_id) REFERENCES comments(id) ON DELETE CASCADE
+);
+
+-- Index for loading the comments of a post and the moderation queue
+CREATE INDEX IF NOT EXISTS idx_comments_post_status ON comments(post_id, status);
+CREATE INDEX IF NOT EXISTS idx_comments_status ON comments(status, created_at);
+-- Index for rate limiting submissions per IP
+CREATE INDEX IF NOT EXISTS idx_comments_ip_created ON comments(ip_address, created_at);
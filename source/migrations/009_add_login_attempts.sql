@@ -0,0 +1,22 @@
+-- Every login attempt, used to throttle brute force attempts and shown to admins
+CREATE TABLE IF NOT EXISTS login_attempts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- The username as it was typed, which may not belong to any account
+    username TEXT NOT NULL,
+    account_id INTEGER,
+    ip_address TEXT,
+    user_agent TEXT,
+    -- success, failure, throttled or locked
+    outcome TEXT NOT NULL,
+    attempted_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(account_id) REFERENCES accounts(account_id) ON DELETE SET NULL
+);
+
+-- Indexes for counting recent failures per account and per IP
+CREATE INDEX IF NOT EXISTS idx_login_attempts_username ON login_attempts(username, attempted_at);
+CREATE INDEX IF NOT EXISTS idx_login_attempts_ip ON login_attempts(ip_address, attempted_at);
+-- Index for listing recent failed attempts
+CREATE INDEX IF NOT EXISTS idx_login_attempts_outcome ON login_attempts(outcome, attempted_at);
+
+-- Accounts with too many failed logins are locked until this time
+ALTER TABLE accounts ADD COLUMN locked_until DATETIME;
@@ -0,0 +1,38 @@
+-- TOTP two-factor authentication. The secret is stored base32 encoded as soon as setup
+-- starts, but only used at login once the first code has been confirmed.
+ALTER TABLE accounts ADD COLUMN totp_secret TEXT;
+ALTER TABLE accounts ADD COLUMN totp_enabled BOOLEAN NOT NULL DEFAULT 0;
+-- The time step of the last accepted code, so a code can't be used twice
+ALTER TABLE accounts ADD COLUMN totp_last_step INTEGER;
+
+-- Single-use recovery codes for when the authenticator app is lost, stored hashed
+CREATE TABLE IF NOT EXISTS recovery_codes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL,
+    code_hash TEXT NOT NULL,
+    used_at DATETIME,
+    FOREIGN KEY(account_id) REFERENCES accounts(account_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_recovery_codes_account ON recovery_codes(account_id);
+
+-- Logins that passed the password check and are waiting for a two-factor code
+CREATE TABLE IF NOT EXISTS two_factor_challenges (
+    token TEXT PRIMARY KEY,
+    account_id INTEGER NOT NULL,
+    ip_address TEXT,
+    user_agent TEXT,
+    -- Wrong codes entered so far, the challenge is dropped after a few
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at DATETIME NOT NULL,
+    FOREIGN KEY(account_id) REFERENCES accounts(account_id) ON DELETE CASCADE
+);
+
+-- Site wide security settings, kept in a single row
+CREATE TABLE IF NOT EXISTS security_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    require_admin_two_factor BOOLEAN NOT NULL DEFAULT 0,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO security_settings (id, require_admin_two_factor) VALUES (1, 0);
@@ -0,0 +1,12 @@
+-- Disabled accounts can't log in and their sessions are invalidated
+ALTER TABLE accounts ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT 1;
+
+-- Usernames were never checked for duplicates, so rename any later duplicates before
+-- adding the constraint. Usernames differing only in case count as duplicates.
+UPDATE accounts
+SET username = username || '-' || account_id
+WHERE account_id NOT IN (
+    SELECT MIN(account_id) FROM accounts GROUP BY username COLLATE NOCASE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_accounts_username ON accounts(username COLLATE NOCASE);
@@ -0,0 +1,16 @@
+-- The last run of each background job, kept up to date by the scheduler and shown to
+-- admins. One row per job, keyed by the job's name.
+CREATE TABLE IF NOT EXISTS scheduled_jobs (
+    name TEXT PRIMARY KEY NOT NULL,
+    -- The schedule as written in the code, e.g. "every 1h" or "cron 30 3 * * *"
+    schedule TEXT NOT NULL,
+    last_started_at DATETIME,
+    last_finished_at DATETIME,
+    -- success or failure, NULL while the job has never finished
+    last_outcome TEXT,
+    -- What the last run did, or why it failed
+    last_message TEXT,
+    next_run_at DATETIME,
+    run_count INTEGER NOT NULL DEFAULT 0,
+    failure_count INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,21 @@
+-- Personal API tokens for scripts, sent as `Authorization: Bearer <token>`. Only a
+-- SHA-256 hash of each token is stored.
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- The account the token acts as
+    account_id INTEGER NOT NULL,
+    -- What the token is for, chosen by whoever created it
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    -- The first characters of the token, so it can be recognised in the list
+    token_prefix TEXT NOT NULL,
+    -- Space separated scopes such as "posts:write flags:write"
+    scopes TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- NULL for tokens that never expire
+    expires_at DATETIME,
+    last_used_at DATETIME,
+    FOREIGN KEY(account_id) REFERENCES accounts(account_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_tokens_account_id ON api_tokens(account_id);
@@ -0,0 +1,33 @@
+-- Search the plain text of posts rather than their Markdown or HTML source, so
+-- markup isn't matched or shown in snippets. The server fills in search_text
+-- whenever a post is saved, and for existing posts when it starts.
+ALTER TABLE blog_posts ADD COLUMN search_text TEXT;
+
+DROP TRIGGER IF EXISTS blog_posts_fts_insert;
+DROP TRIGGER IF EXISTS blog_posts_fts_delete;
+DROP TRIGGER IF EXISTS blog_posts_fts_update;
+DROP TABLE IF EXISTS blog_posts_fts;
+
+CREATE VIRTUAL TABLE blog_posts_fts USING fts5(
+    title,
+    search_text,
+    content = 'blog_posts',
+    content_rowid = 'id',
+    tokenize = 'porter unicode61'
+);
+
+CREATE TRIGGER blog_posts_fts_insert AFTER INSERT ON blog_posts BEGIN
+    INSERT INTO blog_posts_fts (rowid, title, search_text) VALUES (new.id, new.title, new.search_text);
+END;
+
+CREATE TRIGGER blog_posts_fts_delete AFTER DELETE ON blog_posts BEGIN
+    INSERT INTO blog_posts_fts (blog_posts_fts, rowid, title, search_text) VALUES ('delete', old.id, old.title, old.search_text);
+END;
+
+CREATE TRIGGER blog_posts_fts_update AFTER UPDATE OF title, search_text ON blog_posts BEGIN
+    INSERT INTO blog_posts_fts (blog_posts_fts, rowid, title, search_text) VALUES ('delete', old.id, old.title, old.search_text);
+    INSERT INTO blog_posts_fts (rowid, title, search_text) VALUES (new.id, new.title, new.search_text);
+END;
+
+-- Index the titles of the posts that already exist until their text is filled in
+INSERT INTO blog_posts_fts (blog_posts_fts) VALUES ('rebuild');
@@ -0,0 +1,3 @@
+-- Number of words in the plain text of a post, so the post list can be sorted by
+-- the same reading time it shows. Filled in by the server along with search_text.
+ALTER TABLE blog_posts ADD COLUMN word_count INTEGER;
@@ -0,0 +1,51 @@
+/// `/api/v1/accounts` endpoints
+use super::{call_server_fn, pagination_headers, ApiError, PageQuery};
+use crate::models::{get_accounts, AccountSummary, Role, ACCOUNT_LIST_PAGE_SIZE};
+use axum::{
+    extract::{rejection::QueryRejection, Query},
+    http::request::Parts,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// An account as listed by the API
+#[derive(Debug, Serialize)]
+pub struct AccountResource {
+    pub id: i32,
+    pub username: String,
+    /// `admin`, `user` or `guest`
+    pub role: &'static str,
+    pub is_active: bool,
+    pub two_factor_enabled: bool,
+    /// Set while the account is locked after too many failed logins
+    pub locked_until: Option<DateTime<Utc>>,
+    pub active_sessions: i64,
+}
+
+impl From<AccountSummary> for AccountResource {
+    fn from(account: AccountSummary) -> Self {
+        AccountResource {
+            id: account.account_id,
+            username: account.username,
+            role: Role::from_id(account.role_id).map(|role| role.as_str()).unwrap_or("unknown"),
+            is_active: account.is_active,
+            two_factor_enabled: account.totp_enabled,
+            locked_until: account.locked_until,
+            active_sessions: account.active_sessions,
+        }
+    }
+}
+
+/// `GET /api/v1/accounts`: lists the accounts by username, needs the `accounts:read` scope
+pub async fn list_accounts(parts: Parts, query: Result<Query<PageQuery>, QueryRejection>) -> Result<Response, ApiError> {
+    let Query(query) = query?;
+    let page = query.page()?;
+    let path = parts.uri.path().to_string();
+    let account_page = call_server_fn(parts, get_accounts(page)).await?;
+
+    let headers = pagination_headers(&path, page, ACCOUNT_LIST_PAGE_SIZE, account_page.total);
+    let accounts: Vec<AccountResource> = account_page.accounts.into_iter().map(AccountResource::from).collect();
+    Ok((headers, Json(accounts)).into_response())
+}
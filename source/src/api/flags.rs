@@ -0,0 +1,32 @@
+/// `/api/v1/flags` endpoints
+use super::{call_server_fn, ApiError};
+use crate::components::{get_mode, save_mode};
+use axum::{
+    extract::rejection::JsonRejection,
+    http::request::Parts,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+/// The site's web flags
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FlagsResource {
+    /// Whether readers are shown the maintenance banner
+    pub maintenance_mode: bool,
+}
+
+/// `GET /api/v1/flags`: returns the web flags
+pub async fn get_flags(parts: Parts) -> Result<Json<FlagsResource>, ApiError> {
+    let maintenance_mode = call_server_fn(parts, get_mode()).await?;
+    Ok(Json(FlagsResource { maintenance_mode }))
+}
+
+/// `PUT /api/v1/flags`: changes the web flags, needs the `flags:write` scope
+pub async fn update_flags(
+    parts: Parts,
+    body: Result<Json<FlagsResource>, JsonRejection>,
+) -> Result<Json<FlagsResource>, ApiError> {
+    let Json(flags) = body?;
+    call_server_fn(parts, save_mode(flags.maintenance_mode)).await?;
+    Ok(Json(flags))
+}
@@ -0,0 +1,258 @@
+/// Versioned JSON REST API
+///
+/// Server functions are how the app talks to itself, and their wire format changes
+/// whenever their arguments do. Scripts and other clients use this API instead, served
+/// under `/api/v1`. Its paths and JSON bodies only change in backwards compatible ways
+/// until there is a `/api/v2`. The OpenAPI document describing it is served at
+/// `/api/v1/openapi.json`.
+///
+/// Endpoints run the same server functions the app uses, so they are authorized the same
+/// way: send an API token as `Authorization: Bearer <token>`, with the scope each
+/// endpoint lists. Reading published posts, the flags and the projects needs no token.
+///
+/// Every error is answered with its HTTP status and a body like
+/// `{"error": {"status": 404, "message": "Post not found"}}`. Lists are paginated with a
+/// `page` query parameter, and their responses carry `X-Total-Count`, `X-Page`,
+/// `X-Per-Page` and `X-Total-Pages` headers along with a `Link` header pointing to the
+/// first, previous, next and last pages.
+use crate::auth::error_status;
+use axum::{
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::{request::Parts, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use dioxus::fullstack::prelude::{DioxusServerContext, ProvideServerContext};
+use dioxus::logger::tracing::error;
+use dioxus::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
+use std::future::Future;
+
+mod accounts;
+mod flags;
+mod openapi;
+mod posts;
+mod projects;
+
+/// Path every endpoint of this version of the API is served under
+pub const API_V1_PREFIX: &str = "/api/v1";
+
+/// Returns the router serving the API, to be merged into the app's router
+pub fn api_routes() -> Router {
+    Router::new()
+        .route("/api/v1/openapi.json", get(openapi::serve_openapi))
+        .route("/api/v1/posts", get(posts::list_posts).post(posts::create_post))
+        .route(
+            "/api/v1/posts/:id",
+            get(posts::get_post).put(posts::update_post).delete(posts::delete_post),
+        )
+        .route("/api/v1/flags", get(flags::get_flags).put(flags::update_flags))
+        .route("/api/v1/projects", get(projects::list_projects))
+        .route("/api/v1/accounts", get(accounts::list_accounts))
+        // Anything else under the prefix gets a JSON error rather than the app's 404 page
+        .route("/api/v1", get(not_found))
+        .route("/api/v1/*rest", get(not_found).post(not_found).put(not_found).delete(not_found))
+}
+
+/// An error answered with its status and a JSON body describing it
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        ApiError {
+            status,
+            message: message.into(),
+        }
+    }
+
+    /// A 404 for a resource that doesn't exist
+    pub fn not_found(message: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::NOT_FOUND, message)
+    }
+
+    /// A 422 for a request body that is well-formed but not valid
+    pub fn invalid(message: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": {
+                "status": self.status.as_u16(),
+                "message": self.message,
+            }
+        });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::new(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        ApiError::new(StatusCode::BAD_REQUEST, rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiError::new(StatusCode::BAD_REQUEST, rejection.body_text())
+    }
+}
+
+/// Answers requests for paths the API doesn't have
+async fn not_found() -> ApiError {
+    ApiError::not_found("No such endpoint")
+}
+
+/// Runs a server function for an API request
+///
+/// The server function sees the request through its server context, so it checks the
+/// caller's API token or session just as it does when the app calls it. Calls rejected by
+/// [`crate::auth::require_role`] or [`crate::auth::require_scope`] become a 401 or 403,
+/// and ones failed with [`crate::auth::request_error`] get its status. Any other error
+/// becomes a 500.
+///
+/// # Arguments
+/// * `parts` - The parts of the API request
+/// * `server_fn` - The server function call to run
+///
+/// # Examples
+/// ```ignore
+/// let post = call_server_fn(parts, preview_post(post_id)).await?;
+/// ```
+pub async fn call_server_fn<T>(
+    parts: Parts,
+    server_fn: impl Future<Output = Result<T, ServerFnError>>,
+) -> Result<T, ApiError> {
+    let context = DioxusServerContext::new(parts);
+    let result = ProvideServerContext::new(server_fn, context.clone()).await;
+    result.map_err(|e| {
+        let message = match e {
+            ServerFnError::ServerError(message) => message,
+            e => e.to_string(),
+        };
+        match error_status(&context.response_parts().headers) {
+            Some(status) => ApiError::new(status, message),
+            None => {
+                error!("API request failed: {message}");
+                ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, message)
+            }
+        }
+    })
+}
+
+/// Whether the request carries credentials in an `Authorization` header
+pub fn has_authorization(parts: &Parts) -> bool {
+    parts.headers.contains_key(axum::http::header::AUTHORIZATION)
+}
+
+/// Query parameters of paginated lists
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    /// 1-based page number, the first page when left out
+    pub page: Option<u32>,
+}
+
+impl PageQuery {
+    /// Returns the requested page, rejecting page 0
+    pub fn page(&self) -> Result<u32, ApiError> {
+        match self.page {
+            Some(0) => Err(ApiError::new(StatusCode::BAD_REQUEST, "Pages are numbered from 1")),
+            Some(page) => Ok(page),
+            None => Ok(1),
+        }
+    }
+}
+
+/// Builds the pagination headers of one page of a list
+///
+/// # Arguments
+/// * `path` - Path of the list, used to build the `Link` header
+/// * `page` - 1-based number of the page being returned
+/// * `per_page` - Number of items on a full page
+/// * `total` - Number of items across every page
+pub fn pagination_headers(path: &str, page: u32, per_page: u32, total: i64) -> HeaderMap {
+    let total_pages = (total.max(0) as u32).div_ceil(per_page).max(1);
+    let mut links = vec![("first", 1)];
+    if page > 1 {
+        links.push(("prev", (page - 1).min(total_pages)));
+    }
+    if page < total_pages {
+        links.push(("next", page + 1));
+    }
+    links.push(("last", total_pages));
+    let link = links
+        .iter()
+        .map(|(rel, page)| format!("<{path}?page={page}>; rel=\"{rel}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut headers = HeaderMap::new();
+    let values = [
+        ("x-total-count", total.to_string()),
+        ("x-page", page.to_string()),
+        ("x-per-page", per_page.to_string()),
+        ("x-total-pages", total_pages.to_string()),
+        ("link", link),
+    ];
+    for (name, value) in values {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(HeaderName::from_static(name), value);
+        }
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::request_error;
+    use axum::http::Request;
+
+    fn request_parts() -> Parts {
+        Request::new(()).into_parts().0
+    }
+
+    #[tokio::test]
+    async fn request_errors_keep_their_status() {
+        let result: Result<(), ApiError> = call_server_fn(request_parts(), async {
+            Err(request_error(StatusCode::CONFLICT, "Another post is already using this slug"))
+        })
+        .await;
+        let error = result.unwrap_err();
+        assert_eq!(error.status, StatusCode::CONFLICT);
+        assert_eq!(error.message, "Another post is already using this slug");
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_server_errors() {
+        let result: Result<(), ApiError> =
+            call_server_fn(request_parts(), async { Err(ServerFnError::new("Database connection error")) }).await;
+        assert_eq!(result.unwrap_err().status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn links_every_page_of_a_list() {
+        let headers = pagination_headers("/api/v1/posts", 2, 10, 35);
+        assert_eq!(headers["x-total-pages"], "4");
+        assert_eq!(
+            headers["link"],
+            "</api/v1/posts?page=1>; rel=\"first\", </api/v1/posts?page=1>; rel=\"prev\", \
+            </api/v1/posts?page=3>; rel=\"next\", </api/v1/posts?page=4>; rel=\"last\""
+        );
+        assert_eq!(pagination_headers("/api/v1/posts", 1, 10, 0)["x-total-pages"], "1");
+    }
+}
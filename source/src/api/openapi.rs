@@ -0,0 +1,377 @@
+/// The OpenAPI 3.0 document describing the API, served at `/api/v1/openapi.json`
+///
+/// It is built from the same scope list tokens are minted with, so the scopes it
+/// documents can't drift from the ones the server checks. Keep the paths and schemas in
+/// step with the handlers when changing them.
+use super::API_V1_PREFIX;
+use crate::auth::ApiScope;
+use axum::Json;
+use serde_json::{json, Map, Value};
+
+/// `GET /api/v1/openapi.json`: returns the OpenAPI document
+pub async fn serve_openapi() -> Json<Value> {
+    Json(openapi_document())
+}
+
+/// Builds the OpenAPI document of the API
+pub fn openapi_document() -> Value {
+    let scope_list = ApiScope::ALL
+        .iter()
+        .map(|scope| format!("* `{}`: {}", scope.as_str(), scope.description()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let post_id = json!({
+        "name": "id",
+        "in": "path",
+        "required": true,
+        "schema": { "type": "integer" }
+    });
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "nicojudge.com API",
+            "version": "1.0.0",
+            "description": "JSON API for the blog's posts, web flags, projects and accounts. \
+                Errors are answered with an `Error` body. Lists are paginated with the `page` \
+                query parameter and `X-Total-Count`, `X-Page`, `X-Per-Page`, `X-Total-Pages` \
+                and `Link` response headers."
+        },
+        "servers": [{ "url": API_V1_PREFIX }],
+        "paths": {
+            "/posts": {
+                "get": {
+                    "summary": "List published posts, newest first",
+                    "operationId": "listPosts",
+                    "tags": ["posts"],
+                    "parameters": [{ "$ref": "#/components/parameters/page" }],
+                    "responses": {
+                        "200": paginated_response("The posts on the page", "PostSummary"),
+                        "400": error_response("Invalid page"),
+                        "500": error_response("Server error")
+                    }
+                },
+                "post": {
+                    "summary": "Create a post",
+                    "operationId": "createPost",
+                    "tags": ["posts"],
+                    "security": security(ApiScope::PostsWrite),
+                    "requestBody": json_body("PostInput"),
+                    "responses": {
+                        "201": {
+                            "description": "The created post",
+                            "headers": {
+                                "Location": {
+                                    "description": "URL of the created post",
+                                    "schema": { "type": "string" }
+                                }
+                            },
+                            "content": json_content(schema_ref("Post"))
+                        },
+                        "400": error_response("Malformed body"),
+                        "401": error_response("Missing or invalid token"),
+                        "403": error_response("Token lacks the scope"),
+                        "409": error_response("Slug already in use"),
+                        "422": error_response("Invalid post"),
+                        "500": error_response("Server error")
+                    }
+                }
+            },
+            "/posts/{id}": {
+                "parameters": [post_id],
+                "get": {
+                    "summary": "Get a post",
+                    "description": "Without a token only published posts are found. With a \
+                        `posts:read` token drafts, scheduled and archived posts are found too.",
+                    "operationId": "getPost",
+                    "tags": ["posts"],
+                    "security": [{}, { "bearerAuth": [ApiScope::PostsRead.as_str()] }],
+                    "responses": {
+                        "200": {
+                            "description": "The post",
+                            "content": json_content(schema_ref("Post"))
+                        },
+                        "401": error_response("Invalid token"),
+                        "403": error_response("Token lacks the scope"),
+                        "404": error_response("No such post"),
+                        "500": error_response("Server error")
+                    }
+                },
+                "put": {
+                    "summary": "Replace a post",
+                    "operationId": "updatePost",
+                    "tags": ["posts"],
+                    "security": security(ApiScope::PostsWrite),
+                    "requestBody": json_body("PostInput"),
+                    "responses": {
+                        "200": {
+                            "description": "The updated post",
+                            "content": json_content(schema_ref("Post"))
+                        },
+                        "400": error_response("Malformed body"),
+                        "401": error_response("Missing or invalid token"),
+                        "403": error_response("Token lacks the scope"),
+                        "404": error_response("No such post"),
+                        "409": error_response("Slug already in use"),
+                        "422": error_response("Invalid post"),
+                        "500": error_response("Server error")
+                    }
+                },
+                "delete": {
+                    "summary": "Delete a post",
+                    "operationId": "deletePost",
+                    "tags": ["posts"],
+                    "security": security(ApiScope::PostsWrite),
+                    "responses": {
+                        "204": { "description": "The post was deleted" },
+                        "401": error_response("Missing or invalid token"),
+                        "403": error_response("Token lacks the scope"),
+                        "404": error_response("No such post"),
+                        "500": error_response("Server error")
+                    }
+                }
+            },
+            "/flags": {
+                "get": {
+                    "summary": "Get the web flags",
+                    "operationId": "getFlags",
+                    "tags": ["flags"],
+                    "responses": {
+                        "200": {
+                            "description": "The web flags",
+                            "content": json_content(schema_ref("Flags"))
+                        },
+                        "500": error_response("Server error")
+                    }
+                },
+                "put": {
+                    "summary": "Change the web flags",
+                    "operationId": "updateFlags",
+                    "tags": ["flags"],
+                    "security": security(ApiScope::FlagsWrite),
+                    "requestBody": json_body("Flags"),
+                    "responses": {
+                        "200": {
+                            "description": "The web flags",
+                            "content": json_content(schema_ref("Flags"))
+                        },
+                        "400": error_response("Malformed body"),
+                        "401": error_response("Missing or invalid token"),
+                        "403": error_response("Token lacks the scope"),
+                        "500": error_response("Server error")
+                    }
+                }
+            },
+            "/projects": {
+                "get": {
+                    "summary": "List the GitHub repositories, most recently pushed first",
+                    "operationId": "listProjects",
+                    "tags": ["projects"],
+                    "responses": {
+                        "200": {
+                            "description": "The repositories",
+                            "content": json_content(json!({ "type": "array", "items": schema_ref("Project") }))
+                        },
+                        "500": error_response("GitHub could not be reached")
+                    }
+                }
+            },
+            "/accounts": {
+                "get": {
+                    "summary": "List accounts by username",
+                    "operationId": "listAccounts",
+                    "tags": ["accounts"],
+                    "security": security(ApiScope::AccountsRead),
+                    "parameters": [{ "$ref": "#/components/parameters/page" }],
+                    "responses": {
+                        "200": paginated_response("The accounts on the page", "Account"),
+                        "400": error_response("Invalid page"),
+                        "401": error_response("Missing or invalid token"),
+                        "403": error_response("Token lacks the scope"),
+                        "500": error_response("Server error")
+                    }
+                }
+            }
+        },
+        "components": {
+            "securitySchemes": {
+                "bearerAuth": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": format!(
+                        "A personal API token created in the admin panel. Each endpoint \
+                        needs one of the token's scopes:\n\n{scope_list}"
+                    )
+                }
+            },
+            "parameters": {
+                "page": {
+                    "name": "page",
+                    "in": "query",
+                    "description": "1-based page number",
+                    "schema": { "type": "integer", "minimum": 1, "default": 1 }
+                }
+            },
+            "headers": pagination_header_definitions(),
+            "schemas": {
+                "Error": {
+                    "type": "object",
+                    "required": ["error"],
+                    "properties": {
+                        "error": {
+                            "type": "object",
+                            "required": ["status", "message"],
+                            "properties": {
+                                "status": { "type": "integer", "description": "The HTTP status" },
+                                "message": { "type": "string" }
+                            }
+                        }
+                    }
+                },
+                "Post": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer" },
+                        "title": { "type": "string" },
+                        "slug": { "type": "string" },
+                        "content": { "type": "string" },
+                        "content_format": { "type": "string", "enum": ["markdown", "html"] },
+                        "content_html": { "type": "string", "description": "Sanitized HTML rendered from content" },
+                        "status": post_status_schema(),
+                        "publish_at": date_time_schema(),
+                        "created_at": date_time_schema(),
+                        "updated_at": date_time_schema(),
+                        "published_at": date_time_schema(),
+                        "tags": { "type": "array", "items": { "type": "string" } }
+                    }
+                },
+                "PostSummary": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer" },
+                        "title": { "type": "string" },
+                        "slug": { "type": "string" },
+                        "excerpt": { "type": "string" },
+                        "published_at": date_time_schema(),
+                        "reading_time_minutes": { "type": "integer" }
+                    }
+                },
+                "PostInput": {
+                    "type": "object",
+                    "required": ["title", "content"],
+                    "properties": {
+                        "title": { "type": "string" },
+                        "slug": { "type": "string", "description": "Generated from the title when left out" },
+                        "content": { "type": "string" },
+                        "content_format": { "type": "string", "enum": ["markdown", "html"], "default": "markdown" },
+                        "status": post_status_schema(),
+                        "publish_at": {
+                            "type": "string",
+                            "format": "date-time",
+                            "description": "Required for scheduled posts"
+                        },
+                        "tags": { "type": "array", "items": { "type": "string" } }
+                    }
+                },
+                "Flags": {
+                    "type": "object",
+                    "required": ["maintenance_mode"],
+                    "properties": {
+                        "maintenance_mode": { "type": "boolean" }
+                    }
+                },
+                "Project": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "description": { "type": "string", "nullable": true },
+                        "pushed_at": { "type": "string", "format": "date-time" },
+                        "html_url": { "type": "string" }
+                    }
+                },
+                "Account": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer" },
+                        "username": { "type": "string" },
+                        "role": { "type": "string", "enum": ["admin", "user", "guest"] },
+                        "is_active": { "type": "boolean" },
+                        "two_factor_enabled": { "type": "boolean" },
+                        "locked_until": date_time_schema(),
+                        "active_sessions": { "type": "integer" }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Names of the headers every paginated list is answered with
+const PAGINATION_HEADERS: [(&str, &str, &str); 5] = [
+    ("X-Total-Count", "integer", "Number of items across every page"),
+    ("X-Page", "integer", "Number of this page"),
+    ("X-Per-Page", "integer", "Number of items on a full page"),
+    ("X-Total-Pages", "integer", "Number of pages"),
+    ("Link", "string", "Links to the first, previous, next and last pages"),
+];
+
+fn pagination_header_definitions() -> Value {
+    let headers: Map<String, Value> = PAGINATION_HEADERS
+        .iter()
+        .map(|(name, kind, description)| {
+            let definition = json!({ "description": description, "schema": { "type": kind } });
+            (name.to_string(), definition)
+        })
+        .collect();
+    Value::Object(headers)
+}
+
+fn paginated_response(description: &str, item_schema: &str) -> Value {
+    let headers: Map<String, Value> = PAGINATION_HEADERS
+        .iter()
+        .map(|(name, _, _)| (name.to_string(), json!({ "$ref": format!("#/components/headers/{name}") })))
+        .collect();
+    json!({
+        "description": description,
+        "headers": headers,
+        "content": json_content(json!({ "type": "array", "items": schema_ref(item_schema) }))
+    })
+}
+
+fn error_response(description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": json_content(schema_ref("Error"))
+    })
+}
+
+fn json_body(schema: &str) -> Value {
+    json!({
+        "required": true,
+        "content": json_content(schema_ref(schema))
+    })
+}
+
+fn json_content(schema: Value) -> Value {
+    json!({ "application/json": { "schema": schema } })
+}
+
+fn schema_ref(schema: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{schema}") })
+}
+
+fn security(scope: ApiScope) -> Value {
+    json!([{ "bearerAuth": [scope.as_str()] }])
+}
+
+fn post_status_schema() -> Value {
+    json!({
+        "type": "string",
+        "enum": ["draft", "scheduled", "published", "archived"],
+        "default": "draft"
+    })
+}
+
+fn date_time_schema() -> Value {
+    json!({ "type": "string", "format": "date-time", "nullable": true })
+}
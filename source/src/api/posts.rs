@@ -0,0 +1,215 @@
+/// `/api/v1/posts` endpoints
+use super::{call_server_fn, has_authorization, pagination_headers, ApiError, PageQuery, API_V1_PREFIX};
+use crate::markdown::render_content;
+use crate::models::{
+    self, get_post_by_id, get_post_summaries, parse_tag_list, preview_post, save_post, BlogPost,
+    ContentFormat, PostListQuery, PostStatus, PostSummary, POST_LIST_PAGE_SIZE,
+};
+use axum::{
+    extract::{
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        Path, Query,
+    },
+    http::{header::LOCATION, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A post as returned by the API
+#[derive(Debug, Serialize)]
+pub struct PostResource {
+    pub id: i32,
+    pub title: String,
+    pub slug: String,
+    pub content: String,
+    /// `markdown` or `html`
+    pub content_format: &'static str,
+    /// Sanitized HTML rendered from `content`
+    pub content_html: String,
+    /// `draft`, `scheduled`, `published` or `archived`
+    pub status: &'static str,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+}
+
+impl From<BlogPost> for PostResource {
+    fn from(post: BlogPost) -> Self {
+        // Posts returned from a save aren't rendered
+        let content_html = if post.rendered_html.is_empty() {
+            render_content(&post.content, &post.content_format)
+        } else {
+            post.rendered_html
+        };
+        PostResource {
+            id: post.id.unwrap_or_default(),
+            title: post.title,
+            slug: post.slug,
+            content: post.content,
+            content_format: post.content_format.as_str(),
+            content_html,
+            status: post.status.as_str(),
+            publish_at: post.publish_at,
+            created_at: post.created_at,
+            updated_at: post.updated_at,
+            published_at: post.published_at,
+            tags: post.tags.into_iter().map(|tag| tag.name).collect(),
+        }
+    }
+}
+
+/// A post as listed by the API, without its content
+#[derive(Debug, Serialize)]
+pub struct PostSummaryResource {
+    pub id: i32,
+    pub title: String,
+    pub slug: String,
+    /// The start of the post as plain text
+    pub excerpt: String,
+    pub published_at: Option<DateTime<Utc>>,
+    pub reading_time_minutes: u32,
+}
+
+impl From<PostSummary> for PostSummaryResource {
+    fn from(summary: PostSummary) -> Self {
+        PostSummaryResource {
+            id: summary.id,
+            title: summary.title,
+            slug: summary.slug,
+            excerpt: summary.excerpt,
+            published_at: summary.published_at,
+            reading_time_minutes: summary.reading_time_minutes,
+        }
+    }
+}
+
+/// The body of requests creating or replacing a post
+#[derive(Debug, Deserialize)]
+pub struct PostInput {
+    pub title: String,
+    /// Generated from the title when left out
+    #[serde(default)]
+    pub slug: String,
+    pub content: String,
+    /// `markdown` or `html`, `markdown` when left out
+    pub content_format: Option<String>,
+    /// `draft`, `scheduled`, `published` or `archived`, `draft` when left out
+    pub status: Option<String>,
+    /// When a scheduled post becomes visible, required for scheduled posts
+    pub publish_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl PostInput {
+    /// Validates the input and converts it into a post to save
+    fn into_post(self, id: Option<i32>) -> Result<BlogPost, ApiError> {
+        if self.title.trim().is_empty() {
+            return Err(ApiError::invalid("title must not be empty"));
+        }
+        let content_format = match self.content_format.as_deref() {
+            None | Some("markdown") => ContentFormat::Markdown,
+            Some("html") => ContentFormat::Html,
+            Some(other) => return Err(ApiError::invalid(format!("Unknown content_format: {other}"))),
+        };
+        let status = match self.status.as_deref() {
+            None => PostStatus::Draft,
+            Some(status) if PostStatus::from_str_or_draft(status).as_str() == status => {
+                PostStatus::from_str_or_draft(status)
+            }
+            Some(other) => return Err(ApiError::invalid(format!("Unknown status: {other}"))),
+        };
+        if status == PostStatus::Scheduled && self.publish_at.is_none() {
+            return Err(ApiError::invalid("A scheduled post needs publish_at"));
+        }
+        // Tags are parsed the way the editor's comma separated list is
+        if self.tags.iter().any(|tag| tag.contains(',')) {
+            return Err(ApiError::invalid("Tag names can't contain commas"));
+        }
+
+        Ok(BlogPost {
+            id,
+            title: self.title,
+            slug: self.slug,
+            content: self.content,
+            content_format,
+            status,
+            publish_at: self.publish_at,
+            tags: parse_tag_list(&self.tags.join(",")),
+            ..BlogPost::default()
+        })
+    }
+}
+
+/// `GET /api/v1/posts`: lists the published posts, newest first
+pub async fn list_posts(parts: Parts, query: Result<Query<PageQuery>, QueryRejection>) -> Result<Response, ApiError> {
+    let Query(query) = query?;
+    let page = query.page()?;
+    let path = parts.uri.path().to_string();
+    let post_query = PostListQuery {
+        page,
+        ..PostListQuery::default()
+    };
+    let summaries = call_server_fn(parts, get_post_summaries(post_query)).await?;
+
+    let headers = pagination_headers(&path, page, POST_LIST_PAGE_SIZE, summaries.total);
+    let posts: Vec<PostSummaryResource> = summaries.posts.into_iter().map(PostSummaryResource::from).collect();
+    Ok((headers, Json(posts)).into_response())
+}
+
+/// `GET /api/v1/posts/:id`: returns a published post, or with the `posts:read` scope any
+/// post including drafts
+pub async fn get_post(parts: Parts, id: Result<Path<i32>, PathRejection>) -> Result<Json<PostResource>, ApiError> {
+    let Path(post_id) = id?;
+    let post = if has_authorization(&parts) {
+        call_server_fn(parts, preview_post(post_id)).await?
+    } else {
+        call_server_fn(parts, get_post_by_id(post_id)).await?
+    };
+    match post {
+        Some(post) => Ok(Json(post.into())),
+        None => Err(ApiError::not_found(format!("Post {post_id} not found"))),
+    }
+}
+
+/// `POST /api/v1/posts`: creates a post, needs the `posts:write` scope
+pub async fn create_post(parts: Parts, body: Result<Json<PostInput>, JsonRejection>) -> Result<Response, ApiError> {
+    let Json(input) = body?;
+    let post = input.into_post(None)?;
+    let Some(post) = call_server_fn(parts, save_post(post)).await? else {
+        return Err(ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "The post was not saved"));
+    };
+
+    let post = PostResource::from(post);
+    let location = format!("{API_V1_PREFIX}/posts/{}", post.id);
+    Ok((StatusCode::CREATED, [(LOCATION, location)], Json(post)).into_response())
+}
+
+/// `PUT /api/v1/posts/:id`: replaces a post, needs the `posts:write` scope
+pub async fn update_post(
+    parts: Parts,
+    id: Result<Path<i32>, PathRejection>,
+    body: Result<Json<PostInput>, JsonRejection>,
+) -> Result<Json<PostResource>, ApiError> {
+    let Path(post_id) = id?;
+    let Json(input) = body?;
+    let post = input.into_post(Some(post_id))?;
+    match call_server_fn(parts, models::update_post(post)).await? {
+        Some(post) => Ok(Json(post.into())),
+        None => Err(ApiError::not_found(format!("Post {post_id} not found"))),
+    }
+}
+
+/// `DELETE /api/v1/posts/:id`: deletes a post, needs the `posts:write` scope
+pub async fn delete_post(parts: Parts, id: Result<Path<i32>, PathRejection>) -> Result<StatusCode, ApiError> {
+    let Path(post_id) = id?;
+    if call_server_fn(parts, models::delete_post(post_id)).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::not_found(format!("Post {post_id} not found")))
+    }
+}
@@ -0,0 +1,11 @@
+/// `/api/v1/projects` endpoints
+use super::{call_server_fn, ApiError};
+use crate::{components::fetch_github_repos, models::Repository};
+use axum::{http::request::Parts, Json};
+
+/// `GET /api/v1/projects`: lists the GitHub repositories shown on the projects page, most
+/// recently pushed first
+pub async fn list_projects(parts: Parts) -> Result<Json<Vec<Repository>>, ApiError> {
+    let repositories = call_server_fn(parts, fetch_github_repos()).await?;
+    Ok(Json(repositories))
+}
@@ -0,0 +1,377 @@
+/// Personal API tokens for scripts
+///
+/// Admins can mint tokens that act as their account without a browser login. A token is
+/// sent as `Authorization: Bearer <token>` and only grants the [`ApiScope`]s it was
+/// created with, on top of the account's role. Server functions opt in by checking
+/// their scope with [`crate::auth::require_scope`]; everything else refuses API tokens,
+/// so a token can never be used to change accounts, sessions or other tokens.
+///
+/// The token is only shown once, when it is created. Only a SHA-256 hash of it is stored.
+#[cfg(feature = "server")]
+use crate::{
+    auth::{generate_token, hash_token, require_role, CurrentUser},
+    database::create_connection,
+    models::{get_account_by_id, Role},
+};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "server")]
+use chrono::Duration;
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info, warn};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use sqlx::FromRow;
+
+/// Longest name a token can be given
+pub const API_TOKEN_NAME_MAX_LENGTH: usize = 100;
+
+/// Longest a token can be made to last, tokens that should last longer are made to never
+/// expire
+pub const API_TOKEN_MAX_EXPIRY_DAYS: u32 = 10 * 366;
+
+/// Number of characters of a token kept to recognise it by
+#[cfg(feature = "server")]
+const TOKEN_PREFIX_LENGTH: usize = 8;
+
+/// Something an API token is allowed to do
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ApiScope {
+    /// List and preview posts, including drafts, and read their revisions
+    PostsRead,
+    /// Create, edit, delete and restore posts
+    PostsWrite,
+    /// Moderate and delete comments
+    CommentsModerate,
+    /// Turn maintenance mode on and off
+    FlagsWrite,
+    /// List accounts, without changing them
+    AccountsRead,
+}
+
+impl ApiScope {
+    /// Every scope, in the order they are offered
+    pub const ALL: [ApiScope; 5] = [
+        ApiScope::PostsRead,
+        ApiScope::PostsWrite,
+        ApiScope::CommentsModerate,
+        ApiScope::FlagsWrite,
+        ApiScope::AccountsRead,
+    ];
+
+    /// Returns the name of the scope as stored in `api_tokens.scopes`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::PostsRead => "posts:read",
+            ApiScope::PostsWrite => "posts:write",
+            ApiScope::CommentsModerate => "comments:moderate",
+            ApiScope::FlagsWrite => "flags:write",
+            ApiScope::AccountsRead => "accounts:read",
+        }
+    }
+
+    /// Returns the scope with the given name, if there is one
+    pub fn from_name(name: &str) -> Option<Self> {
+        ApiScope::ALL.into_iter().find(|scope| scope.as_str() == name)
+    }
+
+    /// What the scope allows, for showing next to it
+    pub fn description(&self) -> &'static str {
+        match self {
+            ApiScope::PostsRead => "Read posts, including drafts and revisions",
+            ApiScope::PostsWrite => "Create, edit and delete posts",
+            ApiScope::CommentsModerate => "Moderate comments",
+            ApiScope::FlagsWrite => "Turn maintenance mode on and off",
+            ApiScope::AccountsRead => "List accounts",
+        }
+    }
+}
+
+/// An API token as listed in the admin panel
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ApiToken {
+    pub id: i32,
+    pub account_id: i32,
+    /// The username of the account the token acts as
+    pub owner: String,
+    pub name: String,
+    /// The first characters of the token
+    pub token_prefix: String,
+    pub scopes: Vec<ApiScope>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ApiToken {
+    /// Whether the token has expired at the given time
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// An API token that has just been created
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ApiTokenIssued {
+    /// The token itself. It can't be shown again.
+    pub token: String,
+    pub api_token: ApiToken,
+}
+
+/// Lists every API token with the account it belongs to, newest first. The caller must
+/// hold an active admin session.
+#[server]
+pub async fn get_api_tokens() -> Result<Vec<ApiToken>, ServerFnError> {
+    require_role(Role::Admin).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let rows = sqlx::query_as::<_, ApiTokenRow>(
+                "SELECT api_tokens.id, api_tokens.account_id, accounts.username AS owner, api_tokens.name,
+                    api_tokens.token_prefix, api_tokens.scopes, api_tokens.created_at,
+                    api_tokens.expires_at, api_tokens.last_used_at
+                FROM api_tokens
+                JOIN accounts ON accounts.account_id = api_tokens.account_id
+                ORDER BY datetime(api_tokens.created_at) DESC, api_tokens.id DESC",
+            )
+            .fetch_all(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error loading API tokens: {e}");
+                ServerFnError::new(format!("Error loading API tokens: {e}"))
+            })?;
+            Ok(rows.into_iter().map(ApiTokenRow::into_api_token).collect())
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Creates an API token acting as the caller's account. The caller must hold an active
+/// admin session, so an API token can't be used to create more tokens.
+///
+/// # Arguments
+/// * `name` - What the token is for
+/// * `scopes` - What the token is allowed to do, at least one
+/// * `expires_in_days` - How long the token can be used for, at most
+///   [`API_TOKEN_MAX_EXPIRY_DAYS`], or `None` for no expiry
+///
+/// # Returns
+/// A `Result` containing the `ApiTokenIssued` with the token, which can't be retrieved
+/// again later.
+#[server]
+pub async fn create_api_token(
+    name: String,
+    scopes: Vec<ApiScope>,
+    expires_in_days: Option<u32>,
+) -> Result<ApiTokenIssued, ServerFnError> {
+    let user = require_role(Role::Admin).await?;
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ServerFnError::new("Give the token a name"));
+    }
+    if name.chars().count() > API_TOKEN_NAME_MAX_LENGTH {
+        return Err(ServerFnError::new(format!(
+            "Token names can be at most {API_TOKEN_NAME_MAX_LENGTH} characters long"
+        )));
+    }
+    let scopes: Vec<ApiScope> = ApiScope::ALL
+        .into_iter()
+        .filter(|scope| scopes.contains(scope))
+        .collect();
+    if scopes.is_empty() {
+        return Err(ServerFnError::new("Choose at least one scope"));
+    }
+    if expires_in_days.is_some_and(|days| days == 0 || days > API_TOKEN_MAX_EXPIRY_DAYS) {
+        return Err(ServerFnError::new(format!(
+            "Tokens can expire after 1 to {API_TOKEN_MAX_EXPIRY_DAYS} days, or never"
+        )));
+    }
+
+    let token = generate_token();
+    let token_hash = hash_token(&token);
+    let token_prefix = token[..TOKEN_PREFIX_LENGTH].to_string();
+    let scope_names = format_scopes(&scopes);
+    let created_at = Utc::now();
+    let expires_at = expires_in_days.map(|days| created_at + Duration::days(days.into()));
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let id = sqlx::query!(
+                "INSERT INTO api_tokens (account_id, name, token_hash, token_prefix, scopes, created_at, expires_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                user.account_id,
+                name,
+                token_hash,
+                token_prefix,
+                scope_names,
+                created_at,
+                expires_at
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error storing API token for {}: {e}", user.username);
+                ServerFnError::new(format!("Error creating API token: {e}"))
+            })?
+            .last_insert_rowid() as i32;
+
+            info!("{} created API token {name:?} with scopes {scope_names}", user.username);
+            Ok(ApiTokenIssued {
+                token,
+                api_token: ApiToken {
+                    id,
+                    account_id: user.account_id,
+                    owner: user.username,
+                    name,
+                    token_prefix,
+                    scopes,
+                    created_at,
+                    expires_at,
+                    last_used_at: None,
+                },
+            })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Revokes an API token of any account, so it can't be used any more. The caller must
+/// hold an active admin session.
+///
+/// # Arguments
+/// * `token_id` - The ID of the token, from [`get_api_tokens`]
+#[server]
+pub async fn revoke_api_token(token_id: i32) -> Result<(), ServerFnError> {
+    let user = require_role(Role::Admin).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let result = sqlx::query!("DELETE FROM api_tokens WHERE id = ?1", token_id)
+                .execute(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error revoking API token {token_id}: {e}");
+                    ServerFnError::new(format!("Error revoking API token: {e}"))
+                })?;
+            if result.rows_affected() == 0 {
+                return Err(ServerFnError::new("API token not found"));
+            }
+            info!("{} revoked API token {token_id}", user.username);
+            Ok(())
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Reads the bearer token from the `Authorization` header of the current request
+///
+/// # Returns
+/// The token, or `None` when the request doesn't carry one.
+#[cfg(feature = "server")]
+pub fn read_bearer_token() -> Option<String> {
+    let context = server_context();
+    let request = context.request_parts();
+    request
+        .headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Looks up the account and scopes of an API token, recording that it was used
+///
+/// # Returns
+/// A `Result` containing the `CurrentUser` the token acts as with its scopes, or `None`
+/// when the token is unknown or expired or its account is disabled.
+#[cfg(feature = "server")]
+pub async fn authenticate_api_token(token: &str) -> Result<Option<(CurrentUser, Vec<ApiScope>)>, ServerFnError> {
+    let token_hash = hash_token(token);
+    let now = Utc::now();
+    let found = match create_connection().await {
+        Ok(mut conn) => sqlx::query_as::<_, (i32, String)>(
+            "UPDATE api_tokens SET last_used_at = ?1
+            WHERE token_hash = ?2 AND (expires_at IS NULL OR datetime(expires_at) > datetime(?1))
+            RETURNING account_id, scopes",
+        )
+        .bind(now)
+        .bind(&token_hash)
+        .fetch_optional(&mut conn)
+        .await
+        .map_err(|e| {
+            error!("Error checking API token: {e}");
+            ServerFnError::new(format!("Error checking API token: {e}"))
+        })?,
+        Err(e) => {
+            return Err(ServerFnError::new(format!(
+                "Database connection error: {e}"
+            )))
+        }
+    };
+    let Some((account_id, scopes)) = found else {
+        warn!("Unknown or expired API token used");
+        return Ok(None);
+    };
+
+    match get_account_by_id(account_id).await? {
+        Some(account) if account.is_active => {
+            Ok(Some((CurrentUser::for_account(account).await?, parse_scopes(&scopes))))
+        }
+        _ => {
+            warn!("API token of disabled or deleted account {account_id} used");
+            Ok(None)
+        }
+    }
+}
+
+/// An API token joined with its owner's username
+#[cfg(feature = "server")]
+#[derive(FromRow)]
+struct ApiTokenRow {
+    id: i32,
+    account_id: i32,
+    owner: String,
+    name: String,
+    token_prefix: String,
+    scopes: String,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "server")]
+impl ApiTokenRow {
+    fn into_api_token(self) -> ApiToken {
+        ApiToken {
+            id: self.id,
+            account_id: self.account_id,
+            owner: self.owner,
+            name: self.name,
+            token_prefix: self.token_prefix,
+            scopes: parse_scopes(&self.scopes),
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+            last_used_at: self.last_used_at,
+        }
+    }
+}
+
+/// Parses the scopes stored in `api_tokens.scopes`, skipping any that no longer exist
+#[cfg(feature = "server")]
+fn parse_scopes(scopes: &str) -> Vec<ApiScope> {
+    scopes.split_whitespace().filter_map(ApiScope::from_name).collect()
+}
+
+/// Formats scopes for storing in `api_tokens.scopes`
+#[cfg(feature = "server")]
+fn format_scopes(scopes: &[ApiScope]) -> String {
+    scopes.iter().map(ApiScope::as_str).collect::<Vec<_>>().join(" ")
+}
+
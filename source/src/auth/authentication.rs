@@ -1,20 +1,22 @@
 #[cfg(feature = "server")]
-use crate::database::create_connection;
 use crate::{
-    auth::{create_session, get_session, Session},
-    models::{get_account_by_id, get_account_by_username, Account},
+    auth::{
+        check_login_allowed, clear_session_cookie, create_session, get_session, invalidate_session,
+        read_session_cookie, record_login_attempt, set_session_cookie, start_two_factor_challenge,
+        LoginDecision, SessionConfig,
+    },
+    client_info::{client_ip, client_user_agent},
+    database::create_connection,
+    models::{get_account_by_id, get_account_by_username, Account, LoginOutcome},
 };
 #[cfg(feature = "server")]
-use argon2::{password_hash::Salt, Argon2, PasswordHasher, PasswordVerifier};
-use dioxus::{
-    logger::tracing::{error, info, warn},
-    prelude::*,
-};
+use argon2::{Argon2, PasswordHasher, PasswordVerifier};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info, warn};
+use dioxus::prelude::*;
 #[cfg(feature = "server")]
 use password_hash::SaltString;
 use serde::{Deserialize, Serialize};
-#[cfg(feature = "server")]
-use sqlx::{FromRow, Row};
 
 /// Current user information for the session
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
@@ -22,7 +24,10 @@ pub struct CurrentUser {
     pub account_id: i32,
     pub username: String,
     pub role_id: i32,
-    pub session_id: String,
+    pub two_factor_enabled: bool,
+    /// Set for admins without two-factor authentication while it is required for them.
+    /// Such an admin can't use the admin role until it has been set up.
+    pub two_factor_setup_required: bool,
 }
 
 /// Login request structure
@@ -37,111 +42,182 @@ pub struct LoginRequest {
 pub struct LoginResponse {
     pub success: bool,
     pub message: String,
-    pub session_id: Option<String>,
+    /// The password was right and the account has two-factor authentication enabled, so
+    /// the login has to be finished with `verify_two_factor_login`
+    #[serde(default)]
+    pub two_factor_required: bool,
     pub user: Option<CurrentUser>,
 }
 
-// TODO: This is a placeholder for session validation logic.
-// In a real application, you would check if the user is logged in.
+/// Validates the session cookie sent with the request and resolves its user
+///
+/// The session must be active, unexpired, within its maximum lifetime and belong to an
+/// account that hasn't been disabled. Using it slides its expiry forward when
+/// `SessionConfig::extend_on_access` is set, and the cookie is refreshed to match.
+///
+/// # Returns
+/// A `Result` containing either `Some(CurrentUser)` for a valid session, `None` when there
+/// is no valid session, or a `ServerFnError` if an error occurs.
 #[server]
-pub async fn validate_session() -> Result<bool, ServerFnError> {
-    warn!("This feature is not yet implemented and is returning true every time");
-    Ok(true)
-}
+pub async fn validate_session() -> Result<Option<CurrentUser>, ServerFnError> {
+    let Some(session_id) = read_session_cookie() else {
+        return Ok(None);
+    };
+    let Some(session) = get_session(session_id).await? else {
+        info!("Session cookie does not match a valid session");
+        return Ok(None);
+    };
 
-/// Get the current user from session
-#[server]
-pub async fn get_current_user(session_id: String) -> Result<Option<CurrentUser>, ServerFnError> {
-    match get_session(session_id.clone()).await? {
-        Some(session) => {
-            // Get the account details by ID
-            match get_account_by_id(session.account_id).await? {
-                Some(account) => Ok(Some(CurrentUser {
-                    account_id: account.account_id.unwrap_or(0),
-                    username: account.username,
-                    role_id: account.role_id,
-                    session_id,
-                })),
-                None => Ok(None),
+    match get_account_by_id(session.account_id).await? {
+        Some(account) if !account.is_active => {
+            warn!("Session {} belongs to a disabled account", session.session_id);
+            Ok(None)
+        }
+        Some(account) => {
+            if SessionConfig::default().extend_on_access {
+                set_session_cookie(&session.session_id, session.expires_at);
             }
+            Ok(Some(CurrentUser::for_account(account).await?))
+        }
+        None => {
+            warn!("Session {} belongs to a deleted account", session.session_id);
+            Ok(None)
         }
-        None => Ok(None),
     }
 }
 
-/// Login function that creates a session
+/// Login function that creates a session and stores its ID in an HttpOnly session cookie
+///
+/// When the account has two-factor authentication enabled no session is created yet.
+/// The response has `two_factor_required` set instead, and the login is finished by
+/// `verify_two_factor_login`.
+///
+/// The client's IP address and user agent are taken from the request, see
+/// [`crate::client_info`], so they can be relied on when auditing sessions.
 #[server]
-pub async fn login_with_session(
-    username: String,
-    password: String,
-    ip_address: Option<String>,
-    user_agent: Option<String>,
-) -> Result<LoginResponse, ServerFnError> {
-    match get_account_by_username(username.clone()).await? {
-        Some(account) => {
-            // Verify the password
-            let is_valid = verify_password_hash(password, account.password_hash).await?;
-            if is_valid {
-                // Create a new session
-                let session =
-                    create_session(account.account_id.unwrap_or(0), ip_address, user_agent).await?;
-
-                info!("Login successful for user: {}", account.username);
-
-                Ok(LoginResponse {
-                    success: true,
-                    message: "Login successful".to_string(),
-                    session_id: Some(session.session_id.clone()),
-                    user: Some(CurrentUser {
-                        account_id: account.account_id.unwrap_or(0),
-                        username: account.username,
-                        role_id: account.role_id,
-                        session_id: session.session_id,
-                    }),
-                })
-            } else {
-                warn!("Invalid password attempt for user: {}", username);
-                Ok(LoginResponse {
-                    success: false,
-                    message: "Invalid credentials".to_string(),
-                    session_id: None,
-                    user: None,
-                })
-            }
-        }
-        None => {
-            warn!("Login attempt for non-existent user: {}", username);
+pub async fn login_with_session(username: String, password: String) -> Result<LoginResponse, ServerFnError> {
+    let ip_address = client_ip().await;
+    let user_agent = client_user_agent();
+    match authenticate(&username, password, ip_address.as_deref(), user_agent.as_deref()).await? {
+        Authentication::Success(account) if account.totp_enabled => {
+            start_two_factor_challenge(account.account_id.unwrap_or(0), ip_address, user_agent).await?;
+            info!("Password accepted for {}, waiting for two-factor code", account.username);
+
             Ok(LoginResponse {
                 success: false,
-                message: "Invalid credentials".to_string(),
-                session_id: None,
+                message: "Enter the code from your authenticator app or a recovery code".to_string(),
+                two_factor_required: true,
                 user: None,
             })
         }
+        Authentication::Success(account) => {
+            // Create a new session
+            let session =
+                create_session(account.account_id.unwrap_or(0), ip_address, user_agent).await?;
+
+            set_session_cookie(&session.session_id, session.expires_at);
+            info!("Login successful for user: {}", account.username);
+
+            Ok(LoginResponse {
+                success: true,
+                message: "Login successful".to_string(),
+                two_factor_required: false,
+                user: Some(CurrentUser::for_account(account).await?),
+            })
+        }
+        Authentication::Rejected(message) => Ok(LoginResponse {
+            success: false,
+            message,
+            two_factor_required: false,
+            user: None,
+        }),
+    }
+}
+
+/// Logs out by invalidating the session from the request cookie and clearing the cookie
+#[server]
+pub async fn logout() -> Result<(), ServerFnError> {
+    if let Some(session_id) = read_session_cookie() {
+        invalidate_session(session_id).await?;
     }
+    clear_session_cookie();
+    info!("Session cookie cleared");
+    Ok(())
 }
 
-// TODO: obviously this is not secure, I'll be coming back to this later
-// and adding proper auth and session managment later
-// but don't need to worry about that when I have no actual functionality
-// TODO: Should also log bad login attempts as well
+/// Checks a username and password without creating a session. Attempts are recorded
+/// and throttled the same way as [`login_with_session`].
 #[server]
 pub async fn validate_login(username: String, password: String) -> Result<bool, ServerFnError> {
-    match get_account_by_username(username).await? {
-        Some(account) => {
-            // Use the verify_password_hash function to check the password
-            let is_valid = verify_password_hash(password, account.password_hash).await?;
-            if is_valid {
-                info!("Login successful for user: {}", account.username);
-                Ok(true)
-            } else {
-                warn!("Invalid password attempt for user: {}", account.username);
-                Ok(false)
-            }
+    let ip_address = client_ip().await;
+    let user_agent = client_user_agent();
+    match authenticate(&username, password, ip_address.as_deref(), user_agent.as_deref()).await? {
+        Authentication::Success(_) => Ok(true),
+        Authentication::Rejected(_) => Ok(false),
+    }
+}
+
+/// Result of checking a username and password
+#[cfg(feature = "server")]
+enum Authentication {
+    Success(Account),
+    /// The login was refused, with the message to show
+    Rejected(String),
+}
+
+/// Checks a username and password, refusing without checking the password when the
+/// account is locked or the attempt is throttled, and records the attempt. A correct
+/// password for an account with two-factor authentication isn't recorded as a success
+/// until its code has been checked too.
+#[cfg(feature = "server")]
+async fn authenticate(
+    username: &str,
+    password: String,
+    ip_address: Option<&str>,
+    user_agent: Option<&str>,
+) -> Result<Authentication, ServerFnError> {
+    let mut conn = match create_connection().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("Database connection error: {e}");
+            return Err(ServerFnError::new(format!("Database connection error: {e}")));
         }
+    };
+
+    let decision = check_login_allowed(&mut conn, username, ip_address).await?;
+    if decision != LoginDecision::Allowed {
+        warn!("Refusing login for {username} from {ip_address:?}: {decision:?}");
+        record_login_attempt(&mut conn, username, None, ip_address, user_agent, decision.outcome()).await?;
+        return Ok(Authentication::Rejected(decision.message()));
+    }
+
+    let account = get_account_by_username(username.to_string()).await?;
+    let is_valid = match &account {
+        Some(account) => verify_password_hash(password, account.password_hash.clone()).await?,
         None => {
-            warn!("Login attempt for non-existent user");
-            Ok(false)
+            warn!("Login attempt for non-existent user: {username}");
+            false
+        }
+    };
+    let account_id = account.as_ref().and_then(|account| account.account_id);
+
+    match account {
+        Some(account) if is_valid && !account.is_active => {
+            warn!("Login attempt for disabled account: {username}");
+            record_login_attempt(&mut conn, username, account_id, ip_address, user_agent, LoginOutcome::Failure).await?;
+            Ok(Authentication::Rejected("This account has been disabled".to_string()))
+        }
+        Some(account) if is_valid && account.totp_enabled => Ok(Authentication::Success(account)),
+        Some(account) if is_valid => {
+            record_login_attempt(&mut conn, username, account_id, ip_address, user_agent, LoginOutcome::Success).await?;
+            Ok(Authentication::Success(account))
+        }
+        _ => {
+            if account_id.is_some() {
+                warn!("Invalid password attempt for user: {username}");
+            }
+            record_login_attempt(&mut conn, username, account_id, ip_address, user_agent, LoginOutcome::Failure).await?;
+            Ok(Authentication::Rejected("Invalid credentials".to_string()))
         }
     }
 }
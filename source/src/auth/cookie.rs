@@ -0,0 +1,99 @@
+use axum::http::{
+    header::{COOKIE, SET_COOKIE},
+    HeaderValue,
+};
+use chrono::{DateTime, Utc};
+use dioxus::logger::tracing::error;
+use dioxus::prelude::*;
+
+/// Name of the cookie holding the session ID
+pub const SESSION_COOKIE_NAME: &str = "session_id";
+
+/// Name of the cookie holding the token of a login waiting for its two-factor code
+pub const TWO_FACTOR_COOKIE_NAME: &str = "two_factor_challenge";
+
+/// Reads the session ID from the cookie sent with the current request
+///
+/// # Returns
+/// The session ID, or `None` when the request carries no session cookie.
+pub fn read_session_cookie() -> Option<String> {
+    read_cookie(SESSION_COOKIE_NAME)
+}
+
+/// Sets the session cookie on the response to the current request
+///
+/// The cookie is HttpOnly so scripts can't read it, Secure so it's only sent over HTTPS
+/// (browsers make an exception for localhost), and SameSite=Strict so it isn't sent with
+/// requests started by other sites. It expires along with the session, so it is set
+/// again whenever the session's expiry slides forward.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session
+/// * `expires_at` - When the session expires
+pub fn set_session_cookie(session_id: &str, expires_at: DateTime<Utc>) {
+    set_cookie(SESSION_COOKIE_NAME, session_id, expires_at);
+}
+
+/// Tells the browser to delete the session cookie
+pub fn clear_session_cookie() {
+    clear_cookie(SESSION_COOKIE_NAME);
+}
+
+/// Reads the two-factor challenge token from the cookie sent with the current request
+pub fn read_two_factor_cookie() -> Option<String> {
+    read_cookie(TWO_FACTOR_COOKIE_NAME)
+}
+
+/// Sets the two-factor challenge cookie, with the same attributes as the session cookie
+///
+/// # Arguments
+/// * `token` - The token of the challenge
+/// * `expires_at` - When the challenge expires
+pub fn set_two_factor_cookie(token: &str, expires_at: DateTime<Utc>) {
+    set_cookie(TWO_FACTOR_COOKIE_NAME, token, expires_at);
+}
+
+/// Tells the browser to delete the two-factor challenge cookie
+pub fn clear_two_factor_cookie() {
+    clear_cookie(TWO_FACTOR_COOKIE_NAME);
+}
+
+fn read_cookie(cookie_name: &str) -> Option<String> {
+    let context = server_context();
+    let request = context.request_parts();
+    request
+        .headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .flat_map(|header| header.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == cookie_name)
+        .map(|(_, value)| value.to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn set_cookie(name: &str, value: &str, expires_at: DateTime<Utc>) {
+    let max_age = (expires_at - Utc::now()).num_seconds().max(0);
+    append_set_cookie(format!(
+        "{name}={value}; Path=/; Max-Age={max_age}; HttpOnly; Secure; SameSite=Strict"
+    ));
+}
+
+fn clear_cookie(name: &str) {
+    append_set_cookie(format!(
+        "{name}=; Path=/; Max-Age=0; HttpOnly; Secure; SameSite=Strict"
+    ));
+}
+
+fn append_set_cookie(cookie: String) {
+    match HeaderValue::from_str(&cookie) {
+        Ok(value) => {
+            server_context()
+                .response_parts_mut()
+                .headers
+                .append(SET_COOKIE, value);
+        }
+        Err(e) => error!("Invalid cookie header: {e}"),
+    }
+}
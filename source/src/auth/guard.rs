@@ -0,0 +1,203 @@
+#[cfg(feature = "server")]
+use crate::{
+    auth::{authenticate_api_token, read_bearer_token, validate_session, ApiScope, CurrentUser},
+    models::Role,
+};
+#[cfg(feature = "server")]
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::warn;
+#[cfg(feature = "server")]
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Header a rejected server function sets so [`apply_error_status`] can give the
+/// response the matching status code. It is removed before the response is sent.
+#[cfg(feature = "server")]
+const ERROR_STATUS_HEADER: &str = "x-error-status";
+
+/// Why a request was refused by [`require_role`]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum AuthError {
+    /// No valid session came with the request (401)
+    Unauthorized,
+    /// The session's account doesn't have the required role (403)
+    Forbidden,
+    /// The account is an admin that has to set up two-factor authentication before it
+    /// can use the admin role (403)
+    TwoFactorSetupRequired,
+}
+
+impl AuthError {
+    /// The HTTP status code the rejected request is answered with
+    pub fn status_code(&self) -> u16 {
+        match self {
+            AuthError::Unauthorized => 401,
+            AuthError::Forbidden | AuthError::TwoFactorSetupRequired => 403,
+        }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Unauthorized => write!(f, "Not logged in or session expired"),
+            AuthError::Forbidden => write!(f, "You do not have permission to do that"),
+            AuthError::TwoFactorSetupRequired => {
+                write!(f, "Two-factor authentication must be set up before doing that")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Resolves the current session from the request cookie and ensures its account has at
+/// least the given role. Roles are ordered from `Admin` down to `Guest`, so requiring
+/// `Role::User` also admits admins. While two-factor authentication is required for
+/// admins, requiring `Role::Admin` also refuses admins that haven't set it up yet.
+///
+/// Every server function that changes data calls this, or [`require_scope`] if it can
+/// be used with an API token, before doing anything else. Requests authenticated with an
+/// API token are refused.
+///
+/// # Arguments
+/// * `role` - The least privileged role allowed to make the call
+///
+/// # Returns
+/// A `Result` containing the logged in `CurrentUser`, or a `ServerFnError` carrying an
+/// [`AuthError`] when there is no session (401) or the role is insufficient (403).
+///
+/// # Examples
+/// ```ignore
+/// let user = require_role(Role::Admin).await?;
+/// ```
+#[cfg(feature = "server")]
+pub async fn require_role(role: Role) -> Result<CurrentUser, ServerFnError> {
+    authorize(role, None).await
+}
+
+/// Like [`require_role`], but also accepts an API token sent as a bearer token in the
+/// `Authorization` header, as long as the token was granted `scope` and its account has
+/// the role. When a bearer token is sent the session cookie is ignored.
+///
+/// Server functions calling this only document the session they need. API tokens with
+/// the scope passed here are accepted by them as well, see [`ApiScope`] for what each
+/// scope covers.
+///
+/// # Arguments
+/// * `role` - The least privileged role allowed to make the call
+/// * `scope` - The scope an API token needs to make the call
+///
+/// # Examples
+/// ```ignore
+/// let user = require_scope(Role::Admin, ApiScope::PostsWrite).await?;
+/// ```
+#[cfg(feature = "server")]
+pub async fn require_scope(role: Role, scope: ApiScope) -> Result<CurrentUser, ServerFnError> {
+    authorize(role, Some(scope)).await
+}
+
+/// Checks the caller's session or API token against a role and, for API tokens, a scope
+#[cfg(feature = "server")]
+async fn authorize(role: Role, scope: Option<ApiScope>) -> Result<CurrentUser, ServerFnError> {
+    let user = match read_bearer_token() {
+        Some(token) => {
+            let Some((user, scopes)) = authenticate_api_token(&token).await? else {
+                return Err(reject(AuthError::Unauthorized));
+            };
+            match scope {
+                Some(scope) if scopes.contains(&scope) => Some(user),
+                Some(scope) => {
+                    warn!("API token of {} used without the {} scope", user.username, scope.as_str());
+                    return Err(reject(AuthError::Forbidden));
+                }
+                None => {
+                    warn!("API token of {} used for an action that needs a session", user.username);
+                    return Err(reject(AuthError::Forbidden));
+                }
+            }
+        }
+        None => validate_session().await?,
+    };
+
+    match user {
+        Some(user) if role == Role::Admin && user.two_factor_setup_required => {
+            warn!("Admin {} has not set up required two-factor authentication", user.username);
+            Err(reject(AuthError::TwoFactorSetupRequired))
+        }
+        Some(user) if user.role_id <= role as i32 => Ok(user),
+        Some(user) => {
+            warn!("User {} attempted an action requiring the {role:?} role", user.username);
+            Err(reject(AuthError::Forbidden))
+        }
+        None => {
+            warn!("Action requiring the {role:?} role attempted without a valid session");
+            Err(reject(AuthError::Unauthorized))
+        }
+    }
+}
+
+/// Marks the response with the error's status and converts it into a `ServerFnError`
+#[cfg(feature = "server")]
+fn reject(error: AuthError) -> ServerFnError {
+    server_context()
+        .response_parts_mut()
+        .headers
+        .insert(ERROR_STATUS_HEADER, HeaderValue::from(error.status_code()));
+    error.into()
+}
+
+/// Fails a server function call because of a problem with the request rather than with
+/// the server, such as invalid input (422), a conflict with existing data (409) or
+/// something that doesn't exist (404). The call is answered with that status instead of
+/// the generic 500, and the app shows the message like any other error.
+///
+/// # Arguments
+/// * `status` - The 4xx status to answer with
+/// * `message` - What was wrong with the request
+///
+/// # Examples
+/// ```ignore
+/// return Err(request_error(StatusCode::UNPROCESSABLE_ENTITY, "A scheduled post needs a publish date"));
+/// ```
+#[cfg(feature = "server")]
+pub fn request_error(status: StatusCode, message: impl Into<String>) -> ServerFnError {
+    server_context()
+        .response_parts_mut()
+        .headers
+        .insert(ERROR_STATUS_HEADER, HeaderValue::from(status.as_u16()));
+    ServerFnError::new(message.into())
+}
+
+/// Axum middleware that answers server function calls rejected by [`require_role`] or
+/// [`request_error`] with their 4xx status instead of the generic 500 used for server
+/// function errors
+#[cfg(feature = "server")]
+pub async fn apply_error_status(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    if let Some(status) = error_status(response.headers()) {
+        *response.status_mut() = status;
+    }
+    response.headers_mut().remove(ERROR_STATUS_HEADER);
+    response
+}
+
+/// Returns the status a server function call was rejected with by [`require_role`],
+/// [`require_scope`] or [`request_error`], from the response headers of its server
+/// context
+#[cfg(feature = "server")]
+pub fn error_status(headers: &HeaderMap) -> Option<StatusCode> {
+    headers
+        .get(ERROR_STATUS_HEADER)?
+        .to_str()
+        .ok()
+        .and_then(|status| status.parse::<u16>().ok())
+        .and_then(|status| StatusCode::from_u16(status).ok())
+}
@@ -1,6 +1,43 @@
+mod api_token;
+pub use api_token::*;
+
 mod authentication;
 pub use authentication::*;
 
+#[cfg(feature = "server")]
+mod cookie;
+#[cfg(feature = "server")]
+pub use cookie::*;
+
+#[cfg(feature = "server")]
+mod csrf;
+#[cfg(feature = "server")]
+pub use csrf::*;
+
+mod guard;
+pub use guard::*;
+
+#[cfg(feature = "server")]
+mod token;
+#[cfg(feature = "server")]
+pub use token::*;
+
+#[cfg(feature = "server")]
+mod throttle;
+#[cfg(feature = "server")]
+pub use throttle::*;
+
+mod password;
+pub use password::*;
+
 mod session;
 pub use session::*;
 
+mod session_management;
+pub use session_management::*;
+
+mod setup;
+pub use setup::*;
+
+mod two_factor;
+pub use two_factor::*;
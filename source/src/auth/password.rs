@@ -0,0 +1,317 @@
+/// Changing and resetting passwords
+///
+/// Logged in users can change their own password by confirming the current one. Admins
+/// can issue a password reset token for any account, either to share the link themselves
+/// or to have it emailed through the configured [`crate::mailer`]. Tokens expire after
+/// [`RESET_TOKEN_HOURS`] and can only be used once. Only a SHA-256 hash of each token is
+/// stored, so the link can't be rebuilt from the database.
+#[cfg(feature = "server")]
+use crate::{
+    auth::{
+        create_session, generate_token, get_session, hash_password, hash_token,
+        invalidate_all_user_sessions, read_session_cookie, require_role, set_session_cookie,
+        verify_password_hash,
+    },
+    client_info::{client_ip, client_user_agent},
+    database::create_connection,
+    feeds::site_url,
+    mailer::{configured_mailer, validate_address, Email},
+    models::{get_account_by_id, Role},
+};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "server")]
+use chrono::Duration;
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info, warn};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Shortest password accepted when changing or resetting a password
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// How long a password reset token can be used for
+#[cfg(feature = "server")]
+pub const RESET_TOKEN_HOURS: i64 = 24;
+
+/// A password reset token that has just been issued
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct PasswordResetIssued {
+    /// The link to share with the account's owner. Left out when the link was emailed,
+    /// so it only ever reaches the recipient.
+    pub reset_url: Option<String>,
+    /// Where the link was emailed to, if it was
+    pub emailed_to: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Checks that a new password is acceptable
+///
+/// # Returns
+/// `None` when the password is fine, or the reason it isn't.
+pub fn password_problem(password: &str) -> Option<String> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Some(format!(
+            "Passwords must be at least {MIN_PASSWORD_LENGTH} characters long"
+        ));
+    }
+    None
+}
+
+/// Changes the password of the logged in account after checking its current password.
+/// Every other session of the account is logged out, and the caller gets a new session.
+///
+/// # Arguments
+/// * `current_password` - The account's password as it is now
+/// * `new_password` - The password to change it to
+#[server]
+pub async fn change_password(current_password: String, new_password: String) -> Result<(), ServerFnError> {
+    let user = require_role(Role::Guest).await?;
+    if let Some(problem) = password_problem(&new_password) {
+        return Err(ServerFnError::new(problem));
+    }
+
+    let Some(account) = get_account_by_id(user.account_id).await? else {
+        return Err(ServerFnError::new("Account not found"));
+    };
+    if current_password.is_empty()
+        || !verify_password_hash(current_password, account.password_hash).await?
+    {
+        warn!("Wrong current password changing the password of {}", user.username);
+        return Err(ServerFnError::new("The current password is wrong"));
+    }
+
+    store_password(user.account_id, new_password).await?;
+
+    // Keep the caller logged in with a fresh session once the others are gone
+    let current_session = match read_session_cookie() {
+        Some(session_id) => get_session(session_id).await?,
+        None => None,
+    };
+    invalidate_all_user_sessions(user.account_id).await?;
+    let (ip_address, user_agent) = match current_session {
+        Some(session) => (session.ip_address, session.user_agent),
+        None => (client_ip().await, client_user_agent()),
+    };
+    let session = create_session(user.account_id, ip_address, user_agent).await?;
+    set_session_cookie(&session.session_id, session.expires_at);
+
+    info!("{} changed their password", user.username);
+    Ok(())
+}
+
+/// Issues a password reset token for an account, replacing any unused ones. The caller
+/// must hold an active admin session.
+///
+/// # Arguments
+/// * `account_id` - The account whose password can be reset with the token
+/// * `send_to` - An email address to send the reset link to. Without one the link is
+///   returned for the admin to share.
+///
+/// # Returns
+/// A `Result` containing the `PasswordResetIssued`, or a `ServerFnError` if the link
+/// should be emailed but no mailer is configured, or another error occurs.
+#[server]
+pub async fn create_password_reset(
+    account_id: i32,
+    send_to: Option<String>,
+) -> Result<PasswordResetIssued, ServerFnError> {
+    let user = require_role(Role::Admin).await?;
+    let send_to = send_to
+        .map(|address| address.trim().to_string())
+        .filter(|address| !address.is_empty());
+    let mailer = match &send_to {
+        Some(address) => match configured_mailer() {
+            Some(_) if validate_address(address).is_err() => {
+                return Err(ServerFnError::new(format!("{address:?} is not an email address")))
+            }
+            Some(mailer) => Some(mailer),
+            None => return Err(ServerFnError::new("Sending email isn't set up, share the link instead")),
+        },
+        None => None,
+    };
+
+    let Some(account) = get_account_by_id(account_id).await? else {
+        return Err(ServerFnError::new(format!("Account {account_id} not found")));
+    };
+
+    let token = generate_token();
+    let token_hash = hash_token(&token);
+    let now = Utc::now();
+    let expires_at = now + Duration::hours(RESET_TOKEN_HOURS);
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            sqlx::query!(
+                "UPDATE password_reset_tokens SET used_at = ?1 WHERE account_id = ?2 AND used_at IS NULL",
+                now,
+                account_id
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error revoking password reset tokens for account {account_id}: {e}");
+                ServerFnError::new(format!("Error creating password reset: {e}"))
+            })?;
+
+            sqlx::query!(
+                "INSERT INTO password_reset_tokens (account_id, token_hash, created_by, created_at, expires_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                account_id,
+                token_hash,
+                user.account_id,
+                now,
+                expires_at
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error storing password reset token for account {account_id}: {e}");
+                ServerFnError::new(format!("Error creating password reset: {e}"))
+            })?;
+        }
+        Err(e) => {
+            return Err(ServerFnError::new(format!(
+                "Database connection error: {e}"
+            )))
+        }
+    }
+
+    let reset_url = format!(
+        "{}/reset-password?token={token}",
+        site_url(&server_context().request_parts().headers)
+    );
+    info!("{} issued a password reset for {}", user.username, account.username);
+
+    match (mailer, send_to) {
+        (Some(mailer), Some(address)) => {
+            let email = Email {
+                to: address.clone(),
+                subject: "Reset your password".to_string(),
+                body: format!(
+                    "A password reset was requested for your account {}.\n\n\
+                    Open this link to choose a new password:\n{reset_url}\n\n\
+                    The link can be used once and expires in {RESET_TOKEN_HOURS} hours.\n",
+                    account.username
+                ),
+            };
+            mailer.send(&email).await.map_err(|e| {
+                error!("Error emailing password reset for {}: {e}", account.username);
+                ServerFnError::new(format!("{e}"))
+            })?;
+            Ok(PasswordResetIssued {
+                reset_url: None,
+                emailed_to: Some(address),
+                expires_at,
+            })
+        }
+        _ => Ok(PasswordResetIssued {
+            reset_url: Some(reset_url),
+            emailed_to: None,
+            expires_at,
+        }),
+    }
+}
+
+/// Sets a new password with a password reset token. The token is used up, every session
+/// of the account is logged out and a lockout from failed logins is lifted.
+///
+/// # Arguments
+/// * `token` - The token from the reset link
+/// * `new_password` - The new password
+#[server]
+pub async fn reset_password(token: String, new_password: String) -> Result<(), ServerFnError> {
+    if let Some(problem) = password_problem(&new_password) {
+        return Err(ServerFnError::new(problem));
+    }
+
+    let token_hash = hash_token(token.trim());
+    let now = Utc::now();
+    let account_id = match create_connection().await {
+        Ok(mut conn) => {
+            // Claim the token in one statement so it can't be used twice concurrently
+            sqlx::query_scalar::<_, i32>(
+                "UPDATE password_reset_tokens SET used_at = ?1
+                WHERE token_hash = ?2 AND used_at IS NULL AND datetime(expires_at) > datetime(?1)
+                RETURNING account_id",
+            )
+            .bind(now)
+            .bind(&token_hash)
+            .fetch_optional(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error using password reset token: {e}");
+                ServerFnError::new(format!("Error resetting password: {e}"))
+            })?
+        }
+        Err(e) => {
+            return Err(ServerFnError::new(format!(
+                "Database connection error: {e}"
+            )))
+        }
+    };
+    let Some(account_id) = account_id else {
+        warn!("Invalid or expired password reset token used");
+        return Err(ServerFnError::new("This reset link is invalid or has expired"));
+    };
+
+    store_password(account_id, new_password).await?;
+    invalidate_all_user_sessions(account_id).await?;
+    info!("Password of account {account_id} reset with a reset token");
+    Ok(())
+}
+
+/// Deletes password reset tokens that have been used, replaced or have expired. Run
+/// periodically by the [`crate::scheduler`].
+///
+/// # Returns
+/// A `Result` containing the number of tokens deleted.
+#[cfg(feature = "server")]
+pub async fn delete_stale_password_resets() -> Result<u64, ServerFnError> {
+    let now = Utc::now();
+    match create_connection().await {
+        Ok(mut conn) => {
+            let result = sqlx::query!(
+                "DELETE FROM password_reset_tokens
+                WHERE used_at IS NOT NULL OR datetime(expires_at) < datetime(?1)",
+                now
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error deleting stale password reset tokens: {e}");
+                ServerFnError::new(format!("Error deleting stale password reset tokens: {e}"))
+            })?;
+            Ok(result.rows_affected())
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Hashes and stores a new password, lifting any lockout from failed logins. Doesn't check
+/// the caller or log out the account's sessions.
+#[cfg(feature = "server")]
+pub async fn store_password(account_id: i32, password: String) -> Result<(), ServerFnError> {
+    let password_hash = hash_password(password).await?;
+    match create_connection().await {
+        Ok(mut conn) => {
+            sqlx::query!(
+                "UPDATE accounts SET password_hash = ?1, locked_until = NULL WHERE account_id = ?2",
+                password_hash,
+                account_id
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error storing password of account {account_id}: {e}");
+                ServerFnError::new(format!("Error storing password: {e}"))
+            })?;
+            Ok(())
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
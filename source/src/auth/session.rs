@@ -1,11 +1,20 @@
-use dioxus::prelude::{ServerFnError, *};
-use dioxus::logger::tracing::{info, warn, error};
+/// Login sessions stored in the `sessions` table
+///
+/// The functions here take session IDs and account IDs straight from their caller, so
+/// they are only available on the server and are never exposed as server functions.
+/// Client code goes through the session cookie instead.
+#[cfg(feature = "server")]
+use dioxus::prelude::ServerFnError;
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{info, error};
 use serde::{Serialize, Deserialize};
-use chrono::{DateTime, Utc, Duration};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "server")]
+use chrono::Duration;
 #[cfg(feature = "server")]
 use crate::database::create_connection;
 #[cfg(feature = "server")]
-use sqlx::{FromRow, Row};
+use sqlx::FromRow;
 #[cfg(feature = "server")]
 use uuid::Uuid;
 
@@ -25,9 +34,14 @@ pub struct Session {
 
 /// Session configuration
 pub struct SessionConfig {
+    /// How long a session stays valid without being used
     pub duration_hours: i64,
+    /// Whether using a session pushes its expiry back by `duration_hours`
     pub extend_on_access: bool,
+    /// Whether the background scheduler periodically deletes expired and inactive sessions
     pub cleanup_expired: bool,
+    /// How long a session can live in total, however often it is used
+    pub max_lifetime_hours: i64,
 }
 
 impl Default for SessionConfig {
@@ -36,12 +50,21 @@ impl Default for SessionConfig {
             duration_hours: 24, // 24 hours default
             extend_on_access: true,
             cleanup_expired: true,
+            max_lifetime_hours: 24 * 7, // 7 days default
         }
     }
 }
 
+impl SessionConfig {
+    /// Sessions created before this time have outlived `max_lifetime_hours`
+    #[cfg(feature = "server")]
+    pub fn oldest_valid_creation(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::hours(self.max_lifetime_hours)
+    }
+}
+
 /// Create a new session for a user
-#[server]
+#[cfg(feature = "server")]
 pub async fn create_session(
     account_id: i32,
     ip_address: Option<String>,
@@ -99,34 +122,43 @@ pub async fn create_session(
 }
 
 /// Validate and retrieve a session
-#[server]
+///
+/// A session is valid while it is active, hasn't expired and is younger than the
+/// configured maximum lifetime. When `extend_on_access` is set its expiry slides forward
+/// by `duration_hours`, but never past the end of its maximum lifetime, and the returned
+/// session carries the new expiry.
+#[cfg(feature = "server")]
 pub async fn get_session(session_id: String) -> Result<Option<Session>, ServerFnError> {
     let config = SessionConfig::default();
-    
+    let now = Utc::now();
+
     match create_connection().await {
         Ok(mut conn) => {
-            // First, clean up expired sessions if configured
-            if config.cleanup_expired {
-                let _ = cleanup_expired_sessions().await;
-            }
-
             let result = sqlx::query_as::<_, Session>(
                 r#"
                 SELECT session_id, account_id, created_at, expires_at, last_accessed, ip_address, user_agent, is_active
                 FROM sessions
-                WHERE session_id = ?1 AND is_active = 1 AND expires_at > CURRENT_TIMESTAMP
+                WHERE session_id = ?1 AND is_active = 1
+                    AND datetime(expires_at) > datetime(?2)
+                    AND datetime(created_at) > datetime(?3)
                 "#
             )
             .bind(&session_id)
+            .bind(now)
+            .bind(config.oldest_valid_creation(now))
             .fetch_optional(&mut conn)
             .await;
 
             match result {
                 Ok(session_opt) => {
-                    if let Some(session) = session_opt {
-                        // Update last_accessed if configured
+                    if let Some(mut session) = session_opt {
+                        // Slide the expiry forward if configured, capped at the maximum lifetime
                         if config.extend_on_access {
-                            let _ = update_session_access(session_id.clone()).await;
+                            let expires_at = (now + Duration::hours(config.duration_hours))
+                                .min(session.created_at + Duration::hours(config.max_lifetime_hours));
+                            update_session_access(session_id, expires_at).await?;
+                            session.expires_at = expires_at;
+                            session.last_accessed = now;
                         }
                         Ok(Some(session))
                     } else {
@@ -146,18 +178,21 @@ pub async fn get_session(session_id: String) -> Result<Option<Session>, ServerFn
     }
 }
 
-/// Update session last accessed time
-#[server]
-pub async fn update_session_access(session_id: String) -> Result<(), ServerFnError> {
+/// Update session last accessed time and move its expiry
+#[cfg(feature = "server")]
+pub async fn update_session_access(session_id: String, expires_at: DateTime<Utc>) -> Result<(), ServerFnError> {
+    let now = Utc::now();
     match create_connection().await {
         Ok(mut conn) => {
             let result = sqlx::query!(
                 r#"
-                UPDATE sessions 
-                SET last_accessed = CURRENT_TIMESTAMP 
+                UPDATE sessions
+                SET last_accessed = ?2, expires_at = ?3
                 WHERE session_id = ?1 AND is_active = 1
                 "#,
-                session_id
+                session_id,
+                now,
+                expires_at
             )
             .execute(&mut conn)
             .await;
@@ -178,7 +213,7 @@ pub async fn update_session_access(session_id: String) -> Result<(), ServerFnErr
 }
 
 /// Invalidate a session (logout)
-#[server]
+#[cfg(feature = "server")]
 pub async fn invalidate_session(session_id: String) -> Result<(), ServerFnError> {
     match create_connection().await {
         Ok(mut conn) => {
@@ -212,7 +247,7 @@ pub async fn invalidate_session(session_id: String) -> Result<(), ServerFnError>
 }
 
 /// Invalidate all sessions for a user
-#[server]
+#[cfg(feature = "server")]
 pub async fn invalidate_all_user_sessions(account_id: i32) -> Result<(), ServerFnError> {
     match create_connection().await {
         Ok(mut conn) => {
@@ -245,16 +280,24 @@ pub async fn invalidate_all_user_sessions(account_id: i32) -> Result<(), ServerF
     }
 }
 
-/// Clean up expired sessions
-#[server]
+/// Clean up expired sessions, including those past their maximum lifetime. Run
+/// periodically by the [`crate::scheduler`] when `cleanup_expired` is set.
+#[cfg(feature = "server")]
 pub async fn cleanup_expired_sessions() -> Result<u64, ServerFnError> {
+    let config = SessionConfig::default();
+    let now = Utc::now();
+    let oldest_valid_creation = config.oldest_valid_creation(now);
     match create_connection().await {
         Ok(mut conn) => {
             let result = sqlx::query!(
                 r#"
-                DELETE FROM sessions 
-                WHERE expires_at < CURRENT_TIMESTAMP OR is_active = 0
-                "#
+                DELETE FROM sessions
+                WHERE datetime(expires_at) < datetime(?1)
+                    OR datetime(created_at) < datetime(?2)
+                    OR is_active = 0
+                "#,
+                now,
+                oldest_valid_creation
             )
             .execute(&mut conn)
             .await;
@@ -280,20 +323,57 @@ pub async fn cleanup_expired_sessions() -> Result<u64, ServerFnError> {
     }
 }
 
+/// Deletes every session, logging out every account
+///
+/// # Returns
+/// A `Result` containing the number of sessions deleted.
+#[cfg(feature = "server")]
+pub async fn delete_all_sessions() -> Result<u64, ServerFnError> {
+    match create_connection().await {
+        Ok(mut conn) => {
+            let result = sqlx::query!("DELETE FROM sessions")
+                .execute(&mut conn)
+                .await;
+
+            match result {
+                Ok(query_result) => {
+                    let deleted_count = query_result.rows_affected();
+                    info!("Deleted all {} sessions", deleted_count);
+                    Ok(deleted_count)
+                }
+                Err(e) => {
+                    error!("Failed to delete sessions: {}", e);
+                    Err(ServerFnError::new(format!("Failed to delete sessions: {}", e)))
+                }
+            }
+        }
+        Err(e) => {
+            error!("Database connection error: {}", e);
+            Err(ServerFnError::new(format!("Database connection error: {}", e)))
+        }
+    }
+}
+
 /// Get all active sessions for a user
-#[server]
+#[cfg(feature = "server")]
 pub async fn get_user_sessions(account_id: i32) -> Result<Vec<Session>, ServerFnError> {
+    let config = SessionConfig::default();
+    let now = Utc::now();
     match create_connection().await {
         Ok(mut conn) => {
             let result = sqlx::query_as::<_, Session>(
                 r#"
                 SELECT session_id, account_id, created_at, expires_at, last_accessed, ip_address, user_agent, is_active
                 FROM sessions
-                WHERE account_id = ?1 AND is_active = 1 AND expires_at > CURRENT_TIMESTAMP
+                WHERE account_id = ?1 AND is_active = 1
+                    AND datetime(expires_at) > datetime(?2)
+                    AND datetime(created_at) > datetime(?3)
                 ORDER BY last_accessed DESC
                 "#
             )
             .bind(account_id)
+            .bind(now)
+            .bind(config.oldest_valid_creation(now))
             .fetch_all(&mut conn)
             .await;
 
@@ -0,0 +1,238 @@
+/// Listing and revoking sessions
+///
+/// Session IDs are bearer credentials, so they never leave the server. Each session is
+/// identified in the UI by a handle derived from a SHA-256 hash of its ID instead, which
+/// can be used to revoke it but not to log in with.
+#[cfg(feature = "server")]
+use crate::{
+    auth::{
+        clear_session_cookie, get_user_sessions, invalidate_session, read_session_cookie,
+        require_role, SessionConfig,
+    },
+    client_info::describe_user_agent,
+    database::create_connection,
+    models::Role,
+};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info, warn};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "server")]
+use sqlx::FromRow;
+
+/// Number of sessions shown to admins in the list across all accounts
+#[cfg(feature = "server")]
+const ALL_SESSIONS_LIST_LIMIT: i64 = 100;
+
+/// A session as shown on the active sessions page
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ActiveSession {
+    /// Identifies the session for revoking it, without revealing its ID
+    pub handle: String,
+    pub account_id: i32,
+    pub username: String,
+    pub created_at: DateTime<Utc>,
+    pub last_accessed: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ip_address: Option<String>,
+    /// The browser and operating system, parsed from the user agent
+    pub device: String,
+    pub user_agent: Option<String>,
+    /// Whether this is the session the list was requested with
+    pub is_current: bool,
+}
+
+/// Lists the active sessions of the logged in account, most recently used first
+#[server]
+pub async fn get_my_sessions() -> Result<Vec<ActiveSession>, ServerFnError> {
+    let user = require_role(Role::Guest).await?;
+    let current_session_id = read_session_cookie();
+
+    let sessions = get_user_sessions(user.account_id).await?;
+    Ok(sessions
+        .into_iter()
+        .map(|session| SessionRow {
+            session_id: session.session_id,
+            account_id: session.account_id,
+            username: user.username.clone(),
+            created_at: session.created_at,
+            last_accessed: session.last_accessed,
+            expires_at: session.expires_at,
+            ip_address: session.ip_address,
+            user_agent: session.user_agent,
+        })
+        .map(|row| row.into_active_session(current_session_id.as_deref()))
+        .collect())
+}
+
+/// Signs out one of the logged in account's sessions. Revoking the current session logs
+/// the caller out.
+///
+/// # Arguments
+/// * `handle` - The handle of the session, from [`get_my_sessions`]
+#[server]
+pub async fn revoke_my_session(handle: String) -> Result<(), ServerFnError> {
+    let user = require_role(Role::Guest).await?;
+
+    let session = get_user_sessions(user.account_id)
+        .await?
+        .into_iter()
+        .find(|session| session_handle(&session.session_id) == handle);
+    let Some(session) = session else {
+        warn!("{} tried to revoke an unknown session", user.username);
+        return Err(ServerFnError::new("Session not found"));
+    };
+
+    if read_session_cookie().as_deref() == Some(session.session_id.as_str()) {
+        clear_session_cookie();
+    }
+    invalidate_session(session.session_id).await?;
+    info!("{} revoked one of their sessions", user.username);
+    Ok(())
+}
+
+/// Signs out every session of the logged in account except the current one
+///
+/// # Returns
+/// A `Result` containing the number of sessions signed out.
+#[server]
+pub async fn sign_out_other_sessions() -> Result<u64, ServerFnError> {
+    let user = require_role(Role::Guest).await?;
+    let current_session_id = read_session_cookie().unwrap_or_default();
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let result = sqlx::query!(
+                "UPDATE sessions SET is_active = 0 WHERE account_id = ?1 AND session_id != ?2 AND is_active = 1",
+                user.account_id,
+                current_session_id
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error signing out other sessions of {}: {e}", user.username);
+                ServerFnError::new(format!("Error signing out other sessions: {e}"))
+            })?;
+            info!("{} signed out {} other sessions", user.username, result.rows_affected());
+            Ok(result.rows_affected())
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Lists the active sessions of every account, most recently used first. The caller
+/// must hold an active admin session.
+#[server]
+pub async fn get_all_sessions() -> Result<Vec<ActiveSession>, ServerFnError> {
+    require_role(Role::Admin).await?;
+    let current_session_id = read_session_cookie();
+
+    let rows = load_all_sessions().await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| row.into_active_session(current_session_id.as_deref()))
+        .collect())
+}
+
+/// Signs out any account's session. The caller must hold an active admin session.
+///
+/// # Arguments
+/// * `handle` - The handle of the session, from [`get_all_sessions`]
+#[server]
+pub async fn revoke_any_session(handle: String) -> Result<(), ServerFnError> {
+    let user = require_role(Role::Admin).await?;
+
+    let session = load_all_sessions()
+        .await?
+        .into_iter()
+        .find(|row| session_handle(&row.session_id) == handle);
+    let Some(session) = session else {
+        return Err(ServerFnError::new("Session not found"));
+    };
+
+    if read_session_cookie().as_deref() == Some(session.session_id.as_str()) {
+        clear_session_cookie();
+    }
+    invalidate_session(session.session_id).await?;
+    info!("{} revoked a session of {}", user.username, session.username);
+    Ok(())
+}
+
+/// A session joined with its account's username
+#[cfg(feature = "server")]
+#[derive(FromRow)]
+struct SessionRow {
+    session_id: String,
+    account_id: i32,
+    username: String,
+    created_at: DateTime<Utc>,
+    last_accessed: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+}
+
+#[cfg(feature = "server")]
+impl SessionRow {
+    fn into_active_session(self, current_session_id: Option<&str>) -> ActiveSession {
+        ActiveSession {
+            handle: session_handle(&self.session_id),
+            is_current: current_session_id == Some(self.session_id.as_str()),
+            account_id: self.account_id,
+            username: self.username,
+            created_at: self.created_at,
+            last_accessed: self.last_accessed,
+            expires_at: self.expires_at,
+            ip_address: self.ip_address,
+            device: describe_user_agent(self.user_agent.as_deref().unwrap_or_default()),
+            user_agent: self.user_agent,
+        }
+    }
+}
+
+/// Loads the most recently used valid sessions across all accounts
+#[cfg(feature = "server")]
+async fn load_all_sessions() -> Result<Vec<SessionRow>, ServerFnError> {
+    let config = SessionConfig::default();
+    let now = Utc::now();
+    match create_connection().await {
+        Ok(mut conn) => sqlx::query_as::<_, SessionRow>(
+            "SELECT sessions.session_id, sessions.account_id, accounts.username, sessions.created_at,
+                sessions.last_accessed, sessions.expires_at, sessions.ip_address, sessions.user_agent
+            FROM sessions
+            JOIN accounts ON accounts.account_id = sessions.account_id
+            WHERE sessions.is_active = 1
+                AND datetime(sessions.expires_at) > datetime(?1)
+                AND datetime(sessions.created_at) > datetime(?2)
+            ORDER BY datetime(sessions.last_accessed) DESC
+            LIMIT ?3",
+        )
+        .bind(now)
+        .bind(config.oldest_valid_creation(now))
+        .bind(ALL_SESSIONS_LIST_LIMIT)
+        .fetch_all(&mut conn)
+        .await
+        .map_err(|e| {
+            error!("Error loading sessions: {e}");
+            ServerFnError::new(format!("Error loading sessions: {e}"))
+        }),
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// The handle shown in place of a session ID
+#[cfg(feature = "server")]
+fn session_handle(session_id: &str) -> String {
+    Sha256::digest(session_id.as_bytes())
+        .iter()
+        .take(16)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
@@ -0,0 +1,178 @@
+/// First-run setup of the initial admin account
+///
+/// Accounts can only be created by admins, so a fresh database needs its first admin
+/// created another way. [`bootstrap_first_admin`] runs when the server starts and, while
+/// the `accounts` table is empty, either:
+///
+/// * creates an admin from the `ADMIN_USERNAME` and `ADMIN_PASSWORD` environment
+///   variables, when both are set, or
+/// * generates a one-time setup token and logs a link to the `/setup` page, where
+///   whoever can read the server log picks the admin's username and password.
+///
+/// Both ways only ever create an account while there are none, so the setup page stops
+/// working as soon as the first account exists and the environment variables are
+/// ignored from then on. The token only lives in memory, restarting the server before
+/// setup is finished logs a new one.
+#[cfg(feature = "server")]
+use crate::{
+    auth::{constant_time_eq, generate_token, hash_password, password_problem},
+    database::create_connection,
+    models::Role,
+};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info, warn};
+use dioxus::prelude::*;
+#[cfg(feature = "server")]
+use std::{
+    env,
+    sync::{Mutex, OnceLock},
+};
+
+/// The setup token logged at startup, until the first admin has been created
+#[cfg(feature = "server")]
+static SETUP_TOKEN: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+#[cfg(feature = "server")]
+fn setup_token() -> &'static Mutex<Option<String>> {
+    SETUP_TOKEN.get_or_init(|| Mutex::new(None))
+}
+
+/// Creates the first admin from the environment, or prepares the setup page, when the
+/// database has no accounts yet. Called once when the server starts.
+#[cfg(feature = "server")]
+pub async fn bootstrap_first_admin() {
+    match count_accounts().await {
+        Ok(0) => {}
+        Ok(_) => return,
+        Err(e) => {
+            error!("Could not check for existing accounts, skipping first-run setup: {e}");
+            return;
+        }
+    }
+
+    let username = env::var("ADMIN_USERNAME").unwrap_or_default();
+    let password = env::var("ADMIN_PASSWORD").unwrap_or_default();
+    if !username.trim().is_empty() && !password.is_empty() {
+        match create_first_admin(&username, password).await {
+            Ok(true) => {
+                info!("Created the first admin account {} from ADMIN_USERNAME", username.trim());
+                return;
+            }
+            Ok(false) => return,
+            Err(e) => error!("Could not create the first admin from ADMIN_USERNAME and ADMIN_PASSWORD: {e}"),
+        }
+    }
+
+    let token = generate_token();
+    let site_url = env::var("SITE_URL").unwrap_or_default();
+    warn!(
+        "No accounts exist yet. Create the first admin at {}/setup?token={token}",
+        site_url.trim_end_matches('/')
+    );
+    if let Ok(mut setup_token) = setup_token().lock() {
+        *setup_token = Some(token);
+    }
+}
+
+/// Returns whether the site still needs its first admin, so the setup page can say so
+/// instead of showing a form that can't work
+#[server]
+pub async fn is_setup_required() -> Result<bool, ServerFnError> {
+    Ok(count_accounts().await? == 0)
+}
+
+/// Creates the first admin account with the setup token logged at startup. Fails once
+/// any account exists, and the token can't be used again afterwards.
+///
+/// # Arguments
+/// * `token` - The setup token from the server log
+/// * `username` - The new admin's username
+/// * `password` - The new admin's password
+#[server]
+pub async fn complete_setup(token: String, username: String, password: String) -> Result<(), ServerFnError> {
+    let token_matches = match setup_token().lock() {
+        Ok(setup_token) => setup_token
+            .as_deref()
+            .is_some_and(|expected| constant_time_eq(token.trim().as_bytes(), expected.as_bytes())),
+        Err(_) => false,
+    };
+    if !token_matches {
+        warn!("First-run setup attempted with an invalid setup token");
+        return Err(ServerFnError::new("This setup link is invalid or has already been used"));
+    }
+
+    if !create_first_admin(&username, password).await? {
+        return Err(ServerFnError::new("This setup link is invalid or has already been used"));
+    }
+    info!("Created the first admin account {} from the setup page", username.trim());
+    Ok(())
+}
+
+/// Creates an admin account if, and only if, there are no accounts yet, then disables the
+/// setup token
+///
+/// # Returns
+/// A `Result` containing `true` if the admin was created, `false` if an account already
+/// existed, or a `ServerFnError` if the username or password aren't acceptable or an
+/// error occurs.
+#[cfg(feature = "server")]
+async fn create_first_admin(username: &str, password: String) -> Result<bool, ServerFnError> {
+    let username = username.trim();
+    if username.is_empty() {
+        return Err(ServerFnError::new("Username cannot be empty"));
+    }
+    if let Some(problem) = password_problem(&password) {
+        return Err(ServerFnError::new(problem));
+    }
+
+    let password_hash = hash_password(password).await?;
+    let role_id = Role::Admin as i32;
+    let created = match create_connection().await {
+        Ok(mut conn) => {
+            // Checked in the same statement so two requests can't both create an admin
+            sqlx::query!(
+                "INSERT INTO accounts (username, password_hash, role_id)
+                SELECT ?1, ?2, ?3
+                WHERE NOT EXISTS (SELECT 1 FROM accounts)",
+                username,
+                password_hash,
+                role_id
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error creating the first admin account: {e}");
+                ServerFnError::new(format!("Error creating account: {e}"))
+            })?
+            .rows_affected()
+                > 0
+        }
+        Err(e) => {
+            return Err(ServerFnError::new(format!(
+                "Database connection error: {e}"
+            )))
+        }
+    };
+
+    if let Ok(mut setup_token) = setup_token().lock() {
+        *setup_token = None;
+    }
+    Ok(created)
+}
+
+/// Counts the accounts in the database
+#[cfg(feature = "server")]
+async fn count_accounts() -> Result<i64, ServerFnError> {
+    match create_connection().await {
+        Ok(mut conn) => sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM accounts")
+            .fetch_one(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error counting accounts: {e}");
+                ServerFnError::new(format!("Error counting accounts: {e}"))
+            }),
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
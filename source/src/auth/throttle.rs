@@ -0,0 +1,389 @@
+/// Brute force protection for logins
+///
+/// Every login attempt is recorded in `login_attempts`. Once an account or an IP address
+/// has failed a few times within [`THROTTLE_WINDOW_MINUTES`], each further attempt has to
+/// wait twice as long as the previous one, and an account that keeps failing is locked
+/// for [`LOCKOUT_MINUTES`]. A successful login clears the account's failures.
+use crate::{database::create_connection, models::LoginOutcome};
+use chrono::{DateTime, Duration, Utc};
+use dioxus::logger::tracing::{error, warn};
+use dioxus::prelude::*;
+
+/// How far back failed attempts are counted
+pub const THROTTLE_WINDOW_MINUTES: i64 = 15;
+
+/// Failed attempts allowed for an account before it is throttled
+pub const FREE_ACCOUNT_ATTEMPTS: i64 = 3;
+
+/// Failed attempts allowed from an IP address, across all accounts, before it is throttled
+pub const FREE_IP_ATTEMPTS: i64 = 10;
+
+/// Delay after the first throttled failure, doubled for each failure after it
+pub const BACKOFF_BASE_SECONDS: i64 = 2;
+
+/// Longest delay the backoff can reach
+pub const MAX_BACKOFF_SECONDS: i64 = 15 * 60;
+
+/// Failed attempts after which an account is locked
+pub const LOCKOUT_THRESHOLD: i64 = 10;
+
+/// How long a locked account stays locked
+pub const LOCKOUT_MINUTES: i64 = 30;
+
+/// How long login attempts are kept before [`prune_login_attempts`] deletes them
+pub const LOGIN_ATTEMPT_RETENTION_DAYS: i64 = 90;
+
+/// Whether a login attempt may go ahead
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoginDecision {
+    Allowed,
+    /// Too many recent failures, the client has to wait before trying again
+    Throttled { retry_after_seconds: i64 },
+    /// The account is locked until the given time
+    Locked { until: DateTime<Utc> },
+}
+
+impl LoginDecision {
+    /// The outcome recorded for an attempt refused with this decision
+    pub fn outcome(&self) -> LoginOutcome {
+        match self {
+            LoginDecision::Allowed => LoginOutcome::Success,
+            LoginDecision::Throttled { .. } => LoginOutcome::Throttled,
+            LoginDecision::Locked { .. } => LoginOutcome::Locked,
+        }
+    }
+
+    /// The message shown to the person logging in when the attempt is refused
+    pub fn message(&self) -> String {
+        match self {
+            LoginDecision::Allowed => "Login allowed".to_string(),
+            LoginDecision::Throttled { retry_after_seconds } => format!(
+                "Too many failed login attempts, please try again in {retry_after_seconds} seconds"
+            ),
+            LoginDecision::Locked { until } => {
+                let minutes = ((*until - Utc::now()).num_seconds().max(0) + 59) / 60;
+                format!(
+                    "This account is temporarily locked after too many failed login attempts, please try again in {minutes} minutes"
+                )
+            }
+        }
+    }
+}
+
+/// Decides whether a login attempt may check its password, based on the account's lock
+/// and the recent failures for the username and the IP address
+///
+/// # Arguments
+/// * `conn` - An open database connection
+/// * `username` - The username being logged into
+/// * `ip_address` - The address the attempt comes from, if known
+pub async fn check_login_allowed(
+    conn: &mut sqlx::SqliteConnection,
+    username: &str,
+    ip_address: Option<&str>,
+) -> Result<LoginDecision, ServerFnError> {
+    let now = Utc::now();
+
+    let locked_until = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+        "SELECT locked_until FROM accounts WHERE username = ?1 AND datetime(locked_until) > datetime(?2)",
+    )
+    .bind(username)
+    .bind(now)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("Error checking account lock for {username}: {e}");
+        ServerFnError::new(format!("Error checking login attempts: {e}"))
+    })?
+    .flatten();
+    if let Some(until) = locked_until {
+        return Ok(LoginDecision::Locked { until });
+    }
+
+    let (account_failures, last_account_failure) = recent_failures(conn, "username", username).await?;
+    let mut wait = remaining_backoff(account_failures, FREE_ACCOUNT_ATTEMPTS, last_account_failure, now);
+
+    if let Some(ip_address) = ip_address {
+        let (ip_failures, last_ip_failure) = recent_failures(conn, "ip_address", ip_address).await?;
+        wait = wait.max(remaining_backoff(ip_failures, FREE_IP_ATTEMPTS, last_ip_failure, now));
+    }
+
+    if wait > 0 {
+        Ok(LoginDecision::Throttled {
+            retry_after_seconds: wait,
+        })
+    } else {
+        Ok(LoginDecision::Allowed)
+    }
+}
+
+/// Records a login attempt, locking the account once it reaches [`LOCKOUT_THRESHOLD`]
+/// failures and unlocking it again on a successful login
+///
+/// # Arguments
+/// * `conn` - An open database connection
+/// * `username` - The username as it was typed
+/// * `account_id` - The account the username belongs to, if any
+/// * `ip_address` - The address the attempt came from, if known
+/// * `user_agent` - The browser the attempt came from, if known
+/// * `outcome` - What happened to the attempt
+pub async fn record_login_attempt(
+    conn: &mut sqlx::SqliteConnection,
+    username: &str,
+    account_id: Option<i32>,
+    ip_address: Option<&str>,
+    user_agent: Option<&str>,
+    outcome: LoginOutcome,
+) -> Result<(), ServerFnError> {
+    let now = Utc::now();
+    let outcome_value = outcome.as_str();
+    sqlx::query!(
+        "INSERT INTO login_attempts (username, account_id, ip_address, user_agent, outcome, attempted_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        username,
+        account_id,
+        ip_address,
+        user_agent,
+        outcome_value,
+        now
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("Error recording login attempt for {username}: {e}");
+        ServerFnError::new(format!("Error recording login attempt: {e}"))
+    })?;
+
+    let Some(account_id) = account_id else {
+        return Ok(());
+    };
+    match outcome {
+        LoginOutcome::Success => {
+            sqlx::query!("UPDATE accounts SET locked_until = NULL WHERE account_id = ?1", account_id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| {
+                    error!("Error unlocking account {account_id}: {e}");
+                    ServerFnError::new(format!("Error recording login attempt: {e}"))
+                })?;
+        }
+        LoginOutcome::Failure => {
+            let (failures, _) = recent_failures(conn, "username", username).await?;
+            if failures >= LOCKOUT_THRESHOLD {
+                let locked_until = now + Duration::minutes(LOCKOUT_MINUTES);
+                warn!("Locking account {username} after {failures} failed login attempts");
+                sqlx::query!(
+                    "UPDATE accounts SET locked_until = ?1 WHERE account_id = ?2",
+                    locked_until,
+                    account_id
+                )
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| {
+                    error!("Error locking account {account_id}: {e}");
+                    ServerFnError::new(format!("Error recording login attempt: {e}"))
+                })?;
+            }
+        }
+        LoginOutcome::Throttled | LoginOutcome::Locked => {}
+    }
+    Ok(())
+}
+
+/// Deletes login attempts older than [`LOGIN_ATTEMPT_RETENTION_DAYS`]. Run periodically
+/// by the [`crate::scheduler`].
+///
+/// # Returns
+/// A `Result` containing the number of attempts deleted.
+pub async fn prune_login_attempts() -> Result<u64, ServerFnError> {
+    let cutoff = Utc::now() - Duration::days(LOGIN_ATTEMPT_RETENTION_DAYS);
+    match create_connection().await {
+        Ok(mut conn) => {
+            let result = sqlx::query!(
+                "DELETE FROM login_attempts WHERE datetime(attempted_at) < datetime(?1)",
+                cutoff
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error pruning login attempts: {e}");
+                ServerFnError::new(format!("Error pruning login attempts: {e}"))
+            })?;
+            Ok(result.rows_affected())
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Counts the failed attempts within the throttle window for a username or an IP
+/// address, along with the time of the latest one. For usernames only the failures since
+/// the last successful login are counted.
+async fn recent_failures(
+    conn: &mut sqlx::SqliteConnection,
+    column: &'static str,
+    value: &str,
+) -> Result<(i64, Option<DateTime<Utc>>), ServerFnError> {
+    let window_start = Utc::now() - Duration::minutes(THROTTLE_WINDOW_MINUTES);
+    let since_last_success = if column == "username" {
+        "AND id > COALESCE((SELECT MAX(id) FROM login_attempts WHERE username = ?1 AND outcome = 'success'), 0)"
+    } else {
+        ""
+    };
+    let query = format!(
+        "SELECT COUNT(*), MAX(attempted_at)
+        FROM login_attempts
+        WHERE {column} = ?1 AND outcome = 'failure' AND datetime(attempted_at) > datetime(?2)
+        {since_last_success}"
+    );
+    sqlx::query_as::<_, (i64, Option<DateTime<Utc>>)>(&query)
+        .bind(value)
+        .bind(window_start)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| {
+            error!("Error counting failed logins by {column}: {e}");
+            ServerFnError::new(format!("Error checking login attempts: {e}"))
+        })
+}
+
+/// Seconds left to wait after `failures` failed attempts, the latest at `last_failure`
+fn remaining_backoff(
+    failures: i64,
+    free_attempts: i64,
+    last_failure: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> i64 {
+    let Some(last_failure) = last_failure else {
+        return 0;
+    };
+    if failures < free_attempts {
+        return 0;
+    }
+    // Cap the exponent so the shift can't overflow before the maximum applies
+    let doublings = (failures - free_attempts).min(20) as u32;
+    let delay = (BACKOFF_BASE_SECONDS << doublings).min(MAX_BACKOFF_SECONDS);
+    // Round up so a client told to wait N seconds isn't still throttled after N seconds
+    let remaining_ms = (last_failure + Duration::seconds(delay) - now).num_milliseconds();
+    (remaining_ms.max(0) + 999) / 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Connection;
+
+    /// A fresh in-memory database with every migration applied
+    async fn test_connection() -> sqlx::SqliteConnection {
+        let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&mut conn).await.unwrap();
+        conn
+    }
+
+    async fn fail_logins(conn: &mut sqlx::SqliteConnection, username: &str, account_id: Option<i32>, count: i64) {
+        for _ in 0..count {
+            record_login_attempt(conn, username, account_id, None, None, LoginOutcome::Failure)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn free_attempts_are_not_throttled() {
+        let now = Utc::now();
+        for failures in 0..FREE_ACCOUNT_ATTEMPTS {
+            assert_eq!(remaining_backoff(failures, FREE_ACCOUNT_ATTEMPTS, Some(now), now), 0);
+        }
+        assert_eq!(
+            remaining_backoff(FREE_ACCOUNT_ATTEMPTS, FREE_ACCOUNT_ATTEMPTS, Some(now), now),
+            BACKOFF_BASE_SECONDS
+        );
+        assert_eq!(
+            remaining_backoff(FREE_ACCOUNT_ATTEMPTS + 2, FREE_ACCOUNT_ATTEMPTS, Some(now), now),
+            BACKOFF_BASE_SECONDS * 4
+        );
+    }
+
+    #[test]
+    fn backoff_counts_down_from_the_last_failure() {
+        let now = Utc::now();
+        let last_failure = now - Duration::milliseconds(500);
+        assert_eq!(remaining_backoff(FREE_ACCOUNT_ATTEMPTS, FREE_ACCOUNT_ATTEMPTS, Some(last_failure), now), 2);
+        let last_failure = now - Duration::seconds(BACKOFF_BASE_SECONDS);
+        assert_eq!(remaining_backoff(FREE_ACCOUNT_ATTEMPTS, FREE_ACCOUNT_ATTEMPTS, Some(last_failure), now), 0);
+        assert_eq!(remaining_backoff(50, FREE_ACCOUNT_ATTEMPTS, None, now), 0);
+    }
+
+    #[test]
+    fn backoff_is_capped_at_the_maximum() {
+        let now = Utc::now();
+        // 2 << 9 seconds is already past the cap
+        assert_eq!(
+            remaining_backoff(FREE_ACCOUNT_ATTEMPTS + 9, FREE_ACCOUNT_ATTEMPTS, Some(now), now),
+            MAX_BACKOFF_SECONDS
+        );
+    }
+
+    #[test]
+    fn backoff_exponent_is_capped() {
+        let now = Utc::now();
+        for failures in [FREE_ACCOUNT_ATTEMPTS + 63, FREE_ACCOUNT_ATTEMPTS + 64, i64::MAX] {
+            assert_eq!(
+                remaining_backoff(failures, FREE_ACCOUNT_ATTEMPTS, Some(now), now),
+                MAX_BACKOFF_SECONDS
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn account_is_throttled_after_its_free_attempts() {
+        let mut conn = test_connection().await;
+        fail_logins(&mut conn, "alice", None, FREE_ACCOUNT_ATTEMPTS - 1).await;
+        assert_eq!(check_login_allowed(&mut conn, "alice", None).await.unwrap(), LoginDecision::Allowed);
+
+        fail_logins(&mut conn, "alice", None, 1).await;
+        assert!(matches!(
+            check_login_allowed(&mut conn, "alice", None).await.unwrap(),
+            LoginDecision::Throttled { retry_after_seconds } if retry_after_seconds == BACKOFF_BASE_SECONDS
+        ));
+        // Other accounts aren't affected
+        assert_eq!(check_login_allowed(&mut conn, "bob", None).await.unwrap(), LoginDecision::Allowed);
+    }
+
+    #[tokio::test]
+    async fn success_resets_the_account_failures() {
+        let mut conn = test_connection().await;
+        fail_logins(&mut conn, "alice", None, FREE_ACCOUNT_ATTEMPTS + 2).await;
+        assert!(matches!(
+            check_login_allowed(&mut conn, "alice", None).await.unwrap(),
+            LoginDecision::Throttled { .. }
+        ));
+
+        record_login_attempt(&mut conn, "alice", None, None, None, LoginOutcome::Success)
+            .await
+            .unwrap();
+        assert_eq!(check_login_allowed(&mut conn, "alice", None).await.unwrap(), LoginDecision::Allowed);
+
+        // Counting starts again from zero
+        fail_logins(&mut conn, "alice", None, FREE_ACCOUNT_ATTEMPTS - 1).await;
+        assert_eq!(check_login_allowed(&mut conn, "alice", None).await.unwrap(), LoginDecision::Allowed);
+    }
+
+    #[tokio::test]
+    async fn account_is_locked_at_the_threshold() {
+        let mut conn = test_connection().await;
+        let account_id = sqlx::query_scalar::<_, i32>(
+            "INSERT INTO accounts (username, password_hash, role_id) VALUES ('alice', 'hash', 2) RETURNING account_id",
+        )
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+
+        fail_logins(&mut conn, "alice", Some(account_id), LOCKOUT_THRESHOLD).await;
+        assert!(matches!(
+            check_login_allowed(&mut conn, "alice", None).await.unwrap(),
+            LoginDecision::Locked { .. }
+        ));
+    }
+}
@@ -0,0 +1,40 @@
+/// Random tokens handed to clients, such as API, password reset, CSRF and setup tokens
+///
+/// Tokens that are kept in the database are stored as their [`hash_token`], so a leaked
+/// copy of the database doesn't give away working tokens.
+use password_hash::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// Generates a random token of 32 bytes, hex encoded
+pub(crate) fn generate_token() -> String {
+    let mut bytes = [0_u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex(&bytes)
+}
+
+/// The SHA-256 hash of a token, hex encoded, as stored in place of the token
+pub(crate) fn hash_token(token: &str) -> String {
+    hex(&Sha256::digest(token.as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_distinct_hex_tokens() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert!(token.bytes().all(|byte| byte.is_ascii_hexdigit() && !byte.is_ascii_uppercase()));
+        assert_ne!(token, generate_token());
+    }
+
+    #[test]
+    fn hashes_with_sha256() {
+        assert_eq!(hash_token("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+}
@@ -0,0 +1,821 @@
+/// TOTP two-factor authentication (RFC 6238)
+///
+/// Setting up two-factor authentication generates a secret that is shown as a QR code for
+/// an authenticator app. Once the first code from the app is confirmed, logging in takes
+/// a second step: after the password is checked a short-lived challenge is stored and its
+/// token is put in an HttpOnly cookie, and the session is only created once a valid code
+/// or one of the single-use recovery codes is entered.
+#[cfg(feature = "server")]
+use crate::{
+    auth::{
+        check_login_allowed, clear_two_factor_cookie, create_session, hash_password,
+        read_two_factor_cookie, record_login_attempt, require_role, set_session_cookie,
+        set_two_factor_cookie, verify_password_hash, CurrentUser, LoginDecision,
+    },
+    database::create_connection,
+    models::{get_account_by_id, Account, LoginOutcome, Role},
+};
+use crate::auth::LoginResponse;
+#[cfg(feature = "server")]
+use chrono::{DateTime, Duration, Utc};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info, warn};
+use dioxus::prelude::*;
+#[cfg(feature = "server")]
+use password_hash::rand_core::{OsRng, RngCore};
+#[cfg(feature = "server")]
+use qrcode::{render::svg, QrCode};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use totp_rs::{Algorithm, Secret, TOTP};
+
+/// Issuer shown next to the account in authenticator apps
+#[cfg(feature = "server")]
+const TOTP_ISSUER: &str = "nicojudge.com";
+
+/// Seconds each TOTP code is valid for
+#[cfg(feature = "server")]
+const TOTP_STEP_SECONDS: u64 = 30;
+
+/// Codes from this many steps either side of the current one are accepted, to allow for
+/// clock drift between the server and the authenticator app
+#[cfg(feature = "server")]
+const TOTP_ALLOWED_DRIFT_STEPS: u64 = 1;
+
+/// Number of recovery codes generated at a time
+#[cfg(feature = "server")]
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Characters recovery codes are made of, without ones that are easily confused
+#[cfg(feature = "server")]
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// How long a login has to enter its two-factor code
+#[cfg(feature = "server")]
+const CHALLENGE_MINUTES: i64 = 5;
+
+/// Wrong codes allowed before a login has to start over with the password
+#[cfg(feature = "server")]
+const MAX_CHALLENGE_ATTEMPTS: i64 = 5;
+
+/// A newly generated TOTP secret, ready to be added to an authenticator app
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct TwoFactorSetup {
+    /// The base32 encoded secret, for apps that can't scan QR codes
+    pub secret: String,
+    pub otpauth_uri: String,
+    /// The `otpauth://` URI as a QR code in SVG markup
+    pub qr_svg: String,
+}
+
+/// Two-factor authentication state of the logged in account
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    /// Whether the account has to use two-factor authentication because of its role
+    pub required: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+/// Site wide security settings
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SecuritySettings {
+    /// Whether admins have to set up two-factor authentication before they can do
+    /// anything that needs the admin role
+    pub require_admin_two_factor: bool,
+}
+
+/// Gets the two-factor authentication state of the logged in account
+#[server]
+pub async fn get_two_factor_status() -> Result<TwoFactorStatus, ServerFnError> {
+    let user = require_role(Role::Guest).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let (enabled, recovery_codes_remaining) = sqlx::query_as::<_, (bool, i64)>(
+                "SELECT accounts.totp_enabled,
+                    (SELECT COUNT(*) FROM recovery_codes WHERE account_id = accounts.account_id AND used_at IS NULL)
+                FROM accounts WHERE account_id = ?1",
+            )
+            .bind(user.account_id)
+            .fetch_one(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error loading two-factor status for {}: {e}", user.username);
+                ServerFnError::new(format!("Error loading two-factor status: {e}"))
+            })?;
+            let required = user.role_id == Role::Admin as i32 && admin_two_factor_required(&mut conn).await?;
+            Ok(TwoFactorStatus {
+                enabled,
+                required,
+                recovery_codes_remaining,
+            })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Starts setting up two-factor authentication for the logged in account by generating
+/// a new secret. It isn't used at login until a code from it has been confirmed with
+/// [`confirm_two_factor_setup`].
+///
+/// # Returns
+/// A `Result` containing the `TwoFactorSetup` with the secret and its QR code, or a
+/// `ServerFnError` if two-factor authentication is already enabled or an error occurs.
+#[server]
+pub async fn begin_two_factor_setup() -> Result<TwoFactorSetup, ServerFnError> {
+    let user = require_role(Role::Guest).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            if load_totp_state(&mut conn, user.account_id).await?.enabled {
+                return Err(ServerFnError::new("Two-factor authentication is already enabled"));
+            }
+
+            let secret = Secret::generate_secret().to_encoded().to_string();
+            let totp = build_totp(&secret, &user.username)?;
+            let otpauth_uri = totp.get_url();
+            let qr_svg = QrCode::new(otpauth_uri.as_bytes())
+                .map_err(|e| ServerFnError::new(format!("Error generating QR code: {e}")))?
+                .render::<svg::Color>()
+                .min_dimensions(200, 200)
+                .build();
+
+            sqlx::query!(
+                "UPDATE accounts SET totp_secret = ?1, totp_enabled = 0, totp_last_step = NULL WHERE account_id = ?2",
+                secret,
+                user.account_id
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error storing TOTP secret for {}: {e}", user.username);
+                ServerFnError::new(format!("Error starting two-factor setup: {e}"))
+            })?;
+
+            info!("Two-factor setup started for {}", user.username);
+            Ok(TwoFactorSetup {
+                secret,
+                otpauth_uri,
+                qr_svg,
+            })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Finishes setting up two-factor authentication by checking a code from the new secret,
+/// then enables it and generates a fresh set of recovery codes
+///
+/// # Arguments
+/// * `code` - The current code shown by the authenticator app
+///
+/// # Returns
+/// A `Result` containing the recovery codes, which are only ever shown this once, or a
+/// `ServerFnError` if the code is wrong or an error occurs.
+#[server]
+pub async fn confirm_two_factor_setup(code: String) -> Result<Vec<String>, ServerFnError> {
+    let user = require_role(Role::Guest).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let state = load_totp_state(&mut conn, user.account_id).await?;
+            if state.enabled {
+                return Err(ServerFnError::new("Two-factor authentication is already enabled"));
+            }
+            if state.secret.is_none() {
+                return Err(ServerFnError::new("Two-factor setup hasn't been started"));
+            }
+            if !verify_totp_code(&mut conn, user.account_id, &user.username, &state, &code, Utc::now()).await? {
+                warn!("Wrong code confirming two-factor setup for {}", user.username);
+                return Err(ServerFnError::new("That code isn't valid, please try again"));
+            }
+
+            sqlx::query!("UPDATE accounts SET totp_enabled = 1 WHERE account_id = ?1", user.account_id)
+                .execute(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error enabling two-factor for {}: {e}", user.username);
+                    ServerFnError::new(format!("Error enabling two-factor authentication: {e}"))
+                })?;
+            info!("Two-factor authentication enabled for {}", user.username);
+
+            replace_recovery_codes(&mut conn, user.account_id).await
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Turns off two-factor authentication for the logged in account. Not allowed for admins
+/// while two-factor authentication is required for them.
+///
+/// # Arguments
+/// * `code` - A current code from the authenticator app, or a recovery code
+#[server]
+pub async fn disable_two_factor(code: String) -> Result<(), ServerFnError> {
+    let user = require_role(Role::Guest).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            if user.role_id == Role::Admin as i32 && admin_two_factor_required(&mut conn).await? {
+                return Err(ServerFnError::new("Two-factor authentication is required for admins"));
+            }
+            if !verify_second_factor(&mut conn, user.account_id, &user.username, &code, Utc::now()).await? {
+                warn!("Wrong code disabling two-factor for {}", user.username);
+                return Err(ServerFnError::new("That code isn't valid, please try again"));
+            }
+
+            sqlx::query!(
+                "UPDATE accounts SET totp_secret = NULL, totp_enabled = 0, totp_last_step = NULL WHERE account_id = ?1",
+                user.account_id
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error disabling two-factor for {}: {e}", user.username);
+                ServerFnError::new(format!("Error disabling two-factor authentication: {e}"))
+            })?;
+            sqlx::query!("DELETE FROM recovery_codes WHERE account_id = ?1", user.account_id)
+                .execute(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error deleting recovery codes for {}: {e}", user.username);
+                    ServerFnError::new(format!("Error disabling two-factor authentication: {e}"))
+                })?;
+
+            info!("Two-factor authentication disabled for {}", user.username);
+            Ok(())
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Replaces the recovery codes of the logged in account with a fresh set
+///
+/// # Arguments
+/// * `code` - A current code from the authenticator app
+///
+/// # Returns
+/// A `Result` containing the new recovery codes, which are only ever shown this once.
+#[server]
+pub async fn regenerate_recovery_codes(code: String) -> Result<Vec<String>, ServerFnError> {
+    let user = require_role(Role::Guest).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let state = load_totp_state(&mut conn, user.account_id).await?;
+            if !state.enabled {
+                return Err(ServerFnError::new("Two-factor authentication isn't enabled"));
+            }
+            if !verify_totp_code(&mut conn, user.account_id, &user.username, &state, &code, Utc::now()).await? {
+                warn!("Wrong code regenerating recovery codes for {}", user.username);
+                return Err(ServerFnError::new("That code isn't valid, please try again"));
+            }
+            replace_recovery_codes(&mut conn, user.account_id).await
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Second step of logging in for accounts with two-factor authentication. Checks the
+/// code against the challenge from the cookie set by `login_with_session`, and creates the
+/// session once it is valid.
+///
+/// # Arguments
+/// * `code` - A current code from the authenticator app, or a recovery code
+#[server]
+pub async fn verify_two_factor_login(code: String) -> Result<LoginResponse, ServerFnError> {
+    let failed = |message: &str| LoginResponse {
+        success: false,
+        message: message.to_string(),
+        two_factor_required: false,
+        user: None,
+    };
+
+    let Some(token) = read_two_factor_cookie() else {
+        return Ok(failed("Your login has expired, please log in again"));
+    };
+
+    let mut conn = match create_connection().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("Database connection error: {e}");
+            return Err(ServerFnError::new(format!("Database connection error: {e}")));
+        }
+    };
+
+    let now = Utc::now();
+    let challenge = sqlx::query_as::<_, (i32, Option<String>, Option<String>, i64)>(
+        "SELECT account_id, ip_address, user_agent, failed_attempts
+        FROM two_factor_challenges
+        WHERE token = ?1 AND datetime(expires_at) > datetime(?2)",
+    )
+    .bind(&token)
+    .bind(now)
+    .fetch_optional(&mut conn)
+    .await
+    .map_err(|e| {
+        error!("Error loading two-factor challenge: {e}");
+        ServerFnError::new(format!("Error checking two-factor code: {e}"))
+    })?;
+    let Some((account_id, ip_address, user_agent, failed_attempts)) = challenge else {
+        clear_two_factor_cookie();
+        return Ok(failed("Your login has expired, please log in again"));
+    };
+    let Some(account) = get_account_by_id(account_id).await? else {
+        delete_challenge(&mut conn, &token).await?;
+        return Ok(failed("Your login has expired, please log in again"));
+    };
+    if !account.is_active {
+        delete_challenge(&mut conn, &token).await?;
+        return Ok(failed("This account has been disabled"));
+    }
+
+    let decision = check_login_allowed(&mut conn, &account.username, ip_address.as_deref()).await?;
+    if decision != LoginDecision::Allowed {
+        record_login_attempt(&mut conn, &account.username, Some(account_id), ip_address.as_deref(), user_agent.as_deref(), decision.outcome()).await?;
+        // The challenge stays valid, so the code can be tried again after waiting
+        return Ok(LoginResponse {
+            two_factor_required: true,
+            ..failed(&decision.message())
+        });
+    }
+
+    if !verify_second_factor(&mut conn, account_id, &account.username, &code, now).await? {
+        warn!("Wrong two-factor code for {}", account.username);
+        record_login_attempt(&mut conn, &account.username, Some(account_id), ip_address.as_deref(), user_agent.as_deref(), LoginOutcome::Failure).await?;
+        if failed_attempts + 1 >= MAX_CHALLENGE_ATTEMPTS {
+            delete_challenge(&mut conn, &token).await?;
+            return Ok(failed("Too many wrong codes, please log in again"));
+        }
+        sqlx::query!(
+            "UPDATE two_factor_challenges SET failed_attempts = failed_attempts + 1 WHERE token = ?1",
+            token
+        )
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            error!("Error updating two-factor challenge: {e}");
+            ServerFnError::new(format!("Error checking two-factor code: {e}"))
+        })?;
+        return Ok(LoginResponse {
+            two_factor_required: true,
+            ..failed("That code isn't valid, please try again")
+        });
+    }
+
+    delete_challenge(&mut conn, &token).await?;
+    record_login_attempt(&mut conn, &account.username, Some(account_id), ip_address.as_deref(), user_agent.as_deref(), LoginOutcome::Success).await?;
+    let session = create_session(account_id, ip_address, user_agent).await?;
+    set_session_cookie(&session.session_id, session.expires_at);
+    info!("Two-factor login successful for user: {}", account.username);
+
+    Ok(LoginResponse {
+        success: true,
+        message: "Login successful".to_string(),
+        two_factor_required: false,
+        user: Some(CurrentUser::for_account(account).await?),
+    })
+}
+
+/// Gets the site wide security settings. The caller must hold an active admin session.
+#[server]
+pub async fn get_security_settings() -> Result<SecuritySettings, ServerFnError> {
+    require_role(Role::Admin).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => Ok(SecuritySettings {
+            require_admin_two_factor: admin_two_factor_required(&mut conn).await?,
+        }),
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Saves the site wide security settings. The caller must hold an active admin session,
+/// and has to have two-factor authentication enabled before requiring it for admins so
+/// they can't lock themselves out of the admin pages.
+///
+/// # Arguments
+/// * `settings` - The new settings
+#[server]
+pub async fn save_security_settings(settings: SecuritySettings) -> Result<(), ServerFnError> {
+    let user = require_role(Role::Admin).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            if settings.require_admin_two_factor && !load_totp_state(&mut conn, user.account_id).await?.enabled {
+                return Err(ServerFnError::new(
+                    "Enable two-factor authentication on your own account before requiring it for admins",
+                ));
+            }
+
+            let now = Utc::now();
+            sqlx::query!(
+                "INSERT INTO security_settings (id, require_admin_two_factor, updated_at) VALUES (1, ?1, ?2)
+                ON CONFLICT(id) DO UPDATE SET require_admin_two_factor = excluded.require_admin_two_factor, updated_at = excluded.updated_at",
+                settings.require_admin_two_factor,
+                now
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error saving security settings: {e}");
+                ServerFnError::new(format!("Error saving security settings: {e}"))
+            })?;
+
+            info!("{} set require_admin_two_factor to {}", user.username, settings.require_admin_two_factor);
+            Ok(())
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Stores a challenge for a login that passed the password check and puts its token in
+/// the two-factor cookie
+///
+/// # Arguments
+/// * `account_id` - The account being logged into
+/// * `ip_address` - The address the login came from, if known
+/// * `user_agent` - The browser the login came from, if known
+#[cfg(feature = "server")]
+pub async fn start_two_factor_challenge(
+    account_id: i32,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+) -> Result<(), ServerFnError> {
+    let token = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now();
+    let expires_at = now + Duration::minutes(CHALLENGE_MINUTES);
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            // Drop this account's expired challenges while we're here
+            sqlx::query!(
+                "DELETE FROM two_factor_challenges WHERE account_id = ?1 AND datetime(expires_at) <= datetime(?2)",
+                account_id,
+                now
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error cleaning up two-factor challenges: {e}");
+                ServerFnError::new(format!("Error starting two-factor login: {e}"))
+            })?;
+
+            sqlx::query!(
+                "INSERT INTO two_factor_challenges (token, account_id, ip_address, user_agent, expires_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                token,
+                account_id,
+                ip_address,
+                user_agent,
+                expires_at
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error storing two-factor challenge: {e}");
+                ServerFnError::new(format!("Error starting two-factor login: {e}"))
+            })?;
+
+            set_two_factor_cookie(&token, expires_at);
+            Ok(())
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Whether admins must have two-factor authentication enabled
+#[cfg(feature = "server")]
+pub async fn admin_two_factor_required(conn: &mut sqlx::SqliteConnection) -> Result<bool, ServerFnError> {
+    sqlx::query_scalar::<_, bool>("SELECT require_admin_two_factor FROM security_settings WHERE id = 1")
+        .fetch_optional(conn)
+        .await
+        .map(|required| required.unwrap_or(false))
+        .map_err(|e| {
+            error!("Error loading security settings: {e}");
+            ServerFnError::new(format!("Error loading security settings: {e}"))
+        })
+}
+
+#[cfg(feature = "server")]
+impl CurrentUser {
+    /// Builds the current user for an account, working out whether it still has to set
+    /// up two-factor authentication before it can use the admin role
+    pub async fn for_account(account: Account) -> Result<Self, ServerFnError> {
+        let two_factor_setup_required = if account.role_id == Role::Admin as i32 && !account.totp_enabled {
+            match create_connection().await {
+                Ok(mut conn) => admin_two_factor_required(&mut conn).await?,
+                Err(e) => {
+                    return Err(ServerFnError::new(format!(
+                        "Database connection error: {e}"
+                    )))
+                }
+            }
+        } else {
+            false
+        };
+
+        Ok(CurrentUser {
+            account_id: account.account_id.unwrap_or(0),
+            username: account.username,
+            role_id: account.role_id,
+            two_factor_enabled: account.totp_enabled,
+            two_factor_setup_required,
+        })
+    }
+}
+
+/// The TOTP columns of an account
+#[cfg(feature = "server")]
+struct TotpState {
+    secret: Option<String>,
+    enabled: bool,
+    last_step: Option<i64>,
+}
+
+#[cfg(feature = "server")]
+async fn load_totp_state(conn: &mut sqlx::SqliteConnection, account_id: i32) -> Result<TotpState, ServerFnError> {
+    let (secret, enabled, last_step) = sqlx::query_as::<_, (Option<String>, bool, Option<i64>)>(
+        "SELECT totp_secret, totp_enabled, totp_last_step FROM accounts WHERE account_id = ?1",
+    )
+    .bind(account_id)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        error!("Error loading TOTP state for account {account_id}: {e}");
+        ServerFnError::new(format!("Error loading two-factor state: {e}"))
+    })?;
+    Ok(TotpState {
+        secret,
+        enabled,
+        last_step,
+    })
+}
+
+#[cfg(feature = "server")]
+fn build_totp(secret: &str, username: &str) -> Result<TOTP, ServerFnError> {
+    let secret_bytes = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| ServerFnError::new(format!("Invalid TOTP secret: {e:?}")))?;
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        0,
+        TOTP_STEP_SECONDS,
+        secret_bytes,
+        Some(TOTP_ISSUER.to_string()),
+        username.replace(':', ""),
+    )
+    .map_err(|e| ServerFnError::new(format!("Invalid TOTP settings: {e}")))
+}
+
+/// Checks a TOTP code at the time `now` and remembers its time step so the same code
+/// can't be used again
+#[cfg(feature = "server")]
+async fn verify_totp_code(
+    conn: &mut sqlx::SqliteConnection,
+    account_id: i32,
+    username: &str,
+    state: &TotpState,
+    code: &str,
+    now: DateTime<Utc>,
+) -> Result<bool, ServerFnError> {
+    let Some(secret) = &state.secret else {
+        return Ok(false);
+    };
+    let code = code.trim().replace(' ', "");
+    let totp = build_totp(secret, username)?;
+    let current_step = now.timestamp() as u64 / TOTP_STEP_SECONDS;
+
+    let matched_step = (current_step.saturating_sub(TOTP_ALLOWED_DRIFT_STEPS)..=current_step + TOTP_ALLOWED_DRIFT_STEPS)
+        .find(|step| totp.check(&code, step * TOTP_STEP_SECONDS));
+    let Some(step) = matched_step else {
+        return Ok(false);
+    };
+    let step = step as i64;
+    if state.last_step.is_some_and(|last_step| step <= last_step) {
+        warn!("Rejecting reused TOTP code for account {account_id}");
+        return Ok(false);
+    }
+
+    sqlx::query!("UPDATE accounts SET totp_last_step = ?1 WHERE account_id = ?2", step, account_id)
+        .execute(conn)
+        .await
+        .map_err(|e| {
+            error!("Error saving TOTP step for account {account_id}: {e}");
+            ServerFnError::new(format!("Error checking two-factor code: {e}"))
+        })?;
+    Ok(true)
+}
+
+/// Checks a code from the authenticator app, falling back to the unused recovery codes,
+/// which are used up when they match
+#[cfg(feature = "server")]
+async fn verify_second_factor(
+    conn: &mut sqlx::SqliteConnection,
+    account_id: i32,
+    username: &str,
+    code: &str,
+    now: DateTime<Utc>,
+) -> Result<bool, ServerFnError> {
+    let state = load_totp_state(conn, account_id).await?;
+    if !state.enabled {
+        return Ok(false);
+    }
+    if verify_totp_code(conn, account_id, username, &state, code, now).await? {
+        return Ok(true);
+    }
+
+    let code = code.trim().to_lowercase();
+    if code.is_empty() {
+        return Ok(false);
+    }
+    let recovery_codes = sqlx::query_as::<_, (i32, String)>(
+        "SELECT id, code_hash FROM recovery_codes WHERE account_id = ?1 AND used_at IS NULL",
+    )
+    .bind(account_id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("Error loading recovery codes for account {account_id}: {e}");
+        ServerFnError::new(format!("Error checking two-factor code: {e}"))
+    })?;
+
+    for (id, code_hash) in recovery_codes {
+        if verify_password_hash(code.clone(), code_hash).await? {
+            sqlx::query!("UPDATE recovery_codes SET used_at = ?1 WHERE id = ?2", now, id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| {
+                    error!("Error using recovery code {id}: {e}");
+                    ServerFnError::new(format!("Error checking two-factor code: {e}"))
+                })?;
+            info!("Recovery code used by {username}");
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Deletes an account's recovery codes and stores a newly generated set, hashed
+#[cfg(feature = "server")]
+async fn replace_recovery_codes(
+    conn: &mut sqlx::SqliteConnection,
+    account_id: i32,
+) -> Result<Vec<String>, ServerFnError> {
+    sqlx::query!("DELETE FROM recovery_codes WHERE account_id = ?1", account_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("Error deleting recovery codes for account {account_id}: {e}");
+            ServerFnError::new(format!("Error generating recovery codes: {e}"))
+        })?;
+
+    let mut codes = Vec::with_capacity(RECOVERY_CODE_COUNT);
+    for _ in 0..RECOVERY_CODE_COUNT {
+        let code = generate_recovery_code();
+        let code_hash = hash_password(code.clone()).await?;
+        sqlx::query!(
+            "INSERT INTO recovery_codes (account_id, code_hash) VALUES (?1, ?2)",
+            account_id,
+            code_hash
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("Error storing recovery code for account {account_id}: {e}");
+            ServerFnError::new(format!("Error generating recovery codes: {e}"))
+        })?;
+        codes.push(code);
+    }
+    info!("Generated new recovery codes for account {account_id}");
+    Ok(codes)
+}
+
+/// Generates a random recovery code like `k7p2m-x9qdr`
+#[cfg(feature = "server")]
+fn generate_recovery_code() -> String {
+    let mut code = String::with_capacity(11);
+    for index in 0..10 {
+        if index == 5 {
+            code.push('-');
+        }
+        let choice = OsRng.next_u32() as usize % RECOVERY_CODE_ALPHABET.len();
+        code.push(RECOVERY_CODE_ALPHABET[choice] as char);
+    }
+    code
+}
+
+#[cfg(feature = "server")]
+async fn delete_challenge(conn: &mut sqlx::SqliteConnection, token: &str) -> Result<(), ServerFnError> {
+    clear_two_factor_cookie();
+    sqlx::query!("DELETE FROM two_factor_challenges WHERE token = ?1", token)
+        .execute(conn)
+        .await
+        .map_err(|e| {
+            error!("Error deleting two-factor challenge: {e}");
+            ServerFnError::new(format!("Error checking two-factor code: {e}"))
+        })?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use sqlx::{Connection, SqliteConnection};
+
+    const SECRET: &str = "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP";
+
+    /// Creates an account with two-factor authentication enabled using [`SECRET`]
+    async fn account_with_totp() -> (SqliteConnection, i32) {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&mut conn).await.unwrap();
+        let account_id = sqlx::query_scalar::<_, i32>(
+            "INSERT INTO accounts (username, password_hash, role_id, totp_secret, totp_enabled)
+            VALUES ('alice', 'hash', 2, ?1, 1) RETURNING account_id",
+        )
+        .bind(SECRET)
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+        (conn, account_id)
+    }
+
+    fn code_at(time: DateTime<Utc>) -> String {
+        build_totp(SECRET, "alice").unwrap().generate(time.timestamp() as u64)
+    }
+
+    #[tokio::test]
+    async fn accepts_one_step_of_drift_and_never_a_used_step() {
+        let (mut conn, account_id) = account_with_totp().await;
+        let now = Utc.with_ymd_and_hms(2024, 6, 5, 12, 0, 10).unwrap();
+        let step = Duration::seconds(TOTP_STEP_SECONDS as i64);
+        let mut verify = async |code: String| {
+            let state = load_totp_state(&mut conn, account_id).await.unwrap();
+            verify_totp_code(&mut conn, account_id, "alice", &state, &code, now).await.unwrap()
+        };
+
+        assert!(!verify(code_at(now - step * 2)).await, "two steps behind");
+        assert!(!verify(code_at(now + step * 2)).await, "two steps ahead");
+        assert!(!verify("000000".to_string()).await);
+        assert!(verify(code_at(now - step)).await, "one step behind");
+        assert!(!verify(code_at(now - step)).await, "the same code again");
+        let code = code_at(now);
+        assert!(verify(format!(" {} {} ", &code[..3], &code[3..])).await, "the current code, spaced");
+        assert!(!verify(code).await, "the current code again");
+        assert!(verify(code_at(now + step)).await, "one step ahead");
+        // Every step up to the last one used is spent, not just that one
+        assert!(!verify(code_at(now)).await);
+    }
+
+    #[tokio::test]
+    async fn recovery_codes_work_once() {
+        let (mut conn, account_id) = account_with_totp().await;
+        let now = Utc.with_ymd_and_hms(2024, 6, 5, 12, 0, 10).unwrap();
+        let codes = ["k7p2m-x9qdr", "abcde-fghjk"];
+        for code in codes {
+            sqlx::query("INSERT INTO recovery_codes (account_id, code_hash) VALUES (?1, ?2)")
+                .bind(account_id)
+                .bind(hash_password(code.to_string()).await.unwrap())
+                .execute(&mut conn)
+                .await
+                .unwrap();
+        }
+
+        assert!(verify_second_factor(&mut conn, account_id, "alice", " K7P2M-X9QDR ", now).await.unwrap());
+        assert!(!verify_second_factor(&mut conn, account_id, "alice", codes[0], now).await.unwrap());
+        assert!(verify_second_factor(&mut conn, account_id, "alice", codes[1], now).await.unwrap());
+        assert!(!verify_second_factor(&mut conn, account_id, "alice", "", now).await.unwrap());
+
+        // Neither kind of code is accepted while two-factor authentication is off
+        sqlx::query("UPDATE accounts SET totp_enabled = 0").execute(&mut conn).await.unwrap();
+        assert!(!verify_second_factor(&mut conn, account_id, "alice", &code_at(now), now).await.unwrap());
+    }
+
+    #[test]
+    fn generates_recovery_codes_from_the_alphabet() {
+        let code = generate_recovery_code();
+        assert_eq!(code.len(), 11);
+        assert_eq!(&code[5..6], "-");
+        assert!(code.bytes().filter(|byte| *byte != b'-').all(|byte| RECOVERY_CODE_ALPHABET.contains(&byte)));
+        assert_ne!(code, generate_recovery_code());
+    }
+}
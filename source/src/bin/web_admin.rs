@@ -0,0 +1,346 @@
+/// Command line tool for administering the site without the web UI
+///
+/// It works on the database in `DATABASE_URL`, the same as the server, so run it on the
+/// server with the same environment. `web-admin help` lists every command.
+///
+/// Anyone who can run it can already read and write the database, so it doesn't log in.
+/// It calls the server-only functions behind the server functions, like `insert_account`
+/// behind `save_new_account`. Those don't check the caller, which is left to the server
+/// function in front of them, and must not be called from anywhere a request can reach
+/// without such a check.
+///
+/// Passwords are read from standard input rather than taken as arguments, so they don't
+/// end up in the shell history or the process list, and aren't echoed when typed at a
+/// terminal. They can be piped in, e.g.
+/// `echo "$PASSWORD" | web-admin user reset-password alice`.
+use sqlx::Connection;
+use std::{
+    env, fs,
+    io::{self, BufRead, IsTerminal, Write},
+    process::ExitCode,
+};
+use web::{
+    auth::{
+        cleanup_expired_sessions, delete_all_sessions, invalidate_all_user_sessions, password_problem,
+        store_password,
+    },
+    components::store_mode,
+    database::{create_connection, create_pool, run_migrations},
+    models::{
+        backfill_post_slugs, backfill_post_text, get_account_by_username, insert_account, insert_post, load_accounts,
+        load_all_posts, store_account_role, BlogPost, Role,
+    },
+};
+
+const USAGE: &str = "\
+Usage: web-admin <command>
+
+Commands:
+  user create <username> [--role admin|user|guest]  Create an account, a user unless a role is given
+  user list                                         List every account
+  user set-role <username> <admin|user|guest>       Change the role of an account
+  user reset-password <username>                    Set a new password and log the account out everywhere
+  migrate                                           Apply pending database migrations
+  maintenance <on|off>                              Turn maintenance mode on or off
+  posts export [file]                               Write every post as JSON to the file, or standard output
+  posts import <file>                               Add the posts from a JSON export, skipping taken slugs
+  sessions purge [--all]                            Delete expired sessions, or with --all every session
+  help                                              Show this message
+
+The database is taken from DATABASE_URL, like the server.
+";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match run(&args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the command given on the command line
+async fn run(args: &[&str]) -> Result<(), String> {
+    match args {
+        ["user", "create", username] => create_user(username, Role::User).await,
+        ["user", "create", username, "--role", role] => create_user(username, parse_role(role)?).await,
+        ["user", "list"] => list_users().await,
+        ["user", "set-role", username, role] => set_role(username, parse_role(role)?).await,
+        ["user", "reset-password", username] => reset_password(username).await,
+        ["migrate"] => migrate().await,
+        ["maintenance", "on"] => set_maintenance(true).await,
+        ["maintenance", "off"] => set_maintenance(false).await,
+        ["posts", "export"] => export_posts(None).await,
+        ["posts", "export", path] => export_posts(Some(path)).await,
+        ["posts", "import", path] => import_posts(path).await,
+        ["sessions", "purge"] => purge_sessions(false).await,
+        ["sessions", "purge", "--all"] => purge_sessions(true).await,
+        [] | ["help"] | ["--help"] | ["-h"] => {
+            print!("{USAGE}");
+            Ok(())
+        }
+        _ => Err(format!("unknown command: {}\n\n{USAGE}", args.join(" "))),
+    }
+}
+
+async fn create_user(username: &str, role: Role) -> Result<(), String> {
+    let password = read_new_password()?;
+    let account = insert_account(username.to_string(), password, role)
+        .await
+        .map_err(message)?;
+    println!(
+        "Created {} account {} with id {}",
+        role.as_str(),
+        account.username,
+        account.account_id.unwrap_or_default()
+    );
+    Ok(())
+}
+
+async fn list_users() -> Result<(), String> {
+    println!("{:>5}  {:<24} {:<6} {:<8} {:<4} locked until", "id", "username", "role", "active", "2fa");
+    let mut page = 1;
+    loop {
+        let account_page = load_accounts(page).await.map_err(message)?;
+        for account in &account_page.accounts {
+            let role = Role::from_id(account.role_id).map(|role| role.as_str()).unwrap_or("?");
+            let locked_until = account
+                .locked_until
+                .map(|locked_until| locked_until.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            println!(
+                "{:>5}  {:<24} {:<6} {:<8} {:<4} {locked_until}",
+                account.account_id,
+                account.username,
+                role,
+                yes_no(account.is_active),
+                yes_no(account.totp_enabled),
+            );
+        }
+        if page >= account_page.page_count() {
+            return Ok(());
+        }
+        page += 1;
+    }
+}
+
+async fn set_role(username: &str, role: Role) -> Result<(), String> {
+    let account_id = find_account_id(username).await?;
+    store_account_role(account_id, role).await.map_err(message)?;
+    println!("{username} is now {}", role.as_str());
+    Ok(())
+}
+
+async fn reset_password(username: &str) -> Result<(), String> {
+    let account_id = find_account_id(username).await?;
+    let password = read_new_password()?;
+    store_password(account_id, password).await.map_err(message)?;
+    invalidate_all_user_sessions(account_id).await.map_err(message)?;
+    println!("Changed the password of {username} and logged it out everywhere");
+    Ok(())
+}
+
+async fn migrate() -> Result<(), String> {
+    let pool = create_pool()
+        .await
+        .map_err(|e| format!("Could not open the database: {e}"))?;
+    run_migrations(&pool)
+        .await
+        .map_err(|e| format!("Migrations failed: {e}"))?;
+    let backfilled = backfill_post_slugs().await.map_err(message)?;
+    if backfilled > 0 {
+        println!("Gave {backfilled} posts a slug");
+    }
+    let indexed = backfill_post_text().await.map_err(message)?;
+    if indexed > 0 {
+        println!("Indexed the text of {indexed} posts for search");
+    }
+    println!("The database is up to date");
+    Ok(())
+}
+
+async fn set_maintenance(enabled: bool) -> Result<(), String> {
+    store_mode(enabled).await.map_err(message)?;
+    println!("Maintenance mode is {}", if enabled { "on" } else { "off" });
+    Ok(())
+}
+
+async fn export_posts(path: Option<&str>) -> Result<(), String> {
+    let mut conn = connect().await?;
+    let posts = load_all_posts(&mut conn).await.map_err(message)?;
+    let json = serde_json::to_string_pretty(&posts).map_err(|e| format!("Could not encode the posts: {e}"))?;
+    match path {
+        Some(path) => {
+            fs::write(path, json).map_err(|e| format!("Could not write {path}: {e}"))?;
+            println!("Exported {} posts to {path}", posts.len());
+        }
+        None => writeln!(io::stdout(), "{json}").map_err(|e| format!("Could not write the posts: {e}"))?,
+    }
+    Ok(())
+}
+
+async fn import_posts(path: &str) -> Result<(), String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Could not read {path}: {e}"))?;
+    let posts: Vec<BlogPost> =
+        serde_json::from_str(&json).map_err(|e| format!("{path} is not a post export: {e}"))?;
+
+    let mut conn = connect().await?;
+    // Import all of the posts or none of them
+    let mut transaction = conn
+        .begin()
+        .await
+        .map_err(|e| format!("Could not start a transaction: {e}"))?;
+    let mut imported = 0;
+    for post in posts {
+        let slug_taken = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM blog_posts WHERE slug = ?1)")
+            .bind(&post.slug)
+            .fetch_one(&mut *transaction)
+            .await
+            .map_err(|e| format!("Could not check the slug {}: {e}", post.slug))?;
+        if slug_taken {
+            println!("Skipped {:?}, the slug {} is taken", post.title, post.slug);
+            continue;
+        }
+        let post = insert_post(&mut transaction, post, None).await.map_err(message)?;
+        println!("Imported {:?} as post {}", post.title, post.id.unwrap_or_default());
+        imported += 1;
+    }
+    transaction
+        .commit()
+        .await
+        .map_err(|e| format!("Could not save the imported posts: {e}"))?;
+    println!("Imported {imported} posts");
+    Ok(())
+}
+
+async fn purge_sessions(all: bool) -> Result<(), String> {
+    let deleted = if all {
+        delete_all_sessions().await
+    } else {
+        cleanup_expired_sessions().await
+    }
+    .map_err(message)?;
+    println!("Deleted {deleted} sessions");
+    Ok(())
+}
+
+async fn connect() -> Result<sqlx::SqliteConnection, String> {
+    create_connection()
+        .await
+        .map_err(|e| format!("Could not open the database: {e}"))
+}
+
+async fn find_account_id(username: &str) -> Result<i32, String> {
+    get_account_by_username(username.to_string())
+        .await
+        .map_err(message)?
+        .and_then(|account| account.account_id)
+        .ok_or_else(|| format!("No account is called {username}"))
+}
+
+fn parse_role(name: &str) -> Result<Role, String> {
+    Role::ALL
+        .into_iter()
+        .find(|role| role.as_str() == name)
+        .ok_or_else(|| format!("Unknown role {name}, use admin, user or guest"))
+}
+
+/// Reads a new password from standard input, asking for it twice when typed at a terminal
+fn read_new_password() -> Result<String, String> {
+    let password = prompt("Password: ")?;
+    if io::stdin().is_terminal() && prompt("Repeat password: ")? != password {
+        return Err("The passwords don't match".to_string());
+    }
+    match password_problem(&password) {
+        Some(problem) => Err(problem),
+        None => Ok(password),
+    }
+}
+
+fn prompt(label: &str) -> Result<String, String> {
+    let _echo_off = if io::stdin().is_terminal() {
+        eprint!("{label}");
+        io::stderr().flush().ok();
+        EchoOff::new()
+    } else {
+        None
+    };
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| format!("Could not read the password: {e}"))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Stops the terminal on standard input echoing what is typed, until dropped
+#[cfg(unix)]
+struct EchoOff {
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl EchoOff {
+    /// Returns `None`, leaving echo on, if the terminal settings can't be changed
+    fn new() -> Option<Self> {
+        let mut original = std::mem::MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: tcgetattr fills in `original` when it succeeds, and it is only read then
+        let original = unsafe {
+            if libc::tcgetattr(libc::STDIN_FILENO, original.as_mut_ptr()) != 0 {
+                return None;
+            }
+            original.assume_init()
+        };
+        let mut silent = original;
+        // Still echo the newline so the next output starts on its own line
+        silent.c_lflag &= !libc::ECHO;
+        silent.c_lflag |= libc::ECHONL;
+        // SAFETY: `silent` is a valid termios copied from the terminal's own settings
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &silent) } != 0 {
+            return None;
+        }
+        Some(EchoOff { original })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        // SAFETY: restores the settings read from the same terminal in `EchoOff::new`
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+/// Other platforms echo passwords as they are typed
+#[cfg(not(unix))]
+struct EchoOff;
+
+#[cfg(not(unix))]
+impl EchoOff {
+    fn new() -> Option<Self> {
+        None
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// The message of a server function error, without the "error running server function"
+/// prefix
+fn message(e: dioxus::prelude::ServerFnError) -> String {
+    match e {
+        dioxus::prelude::ServerFnError::ServerError(message) => message,
+        e => e.to_string(),
+    }
+}
@@ -0,0 +1,422 @@
+/// Information about the client making the current request
+///
+/// The server is started with connect info (see `main.rs`), which lets server functions
+/// see the address of the peer connected to us. When the site runs behind reverse
+/// proxies, list their addresses in the `TRUSTED_PROXIES` environment variable, as IP
+/// addresses or CIDR ranges separated by commas, e.g. `127.0.0.1, 10.0.0.0/8, ::1`. The
+/// `Forwarded` and `X-Forwarded-For` headers are only believed when they were added by
+/// one of those proxies, so clients can't claim to come from another address.
+use axum::{
+    extract::ConnectInfo,
+    http::{header, HeaderMap},
+};
+use dioxus::logger::tracing::warn;
+use dioxus::prelude::*;
+use std::{
+    env,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::OnceLock,
+};
+
+/// Longest `User-Agent` stored, longer ones are cut off
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// Returns the IP address of the client making the current server function request,
+/// or `None` if it can't be determined
+///
+/// Requests through trusted proxies are followed back through their forwarding headers
+/// to the first address that isn't a trusted proxy.
+///
+/// # Examples
+/// ```ignore
+/// let ip_address = client_ip().await;
+/// ```
+pub async fn client_ip() -> Option<String> {
+    let peer = match extract::<ConnectInfo<SocketAddr>, _>().await {
+        Ok(ConnectInfo(address)) => address.ip(),
+        Err(_) => return None,
+    };
+    let context = server_context();
+    let request = context.request_parts();
+    Some(resolve_client_ip(peer, &request.headers, trusted_proxies()).to_string())
+}
+
+/// Returns the `User-Agent` header of the current server function request, or `None`
+/// if there isn't one
+pub fn client_user_agent() -> Option<String> {
+    let context = server_context();
+    let request = context.request_parts();
+    request
+        .headers
+        .get(header::USER_AGENT)
+        .and_then(|user_agent| user_agent.to_str().ok())
+        .map(|user_agent| user_agent.trim().chars().take(MAX_USER_AGENT_LENGTH).collect::<String>())
+        .filter(|user_agent| !user_agent.is_empty())
+}
+
+/// A trusted proxy address, or range of addresses
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ProxyRange {
+    network: IpAddr,
+    prefix_length: u32,
+}
+
+impl ProxyRange {
+    /// Parses an address like `10.0.0.1` or a range like `10.0.0.0/8`. The host bits of
+    /// a range are cleared, so `10.0.0.5/8` is the range `10.0.0.0/8`.
+    fn parse(value: &str) -> Option<ProxyRange> {
+        let (address, prefix_length) = match value.split_once('/') {
+            Some((address, prefix_length)) => (address, Some(prefix_length.parse().ok()?)),
+            None => (value, None),
+        };
+        let address: IpAddr = address.parse().ok()?;
+        let max_length = if address.is_ipv4() { 32 } else { 128 };
+        let prefix_length = prefix_length.unwrap_or(max_length);
+        if prefix_length > max_length {
+            return None;
+        }
+        let network = match address {
+            IpAddr::V4(address) => IpAddr::V4(Ipv4Addr::from(u32::from(address) & ipv4_mask(prefix_length))),
+            IpAddr::V6(address) => IpAddr::V6(Ipv6Addr::from(u128::from(address) & ipv6_mask(prefix_length))),
+        };
+        Some(ProxyRange {
+            network,
+            prefix_length,
+        })
+    }
+
+    fn contains(&self, address: IpAddr) -> bool {
+        match (self.network, address.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                u32::from(network) == u32::from(address) & ipv4_mask(self.prefix_length)
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                u128::from(network) == u128::from(address) & ipv6_mask(self.prefix_length)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The netmask of an IPv4 prefix of the given length
+fn ipv4_mask(prefix_length: u32) -> u32 {
+    u32::MAX.checked_shl(32 - prefix_length).unwrap_or(0)
+}
+
+/// The netmask of an IPv6 prefix of the given length
+fn ipv6_mask(prefix_length: u32) -> u128 {
+    u128::MAX.checked_shl(128 - prefix_length).unwrap_or(0)
+}
+
+/// The proxies from `TRUSTED_PROXIES`, read once
+fn trusted_proxies() -> &'static [ProxyRange] {
+    static TRUSTED_PROXIES: OnceLock<Vec<ProxyRange>> = OnceLock::new();
+    TRUSTED_PROXIES.get_or_init(|| {
+        let value = env::var("TRUSTED_PROXIES").unwrap_or_default();
+        value
+            .split([',', ' '])
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let range = ProxyRange::parse(entry);
+                if range.is_none() {
+                    warn!("Ignoring invalid TRUSTED_PROXIES entry {entry:?}");
+                }
+                range
+            })
+            .collect()
+    })
+}
+
+/// Works out the client's address from the connected peer and the forwarding headers
+///
+/// The forwarded addresses are walked from the nearest hop outwards for as long as they
+/// were added by a trusted proxy. The first address not belonging to a trusted proxy is
+/// the client. `Forwarded` takes precedence over `X-Forwarded-For` when both are sent.
+fn resolve_client_ip(peer: IpAddr, headers: &HeaderMap, trusted: &[ProxyRange]) -> IpAddr {
+    let is_trusted = |address: IpAddr| trusted.iter().any(|range| range.contains(address));
+    let peer = peer.to_canonical();
+    if !is_trusted(peer) {
+        return peer;
+    }
+
+    let forwarded = header_values(headers, header::FORWARDED);
+    let chain: Vec<Option<IpAddr>> = if !forwarded.is_empty() {
+        forwarded
+            .iter()
+            .flat_map(|value| value.split(','))
+            .map(forwarded_for)
+            .collect()
+    } else {
+        header_values(headers, "x-forwarded-for")
+            .iter()
+            .flat_map(|value| value.split(','))
+            .map(|hop| parse_forwarded_address(hop.trim()))
+            .collect()
+    };
+
+    let mut client = peer;
+    for hop in chain.into_iter().rev() {
+        // An address that can't be read ends the trail, as nothing further out can be trusted
+        let Some(hop) = hop else {
+            break;
+        };
+        client = hop.to_canonical();
+        if !is_trusted(client) {
+            break;
+        }
+    }
+    client
+}
+
+/// Every value of a header, in the order they were sent
+fn header_values(headers: &HeaderMap, name: impl header::AsHeaderName) -> Vec<&str> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect()
+}
+
+/// The `for=` address of one element of a `Forwarded` header
+fn forwarded_for(element: &str) -> Option<IpAddr> {
+    element
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("for"))
+        .and_then(|(_, value)| parse_forwarded_address(value.trim().trim_matches('"')))
+}
+
+/// Parses an address as proxies write it: `192.0.2.1`, `192.0.2.1:4711`, `2001:db8::1`
+/// or `[2001:db8::1]:4711`
+fn parse_forwarded_address(value: &str) -> Option<IpAddr> {
+    if let Ok(address) = value.parse::<IpAddr>() {
+        return Some(address);
+    }
+    if let Ok(address) = value.parse::<SocketAddr>() {
+        return Some(address.ip());
+    }
+    value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+        .and_then(|address| address.parse().ok())
+}
+
+/// Describes a `User-Agent` header in a few words, like "Firefox 128 on Windows", for
+/// showing people where they are logged in
+///
+/// Only the common browsers and operating systems are recognised, anything else is
+/// described as an unknown browser or device rather than guessed at.
+///
+/// # Examples
+///
+/// ```rust
+/// use web::client_info::describe_user_agent;
+///
+/// let device = describe_user_agent("Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0");
+/// assert_eq!(device, "Firefox 128 on Linux");
+/// ```
+pub fn describe_user_agent(user_agent: &str) -> String {
+    // Order matters, Chromium based browsers also claim to be Chrome and Safari
+    const BROWSERS: [(&str, &str); 7] = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("FxiOS/", "Firefox"),
+        ("CriOS/", "Chrome"),
+        ("Chrome/", "Chrome"),
+        ("Version/", "Safari"),
+    ];
+    const SYSTEMS: [(&str, &str); 7] = [
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Android", "Android"),
+        ("Windows", "Windows"),
+        ("CrOS", "ChromeOS"),
+        ("Mac OS X", "macOS"),
+        ("Linux", "Linux"),
+    ];
+
+    let browser = BROWSERS.iter().find_map(|(marker, name)| {
+        let version = user_agent.split(marker).nth(1)?;
+        let major_version = version
+            .split(|c: char| !c.is_ascii_digit())
+            .next()
+            .filter(|major_version| !major_version.is_empty());
+        Some(match major_version {
+            Some(major_version) => format!("{name} {major_version}"),
+            None => name.to_string(),
+        })
+    });
+    let system = SYSTEMS
+        .iter()
+        .find(|(marker, _)| user_agent.contains(marker))
+        .map(|(_, name)| *name);
+
+    match (browser, system) {
+        (Some(browser), Some(system)) => format!("{browser} on {system}"),
+        (Some(browser), None) => browser,
+        (None, Some(system)) => format!("Unknown browser on {system}"),
+        (None, None) if user_agent.trim().is_empty() => "Unknown device".to_string(),
+        (None, None) => user_agent.split_whitespace().next().unwrap_or_default().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    fn ranges(entries: &[&str]) -> Vec<ProxyRange> {
+        entries.iter().map(|entry| ProxyRange::parse(entry).unwrap()).collect()
+    }
+
+    fn request_headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn parses_addresses_and_ranges() {
+        assert_eq!(
+            ProxyRange::parse("10.0.0.1"),
+            Some(ProxyRange {
+                network: ip("10.0.0.1"),
+                prefix_length: 32
+            })
+        );
+        assert_eq!(
+            ProxyRange::parse("2001:db8::/32"),
+            Some(ProxyRange {
+                network: ip("2001:db8::"),
+                prefix_length: 32
+            })
+        );
+        assert_eq!(ProxyRange::parse("::1").map(|range| range.prefix_length), Some(128));
+        assert_eq!(ProxyRange::parse("0.0.0.0/0").map(|range| range.network), Some(ip("0.0.0.0")));
+        for invalid in ["10.0.0.0/33", "::/129", "10.0.0.0/", "10.0.0.0/x", "localhost", "10.0.0/8", ""] {
+            assert_eq!(ProxyRange::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn clears_the_host_bits_of_ranges() {
+        assert_eq!(ProxyRange::parse("10.0.0.5/8"), ProxyRange::parse("10.0.0.0/8"));
+        assert_eq!(ProxyRange::parse("10.0.0.5/8").map(|range| range.network), Some(ip("10.0.0.0")));
+        assert_eq!(ProxyRange::parse("192.168.1.77/26").map(|range| range.network), Some(ip("192.168.1.64")));
+        assert_eq!(ProxyRange::parse("2001:db8::1/32").map(|range| range.network), Some(ip("2001:db8::")));
+        assert_eq!(ProxyRange::parse("::ffff/0").map(|range| range.network), Some(ip("::")));
+        assert!(ProxyRange::parse("10.0.0.5/8").unwrap().contains(ip("10.200.3.4")));
+    }
+
+    #[test]
+    fn ranges_contain_their_addresses() {
+        let range = ProxyRange::parse("10.0.0.0/8").unwrap();
+        assert!(range.contains(ip("10.255.0.1")));
+        assert!(!range.contains(ip("11.0.0.1")));
+        // IPv4-mapped IPv6 addresses count as the IPv4 address
+        assert!(range.contains(ip("::ffff:10.1.2.3")));
+        assert!(!range.contains(ip("2001:db8::1")));
+
+        let range = ProxyRange::parse("2001:db8::/32").unwrap();
+        assert!(range.contains(ip("2001:db8:ffff::1")));
+        assert!(!range.contains(ip("2001:db9::1")));
+
+        let everything = ProxyRange::parse("0.0.0.0/0").unwrap();
+        assert!(everything.contains(ip("203.0.113.9")));
+    }
+
+    #[test]
+    fn untrusted_peer_is_the_client() {
+        let trusted = ranges(&["10.0.0.0/8"]);
+        let headers = request_headers(&[("x-forwarded-for", "198.51.100.7")]);
+        assert_eq!(resolve_client_ip(ip("203.0.113.9"), &headers, &trusted), ip("203.0.113.9"));
+        assert_eq!(resolve_client_ip(ip("203.0.113.9"), &headers, &[]), ip("203.0.113.9"));
+    }
+
+    #[test]
+    fn follows_a_chain_of_trusted_proxies() {
+        let trusted = ranges(&["10.0.0.0/8", "127.0.0.1"]);
+        let headers = request_headers(&[("x-forwarded-for", "198.51.100.7, 10.0.0.2, 10.0.0.3")]);
+        assert_eq!(resolve_client_ip(ip("127.0.0.1"), &headers, &trusted), ip("198.51.100.7"));
+
+        // Headers repeated by each proxy are read in order
+        let headers = request_headers(&[("x-forwarded-for", "198.51.100.7"), ("x-forwarded-for", "10.0.0.2")]);
+        assert_eq!(resolve_client_ip(ip("127.0.0.1"), &headers, &trusted), ip("198.51.100.7"));
+    }
+
+    #[test]
+    fn stops_at_the_first_untrusted_hop() {
+        let trusted = ranges(&["10.0.0.0/8"]);
+        // The client made up the first address, the untrusted hop after it is the client
+        let headers = request_headers(&[("x-forwarded-for", "1.2.3.4, 198.51.100.7, 10.0.0.2")]);
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &headers, &trusted), ip("198.51.100.7"));
+    }
+
+    #[test]
+    fn every_hop_trusted_is_the_outermost_address() {
+        let trusted = ranges(&["10.0.0.0/8"]);
+        let headers = request_headers(&[("x-forwarded-for", "10.0.0.3, 10.0.0.2")]);
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &headers, &trusted), ip("10.0.0.3"));
+    }
+
+    #[test]
+    fn reads_ipv6_and_ports() {
+        let trusted = ranges(&["::1", "fd00::/8"]);
+        let headers = request_headers(&[("x-forwarded-for", "2001:db8::7, [fd00::2]:443")]);
+        assert_eq!(resolve_client_ip(ip("::1"), &headers, &trusted), ip("2001:db8::7"));
+
+        let trusted = ranges(&["127.0.0.1"]);
+        let headers = request_headers(&[("x-forwarded-for", "198.51.100.7:4711")]);
+        assert_eq!(resolve_client_ip(ip("127.0.0.1"), &headers, &trusted), ip("198.51.100.7"));
+    }
+
+    #[test]
+    fn reads_forwarded_with_bracketed_and_quoted_values() {
+        let trusted = ranges(&["10.0.0.0/8"]);
+        let headers = request_headers(&[(
+            "forwarded",
+            r#"for="[2001:db8:cafe::17]:4711";proto=https, For=10.0.0.2;by=10.0.0.1"#,
+        )]);
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &headers, &trusted), ip("2001:db8:cafe::17"));
+    }
+
+    #[test]
+    fn forwarded_takes_precedence_over_x_forwarded_for() {
+        let trusted = ranges(&["10.0.0.0/8"]);
+        let headers = request_headers(&[("forwarded", "for=198.51.100.7"), ("x-forwarded-for", "203.0.113.9")]);
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &headers, &trusted), ip("198.51.100.7"));
+    }
+
+    #[test]
+    fn malformed_hops_end_the_trail() {
+        let trusted = ranges(&["10.0.0.0/8"]);
+        // Nothing beyond a hop that can't be read is believed
+        let headers = request_headers(&[("x-forwarded-for", "198.51.100.7, not-an-address, 10.0.0.2")]);
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &headers, &trusted), ip("10.0.0.2"));
+
+        let headers = request_headers(&[("x-forwarded-for", "garbage")]);
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &headers, &trusted), ip("10.0.0.1"));
+
+        // Obfuscated and unknown Forwarded identifiers aren't addresses either
+        for value in ["for=unknown", "for=_hidden", "proto=https", "for=\"[2001:db8::1\""] {
+            let headers = HeaderMap::from_iter([(header::FORWARDED, HeaderValue::from_str(value).unwrap())]);
+            assert_eq!(resolve_client_ip(ip("10.0.0.1"), &headers, &trusted), ip("10.0.0.1"), "{value}");
+        }
+    }
+
+    #[test]
+    fn ipv4_mapped_peer_is_canonical() {
+        let trusted = ranges(&["127.0.0.1"]);
+        let headers = request_headers(&[("x-forwarded-for", "198.51.100.7")]);
+        assert_eq!(resolve_client_ip(ip("::ffff:127.0.0.1"), &headers, &trusted), ip("198.51.100.7"));
+        assert_eq!(resolve_client_ip(ip("::ffff:203.0.113.9"), &headers, &trusted), ip("203.0.113.9"));
+    }
+}
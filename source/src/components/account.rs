@@ -3,11 +3,16 @@ use crate::models::Role;
 use dioxus::logger::tracing::{error, info, warn};
 use dioxus::prelude::*;
 
+/// Form for creating a new account
+///
+/// # Arguments
+/// * `on_created` - Called after the account has been created
 #[component]
-pub fn AddAccount() -> Element {
+pub fn AddAccount(#[props(optional)] on_created: Option<Callback<()>>) -> Element {
     let mut username = use_signal(|| "".to_string());
     let mut password = use_signal(|| "".to_string());
     let mut role = use_signal(|| Role::Guest );
+    let mut status_message = use_signal(|| "".to_string());
 
     rsx! {
         form {
@@ -28,16 +33,22 @@ pub fn AddAccount() -> Element {
                         error!("Cannot have empty password");
                     }
                     if is_error {
+                        status_message.set("Please enter both username and password".to_string());
                         return;
                     }
 
-                    let role_value = (*role.read()).clone();
+                    let role_value = *role.read();
                     match save_new_account(username_str, password_str, role_value).await {
                         Ok(new_account) => {
                             info!("New account created:{:?}", new_account);
+                            status_message.set(format!("Account {} created", new_account.username));
+                            if let Some(on_created) = on_created {
+                                on_created.call(());
+                            }
                         },
                         Err(err) => {
                             error!("Error ocurred during account creation:{}", err);
+                            status_message.set(format!("{err}"));
                         }
                     }
                 });
@@ -71,11 +82,15 @@ pub fn AddAccount() -> Element {
                 option { value: "", "Select a role..." },  // Default option
                 option { value: "admin", "Admin" },
                 option { value: "user", "User" },
+                option { value: "guest", "Guest" },
             },
             button {
                 r#type: "submit",
                 "create new user"
             }
+            if !status_message.read().is_empty() {
+                p { "{status_message}" }
+            }
         }
     }
 }
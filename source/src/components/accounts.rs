@@ -0,0 +1,257 @@
+use crate::{
+    auth::CurrentUser,
+    components::{AddAccount, PasswordResetIssuer},
+    models::{delete_account, get_accounts, set_account_active, update_account_role, AccountSummary, Role},
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+/// Paginated list of every account with controls to change its role, disable or enable
+/// it, issue a password reset link and delete it, along with the form to add new
+/// accounts. Must be rendered inside an `AdminGuard`.
+#[component]
+pub fn AccountManagement() -> Element {
+    let current_user = use_context::<Signal<Option<CurrentUser>>>();
+    let mut page = use_signal(|| 1_u32);
+    let mut status_message = use_signal(|| "".to_string());
+    // The account a password reset link is being issued for
+    let mut resetting = use_signal(|| None::<(i32, String)>);
+
+    let mut account_page = use_resource(move || async move { get_accounts(page()).await });
+
+    let change_role = use_callback(move |(account_id, role): (i32, Role)| {
+        spawn(async move {
+            match update_account_role(account_id, role).await {
+                Ok(()) => {
+                    info!("Account {account_id} is now {}", role.as_str());
+                    status_message.set(format!("Account #{account_id} is now {}", role.as_str()));
+                    account_page.restart();
+                }
+                Err(e) => {
+                    error!("Failed to change role of account {account_id}: {e}");
+                    status_message.set(format!("Failed to change role: {e}"));
+                    account_page.restart();
+                }
+            }
+        });
+    });
+
+    let set_active = use_callback(move |(account_id, is_active): (i32, bool)| {
+        spawn(async move {
+            match set_account_active(account_id, is_active).await {
+                Ok(()) => {
+                    let change = if is_active { "enabled" } else { "disabled" };
+                    info!("Account {account_id} {change}");
+                    status_message.set(format!("Account #{account_id} {change}"));
+                    account_page.restart();
+                }
+                Err(e) => {
+                    error!("Failed to update account {account_id}: {e}");
+                    status_message.set(format!("Failed to update account: {e}"));
+                }
+            }
+        });
+    });
+
+    let delete = use_callback(move |account_id: i32| {
+        spawn(async move {
+            match delete_account(account_id).await {
+                Ok(()) => {
+                    info!("Account {account_id} deleted");
+                    status_message.set(format!("Account #{account_id} deleted"));
+                    account_page.restart();
+                }
+                Err(e) => {
+                    error!("Failed to delete account {account_id}: {e}");
+                    status_message.set(format!("Failed to delete account: {e}"));
+                }
+            }
+        });
+    });
+
+    let own_account_id = current_user.read().as_ref().map(|user| user.account_id);
+
+    let account_list = match &*account_page.read() {
+        Some(Ok(account_page)) if account_page.accounts.is_empty() => rsx! {
+            p { "No accounts." }
+        },
+        Some(Ok(account_page)) => {
+            let page_count = account_page.page_count();
+            let current_page = account_page.page;
+            rsx! {
+                table {
+                    style: "width: 100%; border-collapse: collapse;",
+                    thead {
+                        tr {
+                            th { style: "text-align: left;", "Username" }
+                            th { style: "text-align: left;", "Role" }
+                            th { style: "text-align: left;", "Status" }
+                            th { style: "text-align: left;", "Sessions" }
+                            th { style: "text-align: left;", "Actions" }
+                        }
+                    }
+                    tbody {
+                        for account in account_page.accounts.iter().cloned() {
+                            AccountRow {
+                                key: "{account.account_id}",
+                                is_own_account: own_account_id == Some(account.account_id),
+                                account,
+                                on_change_role: move |change| change_role.call(change),
+                                on_set_active: move |change| set_active.call(change),
+                                on_delete: move |account_id| delete.call(account_id),
+                                on_reset_password: move |account| resetting.set(Some(account)),
+                            }
+                        }
+                    }
+                }
+                if page_count > 1 {
+                    div {
+                        style: "display: flex; gap: 20px; align-items: center; margin-top: 10px;",
+                        if current_page > 1 {
+                            button {
+                                onclick: move |_| page.set(current_page - 1),
+                                "Previous"
+                            }
+                        }
+                        span {
+                            style: "color: #888;",
+                            "Page {current_page} of {page_count}"
+                        }
+                        if current_page < page_count {
+                            button {
+                                onclick: move |_| page.set(current_page + 1),
+                                "Next"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Some(Err(e)) => {
+            error!("Error loading accounts: {e}");
+            rsx! {
+                p { "Error loading accounts." }
+            }
+        }
+        None => rsx! {
+            p { "Loading accounts..." }
+        },
+    };
+
+    rsx! {
+        div {
+            class: "account-management",
+            h1 { "Accounts" }
+            if !status_message.read().is_empty() {
+                p {
+                    class: "status-message",
+                    "{status_message}"
+                }
+            }
+            if let Some((account_id, username)) = resetting() {
+                PasswordResetIssuer {
+                    key: "{account_id}",
+                    account_id,
+                    username,
+                    on_close: move |_| resetting.set(None),
+                }
+            }
+            {account_list}
+            br {}
+            AddAccount {
+                on_created: move |_| account_page.restart(),
+            }
+        }
+    }
+}
+
+/// A single account in the accounts console. The admin's own account can't be changed
+/// from here.
+#[component]
+fn AccountRow(
+    account: AccountSummary,
+    is_own_account: bool,
+    on_change_role: EventHandler<(i32, Role)>,
+    on_set_active: EventHandler<(i32, bool)>,
+    on_delete: EventHandler<i32>,
+    on_reset_password: EventHandler<(i32, String)>,
+) -> Element {
+    let id = account.account_id;
+    let is_active = account.is_active;
+    let mut confirm_delete = use_signal(|| false);
+
+    let mut status = vec![if is_active { "active" } else { "disabled" }];
+    if account.totp_enabled {
+        status.push("2FA");
+    }
+    if account.locked_until.is_some() {
+        status.push("locked");
+    }
+    let status = status.join(", ");
+    let username = if is_own_account {
+        format!("{} (you)", account.username)
+    } else {
+        account.username.clone()
+    };
+
+    rsx! {
+        tr {
+            style: if is_active { "" } else { "color: #888;" },
+            td { "{username}" }
+            td {
+                select {
+                    disabled: is_own_account,
+                    onchange: move |event| {
+                        if let Some(role) = Role::ALL.into_iter().find(|role| role.as_str() == event.value()) {
+                            on_change_role.call((id, role));
+                        }
+                    },
+                    for role in Role::ALL {
+                        option {
+                            value: role.as_str(),
+                            selected: role as i32 == account.role_id,
+                            "{role:?}"
+                        }
+                    }
+                }
+            }
+            td { "{status}" }
+            td { "{account.active_sessions}" }
+            td {
+                if !is_own_account {
+                    button {
+                        style: "margin-right: 5px;",
+                        onclick: move |_| on_set_active.call((id, !is_active)),
+                        if is_active { "Disable" } else { "Enable" }
+                    }
+                    button {
+                        style: "margin-right: 5px;",
+                        onclick: move |_| on_reset_password.call((id, account.username.clone())),
+                        "Reset password"
+                    }
+                    if confirm_delete() {
+                        button {
+                            style: "margin-right: 5px; color: red;",
+                            onclick: move |_| {
+                                confirm_delete.set(false);
+                                on_delete.call(id);
+                            },
+                            "Really delete"
+                        }
+                        button {
+                            onclick: move |_| confirm_delete.set(false),
+                            "Cancel"
+                        }
+                    } else {
+                        button {
+                            onclick: move |_| confirm_delete.set(true),
+                            "Delete"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
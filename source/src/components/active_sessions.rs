@@ -0,0 +1,231 @@
+use crate::{
+    auth::{
+        get_all_sessions, get_my_sessions, revoke_any_session, revoke_my_session,
+        sign_out_other_sessions, ActiveSession, CurrentUser,
+    },
+    models::Role,
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+/// Lists the sessions of the logged in account with buttons to sign out any of them or
+/// every one but the current session. Admins also see the sessions of every account.
+/// Must be rendered inside an `AdminGuard`.
+#[component]
+pub fn ActiveSessions() -> Element {
+    let current_user = use_context::<Signal<Option<CurrentUser>>>();
+    let mut status_message = use_signal(|| "".to_string());
+    let mut sessions = use_resource(|| async move { get_my_sessions().await });
+    let is_admin = current_user
+        .read()
+        .as_ref()
+        .is_some_and(|user| user.role_id == Role::Admin as i32);
+
+    let revoke = use_callback(move |handle: String| {
+        spawn(async move {
+            match revoke_my_session(handle).await {
+                Ok(()) => {
+                    info!("Session revoked");
+                    status_message.set("Session signed out".to_string());
+                    sessions.restart();
+                }
+                Err(e) => {
+                    error!("Failed to revoke session: {e}");
+                    status_message.set(format!("Failed to sign out session: {e}"));
+                }
+            }
+        });
+    });
+
+    let sign_out_others = move |_| {
+        spawn(async move {
+            match sign_out_other_sessions().await {
+                Ok(count) => {
+                    status_message.set(format!("Signed out {count} other sessions"));
+                    sessions.restart();
+                }
+                Err(e) => {
+                    error!("Failed to sign out other sessions: {e}");
+                    status_message.set(format!("Failed to sign out other sessions: {e}"));
+                }
+            }
+        });
+    };
+
+    let session_list = match &*sessions.read() {
+        Some(Ok(session_list)) => rsx! {
+            SessionTable {
+                sessions: session_list.clone(),
+                show_username: false,
+                on_revoke: move |handle| revoke.call(handle),
+            }
+        },
+        Some(Err(e)) => {
+            error!("Error loading sessions: {e}");
+            rsx! {
+                p { "Error loading sessions." }
+            }
+        }
+        None => rsx! {
+            p { "Loading sessions..." }
+        },
+    };
+
+    rsx! {
+        div {
+            class: "active-sessions",
+            h1 { "Active Sessions" }
+            p { "These are the browsers where you are logged in." }
+            if !status_message.read().is_empty() {
+                p {
+                    class: "status-message",
+                    "{status_message}"
+                }
+            }
+            button {
+                style: "margin-bottom: 10px;",
+                onclick: sign_out_others,
+                "Sign out everywhere else"
+            }
+            {session_list}
+            if is_admin {
+                br {}
+                AllSessions {}
+            }
+        }
+    }
+}
+
+/// Lists the sessions of every account with a button to sign out any of them. Must be
+/// rendered inside an `AdminGuard`.
+#[component]
+fn AllSessions() -> Element {
+    let mut status_message = use_signal(|| "".to_string());
+    let mut sessions = use_resource(|| async move { get_all_sessions().await });
+
+    let revoke = use_callback(move |handle: String| {
+        spawn(async move {
+            match revoke_any_session(handle).await {
+                Ok(()) => {
+                    status_message.set("Session signed out".to_string());
+                    sessions.restart();
+                }
+                Err(e) => {
+                    error!("Failed to revoke session: {e}");
+                    status_message.set(format!("Failed to sign out session: {e}"));
+                }
+            }
+        });
+    });
+
+    let session_list = match &*sessions.read() {
+        Some(Ok(session_list)) => rsx! {
+            SessionTable {
+                sessions: session_list.clone(),
+                show_username: true,
+                on_revoke: move |handle| revoke.call(handle),
+            }
+        },
+        Some(Err(e)) => {
+            error!("Error loading sessions: {e}");
+            rsx! {
+                p { "Error loading sessions." }
+            }
+        }
+        None => rsx! {
+            p { "Loading sessions..." }
+        },
+    };
+
+    rsx! {
+        div {
+            class: "all-sessions",
+            h2 { "All Accounts" }
+            if !status_message.read().is_empty() {
+                p {
+                    class: "status-message",
+                    "{status_message}"
+                }
+            }
+            button {
+                style: "margin-bottom: 10px;",
+                onclick: move |_| sessions.restart(),
+                "Refresh"
+            }
+            {session_list}
+        }
+    }
+}
+
+/// A table of sessions with a sign out button for each
+#[component]
+fn SessionTable(sessions: Vec<ActiveSession>, show_username: bool, on_revoke: EventHandler<String>) -> Element {
+    if sessions.is_empty() {
+        return rsx! {
+            p { "No active sessions." }
+        };
+    }
+
+    rsx! {
+        table {
+            style: "width: 100%; border-collapse: collapse;",
+            thead {
+                tr {
+                    if show_username {
+                        th { style: "text-align: left;", "Account" }
+                    }
+                    th { style: "text-align: left;", "Device" }
+                    th { style: "text-align: left;", "IP address" }
+                    th { style: "text-align: left;", "Signed in" }
+                    th { style: "text-align: left;", "Last active" }
+                    th {}
+                }
+            }
+            tbody {
+                for session in sessions {
+                    SessionRow {
+                        key: "{session.handle}",
+                        session,
+                        show_username,
+                        on_revoke,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A single session
+#[component]
+fn SessionRow(session: ActiveSession, show_username: bool, on_revoke: EventHandler<String>) -> Element {
+    let created_at = session.created_at.format("%d/%m/%Y %H:%M").to_string();
+    let last_accessed = session.last_accessed.format("%d/%m/%Y %H:%M").to_string();
+    let ip_address = session.ip_address.clone().unwrap_or_else(|| "unknown".to_string());
+    let user_agent = session.user_agent.clone().unwrap_or_default();
+    let device = if session.is_current {
+        format!("{} (this browser)", session.device)
+    } else {
+        session.device.clone()
+    };
+    let handle = session.handle.clone();
+
+    rsx! {
+        tr {
+            if show_username {
+                td { "{session.username}" }
+            }
+            td { title: "{user_agent}", "{device}" }
+            td { "{ip_address}" }
+            td { "{created_at}" }
+            td { "{last_accessed}" }
+            td {
+                button {
+                    onclick: move |_| on_revoke.call(handle.clone()),
+                    if session.is_current { "Sign out" } else { "Revoke" }
+                }
+            }
+        }
+    }
+}
@@ -1,24 +1,84 @@
 use crate::{
-    auth::{validate_login, validate_session},
-    components::{MaintenanceSettings, NewEditBlog, AddAccount},
-    models::BlogPost,
+    auth::validate_login,
+    components::{
+        ChangePassword, CommentModerationQueue, FailedLoginAttempts, MaintenanceSettings,
+        NewEditBlog, ScheduledJobs, SecuritySettingsPanel, TwoFactorSettings,
+    },
+    models::get_admin_post_list,
+    route::Route,
 };
-use dioxus::logger::tracing::{error, info, warn};
-use dioxus::prelude::{server_fn::error::Result, *};
-use serde::{Deserialize, Serialize};
+use dioxus::logger::tracing::{error, info};
+use dioxus::prelude::*;
 
+/// The admin settings page. Only rendered inside an `AdminGuard`, which takes care of
+/// checking the session before any of this is shown.
 #[component]
 pub fn AdminView() -> Element {
-    // TODO: check to see if the user is verifiedly logged in, maybe do some
-    // cookie magic or session management stuff
     rsx! {
         div {
             h1 { "Admin Settings" }
+            Link { to: Route::AdminAccounts {}, "Manage accounts" }
+            " "
+            Link { to: Route::Sessions {}, "Active sessions" }
+            " "
+            Link { to: Route::AdminApiTokens {}, "API tokens" }
+            br {}
             MaintenanceSettings {}
             br {}
-            NewEditBlog {}
+            AdminPostList {}
+            br {}
+            CommentModerationQueue {}
+            br {}
+            FailedLoginAttempts {}
+            br {}
+            ScheduledJobs {}
+            br {}
+            ChangePassword {}
+            br {}
+            TwoFactorSettings {}
             br {}
-            AddAccount {}
+            SecuritySettingsPanel {}
+            br {}
+            NewEditBlog {}
+        }
+    }
+}
+
+/// Lists every blog post, including drafts, with links to preview and edit it
+#[component]
+pub fn AdminPostList() -> Element {
+    let post_list = use_resource(|| async move { get_admin_post_list().await });
+
+    rsx! {
+        div {
+            class: "admin-post-list",
+            h1 { "Manage Blog Posts" }
+            match &*post_list.read() {
+                Some(Ok(posts)) => rsx! {
+                    ul {
+                        for post in posts.iter() {
+                            li {
+                                key: "{post.id.unwrap_or(-1)}",
+                                "{post.title} ({post.status:?}) "
+                                Link {
+                                    to: Route::PreviewPost { id: post.id.unwrap_or(-1) },
+                                    "Preview"
+                                }
+                                " "
+                                Link {
+                                    to: Route::EditPost { id: post.id.unwrap_or(-1) },
+                                    "Edit"
+                                }
+                            }
+                        }
+                    }
+                },
+                Some(Err(e)) => {
+                    error!("Error loading posts for admin list: {e}");
+                    rsx! { p { "Error loading blog posts." } }
+                },
+                None => rsx! { p { "Loading blog posts..." } },
+            }
         }
     }
 }
@@ -0,0 +1,159 @@
+use crate::{
+    auth::{validate_session, logout, CurrentUser},
+    components::{LoginForm, TwoFactorSettings},
+    models::Role,
+};
+use dioxus::{
+    logger::tracing::{error, info, warn},
+    prelude::*,
+};
+
+/// Wraps admin-only pages so that the children are only rendered once an admin session
+/// has been established. Until then the login form is shown instead.
+///
+/// The session is carried by an HttpOnly cookie, so it survives page reloads and is sent
+/// along with every server function call. The logged in user is provided to the children
+/// as a `Signal<Option<CurrentUser>>` context.
+///
+/// # Arguments
+/// * `role` - The least privileged role that may see the children, admins by default.
+///   Pages for every logged in user pass `Role::Guest`.
+#[component]
+pub fn AdminGuard(#[props(default = Role::Admin)] role: Role, children: Element) -> Element {
+    let mut session_state = use_signal(|| SessionState::Loading);
+    let mut current_user = use_context_provider(|| Signal::new(None::<CurrentUser>));
+
+    // Check for a session cookie on component mount
+    use_effect(move || {
+        spawn(async move {
+            match validate_session().await {
+                Ok(Some(user)) => {
+                    info!("Valid session found for user: {}", user.username);
+                    current_user.set(Some(user));
+                    session_state.set(SessionState::Valid);
+                }
+                Ok(None) => {
+                    info!("No valid session found");
+                    session_state.set(SessionState::Invalid);
+                }
+                Err(e) => {
+                    error!("Error validating session: {}", e);
+                    session_state.set(SessionState::Invalid);
+                }
+            }
+        });
+    });
+
+    // Get the current session state value to avoid borrow conflicts
+    let current_session_state = session_state.read().clone();
+
+    match current_session_state {
+        SessionState::Loading => {
+            rsx! {
+                div {
+                    style: "text-align: center; padding: 50px;",
+                    p { "Validating session..." }
+                    p {
+                        style: "color: #666; font-size: 0.9em;",
+                        "Please wait while we check your authentication status."
+                    }
+                }
+            }
+        }
+        SessionState::Valid => {
+            // Check if we have a valid user, otherwise switch to invalid state
+            match current_user.read().as_ref() {
+                Some(user) if user.role_id > role as i32 => {
+                    warn!("User {} does not have the {role:?} role", user.username);
+                    rsx! {
+                        div {
+                            style: "text-align: center; padding: 50px;",
+                            p { "You do not have permission to view this page." }
+                        }
+                    }
+                }
+                Some(user) if role == Role::Admin && user.two_factor_setup_required => {
+                    info!("Admin {} has to set up two-factor authentication", user.username);
+                    rsx! {
+                        div {
+                            style: "max-width: 600px; margin: 0 auto; padding: 20px;",
+                            TwoFactorSettings {
+                                on_done: move |_| {
+                                    // Check again so the admin pages unlock once setup is done
+                                    spawn(async move {
+                                        match validate_session().await {
+                                            Ok(user) => current_user.set(user),
+                                            Err(e) => error!("Error validating session: {}", e),
+                                        }
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+                Some(user) => {
+                    let user_info = user.clone(); // Clone to avoid borrow issues
+                    info!("Rendering admin view for user: {}", user_info.username);
+                    rsx! {
+                        div {
+                            {children}
+                            // Add a logout option
+                            div {
+                                style: "position: fixed; top: 10px; right: 10px; background: white; padding: 10px; border: 1px solid #ddd; border-radius: 5px;",
+                                p {
+                                    style: "margin: 0 0 10px 0; font-size: 0.9em;",
+                                    "Logged in as: {user_info.username}"
+                                }
+                                button {
+                                    onclick: move |_| {
+                                        // Invalidate the session and clear its cookie
+                                        spawn(async move {
+                                            if let Err(e) = logout().await {
+                                                error!("Error logging out: {}", e);
+                                            }
+                                            current_user.set(None);
+                                            session_state.set(SessionState::Invalid);
+                                        });
+                                    },
+                                    style: "background: #dc3545; color: white; border: none; padding: 5px 10px; border-radius: 3px; cursor: pointer;",
+                                    "Logout"
+                                }
+                            }
+                        }
+                    }
+                }
+                None => {
+                    // No user data but state is Valid - this is an inconsistent state
+                    // Show loading while we fix the state
+                    rsx! {
+                        div {
+                            style: "text-align: center; padding: 50px;",
+                            p { "Refreshing session..." }
+                        }
+                    }
+                }
+            }
+        }
+        SessionState::Invalid => {
+            warn!("Invalid or no session, showing login form");
+            rsx! {
+                div {
+                    LoginForm {
+                        on_login_success: move |user: CurrentUser| {
+                            info!("Login successful, updating admin session state");
+                            current_user.set(Some(user));
+                            session_state.set(SessionState::Valid);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum SessionState {
+    Loading,
+    Valid,
+    Invalid,
+}
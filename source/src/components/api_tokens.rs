@@ -0,0 +1,236 @@
+use crate::auth::{
+    create_api_token, get_api_tokens, revoke_api_token, ApiScope, ApiToken, API_TOKEN_NAME_MAX_LENGTH,
+};
+use chrono::Utc;
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const INPUT_STYLE: &str = "display: block; width: 100%; max-width: 300px; padding: 8px; margin-bottom: 10px; border: 1px solid #ddd; border-radius: 4px;";
+
+/// How long new tokens can be made to last, in days, with `None` for no expiry
+const EXPIRY_CHOICES: [(Option<u32>, &str); 4] = [
+    (Some(30), "30 days"),
+    (Some(90), "90 days"),
+    (Some(365), "1 year"),
+    (None, "Never"),
+];
+
+/// Form to create personal API tokens for scripts, and the list of every account's tokens
+/// with a button to revoke each. A new token is shown once, right after it is created.
+/// Must be rendered inside an `AdminGuard`.
+#[component]
+pub fn ApiTokens() -> Element {
+    let mut name = use_signal(|| "".to_string());
+    let mut scopes = use_signal(Vec::<ApiScope>::new);
+    let mut expires_in_days = use_signal(|| Some(90_u32));
+    let mut status_message = use_signal(|| "".to_string());
+    // The token that was just created, shown until the page is left
+    let mut new_token = use_signal(|| None::<String>);
+    let mut tokens = use_resource(|| async move { get_api_tokens().await });
+
+    let revoke = use_callback(move |token_id: i32| {
+        spawn(async move {
+            match revoke_api_token(token_id).await {
+                Ok(()) => {
+                    info!("API token {token_id} revoked");
+                    status_message.set("Token revoked".to_string());
+                    tokens.restart();
+                }
+                Err(e) => {
+                    error!("Failed to revoke API token {token_id}: {e}");
+                    status_message.set(format!("Failed to revoke token: {e}"));
+                }
+            }
+        });
+    });
+
+    let token_list = match &*tokens.read() {
+        Some(Ok(token_list)) if token_list.is_empty() => rsx! {
+            p { "No API tokens." }
+        },
+        Some(Ok(token_list)) => rsx! {
+            table {
+                style: "width: 100%; border-collapse: collapse;",
+                thead {
+                    tr {
+                        th { style: "text-align: left;", "Name" }
+                        th { style: "text-align: left;", "Account" }
+                        th { style: "text-align: left;", "Token" }
+                        th { style: "text-align: left;", "Scopes" }
+                        th { style: "text-align: left;", "Expires" }
+                        th { style: "text-align: left;", "Last used" }
+                        th {}
+                    }
+                }
+                tbody {
+                    for token in token_list.iter().cloned() {
+                        ApiTokenRow {
+                            key: "{token.id}",
+                            token,
+                            on_revoke: move |token_id| revoke.call(token_id),
+                        }
+                    }
+                }
+            }
+        },
+        Some(Err(e)) => {
+            error!("Error loading API tokens: {e}");
+            rsx! {
+                p { "Error loading API tokens." }
+            }
+        }
+        None => rsx! {
+            p { "Loading API tokens..." }
+        },
+    };
+
+    rsx! {
+        div {
+            class: "api-tokens",
+            h1 { "API Tokens" }
+            p {
+                "Tokens let scripts act as your account without logging in, by sending "
+                code { "Authorization: Bearer <token>" }
+                ". They can only do what their scopes allow."
+            }
+            form {
+                onsubmit: move |_| {
+                    let token_name = name.read().clone();
+                    let token_scopes = scopes.read().clone();
+                    let expiry = expires_in_days();
+                    if token_scopes.is_empty() {
+                        status_message.set("Choose at least one scope".to_string());
+                        return;
+                    }
+                    spawn(async move {
+                        match create_api_token(token_name, token_scopes, expiry).await {
+                            Ok(issued) => {
+                                info!("API token {} created", issued.api_token.name);
+                                name.set("".to_string());
+                                scopes.set(Vec::new());
+                                status_message.set("".to_string());
+                                new_token.set(Some(issued.token));
+                                tokens.restart();
+                            }
+                            Err(e) => {
+                                error!("Failed to create API token: {e}");
+                                status_message.set(format!("Failed to create token: {e}"));
+                            }
+                        }
+                    });
+                },
+                input {
+                    r#type: "text",
+                    placeholder: "Token name, e.g. publishing script",
+                    maxlength: "{API_TOKEN_NAME_MAX_LENGTH}",
+                    value: "{name}",
+                    style: INPUT_STYLE,
+                    oninput: move |event| name.set(event.value()),
+                }
+                for scope in ApiScope::ALL {
+                    label {
+                        style: "display: block; margin-bottom: 5px;",
+                        input {
+                            r#type: "checkbox",
+                            checked: scopes.read().contains(&scope),
+                            onchange: move |event| {
+                                if event.checked() {
+                                    scopes.write().push(scope);
+                                } else {
+                                    scopes.write().retain(|chosen| *chosen != scope);
+                                }
+                            },
+                        }
+                        " "
+                        code { "{scope.as_str()}" }
+                        " {scope.description()}"
+                    }
+                }
+                label {
+                    style: "display: block; margin: 10px 0;",
+                    "Expires after "
+                    select {
+                        onchange: move |event| {
+                            let choice = EXPIRY_CHOICES
+                                .into_iter()
+                                .find(|(_, label)| *label == event.value());
+                            if let Some((days, _)) = choice {
+                                expires_in_days.set(days);
+                            }
+                        },
+                        for (days, label) in EXPIRY_CHOICES {
+                            option {
+                                value: label,
+                                selected: days == expires_in_days(),
+                                "{label}"
+                            }
+                        }
+                    }
+                }
+                button { r#type: "submit", "Create token" }
+            }
+            if let Some(token) = new_token() {
+                div {
+                    style: "margin: 10px 0; padding: 10px; border: 1px solid #ddd; border-radius: 4px;",
+                    p { "Copy the new token now, it won't be shown again:" }
+                    code { style: "word-break: break-all;", "{token}" }
+                    br {}
+                    button {
+                        style: "margin-top: 10px;",
+                        onclick: move |_| new_token.set(None),
+                        "Done"
+                    }
+                }
+            }
+            if !status_message.read().is_empty() {
+                p {
+                    class: "status-message",
+                    "{status_message}"
+                }
+            }
+            br {}
+            {token_list}
+        }
+    }
+}
+
+/// A single API token
+#[component]
+fn ApiTokenRow(token: ApiToken, on_revoke: EventHandler<i32>) -> Element {
+    let expires = match token.expires_at {
+        Some(_) if token.is_expired(Utc::now()) => "expired".to_string(),
+        Some(expires_at) => expires_at.format("%d/%m/%Y").to_string(),
+        None => "never".to_string(),
+    };
+    let last_used = token
+        .last_used_at
+        .map(|last_used_at| last_used_at.format("%d/%m/%Y %H:%M").to_string())
+        .unwrap_or_else(|| "never".to_string());
+    let scopes = token
+        .scopes
+        .iter()
+        .map(ApiScope::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let id = token.id;
+
+    rsx! {
+        tr {
+            style: if token.is_expired(Utc::now()) { "color: #888;" } else { "" },
+            td { "{token.name}" }
+            td { "{token.owner}" }
+            td { code { "{token.token_prefix}…" } }
+            td { "{scopes}" }
+            td { "{expires}" }
+            td { "{last_used}" }
+            td {
+                button {
+                    onclick: move |_| on_revoke.call(id),
+                    "Revoke"
+                }
+            }
+        }
+    }
+}
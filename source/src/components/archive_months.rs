@@ -0,0 +1,51 @@
+use crate::{
+    models::get_archive_months,
+    route::Route,
+};
+use chrono::Month;
+use dioxus::{logger::tracing::error, prelude::*};
+
+/// Returns the English name of a month number, e.g. 5 becomes "May"
+pub fn month_name(month: u32) -> &'static str {
+    u8::try_from(month)
+        .ok()
+        .and_then(|month| Month::try_from(month).ok())
+        .map(|month| month.name())
+        .unwrap_or("Unknown month")
+}
+
+/// Lists every month with visible posts, newest first, linking to its archive page
+#[component]
+pub fn ArchiveMonths() -> Element {
+    let archive_months = use_resource(move || async move { get_archive_months().await });
+
+    let months = match &*archive_months.read() {
+        Some(Ok(months)) if !months.is_empty() => {
+            rsx! {
+                div {
+                    class: "archive-months",
+                    style: "margin-bottom: 20px;",
+                    h3 {
+                        style: "color: #ffffff; margin-bottom: 10px;",
+                        "Archive"
+                    }
+                    for archive_month in months.iter() {
+                        Link {
+                            key: "{archive_month.year}-{archive_month.month}",
+                            to: Route::Archive { year: archive_month.year, month: archive_month.month },
+                            style: "color: #4a9eff; text-decoration: none; margin-right: 15px;",
+                            "{month_name(archive_month.month)} {archive_month.year} ({archive_month.post_count})"
+                        }
+                    }
+                }
+            }
+        }
+        Some(Ok(_)) => rsx! {},
+        Some(Err(e)) => {
+            error!("Error loading archive months: {e}");
+            rsx! {}
+        }
+        None => rsx! {},
+    };
+    months
+}
@@ -0,0 +1,169 @@
+use crate::{
+    models::{delete_comment, get_moderation_queue, moderate_comment, CommentStatus, ModerationComment},
+    route::Route,
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+/// Moderation queue for reader comments, listing the comments with the selected status
+/// with buttons to approve, reject, mark as spam or delete them. Must be rendered inside
+/// an `AdminGuard`.
+#[component]
+pub fn CommentModerationQueue() -> Element {
+    let mut filter = use_signal(CommentStatus::default);
+    let mut status_message = use_signal(|| "".to_string());
+
+    let mut queue = use_resource(move || async move { get_moderation_queue(filter()).await });
+
+    let moderate = use_callback(move |(comment_id, status): (i32, CommentStatus)| {
+        spawn(async move {
+            match moderate_comment(comment_id, status).await {
+                Ok(_) => {
+                    info!("Comment {comment_id} marked {}", status.as_str());
+                    status_message.set(format!("Comment #{comment_id} marked {}", status.as_str()));
+                    queue.restart();
+                }
+                Err(e) => {
+                    error!("Failed to moderate comment {comment_id}: {e}");
+                    status_message.set(format!("Failed to moderate comment: {e}"));
+                }
+            }
+        });
+    });
+
+    let delete = use_callback(move |comment_id: i32| {
+        spawn(async move {
+            match delete_comment(comment_id).await {
+                Ok(_) => {
+                    info!("Comment {comment_id} deleted");
+                    status_message.set(format!("Comment #{comment_id} deleted"));
+                    queue.restart();
+                }
+                Err(e) => {
+                    error!("Failed to delete comment {comment_id}: {e}");
+                    status_message.set(format!("Failed to delete comment: {e}"));
+                }
+            }
+        });
+    });
+
+    let comment_list = match &*queue.read() {
+        Some(Ok(comments)) if comments.is_empty() => rsx! {
+            p { "No {filter().as_str()} comments." }
+        },
+        Some(Ok(comments)) => rsx! {
+            for comment in comments.iter().cloned() {
+                ModerationEntry {
+                    key: "{comment.id}",
+                    comment,
+                    on_moderate: move |change| moderate.call(change),
+                    on_delete: move |comment_id| delete.call(comment_id),
+                }
+            }
+        },
+        Some(Err(e)) => {
+            error!("Error loading moderation queue: {e}");
+            rsx! {
+                p { "Error loading comments." }
+            }
+        }
+        None => rsx! {
+            p { "Loading comments..." }
+        },
+    };
+
+    rsx! {
+        div {
+            class: "comment-moderation",
+            h1 { "Moderate Comments" }
+            div {
+                style: "margin-bottom: 10px;",
+                for status in [CommentStatus::Pending, CommentStatus::Approved, CommentStatus::Rejected, CommentStatus::Spam] {
+                    button {
+                        style: if filter() == status { "font-weight: bold; margin-right: 5px;" } else { "margin-right: 5px;" },
+                        onclick: move |_| {
+                            status_message.set("".to_string());
+                            filter.set(status);
+                        },
+                        "{status:?}"
+                    }
+                }
+            }
+            if !status_message.read().is_empty() {
+                p {
+                    class: "status-message",
+                    "{status_message}"
+                }
+            }
+            {comment_list}
+        }
+    }
+}
+
+/// A single comment in the moderation queue
+#[component]
+fn ModerationEntry(
+    comment: ModerationComment,
+    on_moderate: EventHandler<(i32, CommentStatus)>,
+    on_delete: EventHandler<i32>,
+) -> Element {
+    let id = comment.id;
+    let author = format!(
+        " <{}> from {} on ",
+        comment.author_email,
+        comment.ip_address.as_deref().unwrap_or("unknown IP")
+    );
+    let submitted_at = comment
+        .created_at
+        .map(|created_at| format!(" at {}", created_at.format("%d/%m/%Y %H:%M")))
+        .unwrap_or_default();
+    rsx! {
+        div {
+            class: "moderation-entry",
+            style: "border: 1px solid #444; border-radius: 5px; padding: 10px; margin-bottom: 10px;",
+            p {
+                style: "margin: 0;",
+                strong { "{comment.author_name}" }
+                "{author}"
+                Link {
+                    to: Route::Blog { slug: comment.post_slug.clone() },
+                    "{comment.post_title}"
+                }
+                "{submitted_at}"
+                if let Some(parent_id) = comment.parent_id {
+                    " in reply to #{parent_id}"
+                }
+            }
+            p {
+                style: "white-space: pre-wrap;",
+                "{comment.content}"
+            }
+            for status in [CommentStatus::Approved, CommentStatus::Rejected, CommentStatus::Spam] {
+                if comment.status != status {
+                    button {
+                        style: "margin-right: 5px;",
+                        onclick: move |_| on_moderate.call((id, status)),
+                        {moderate_label(status)}
+                    }
+                }
+            }
+            button {
+                style: "background: #dc3545; color: white; border: none; padding: 5px 10px; border-radius: 3px; cursor: pointer;",
+                onclick: move |_| on_delete.call(id),
+                "Delete"
+            }
+        }
+    }
+}
+
+/// Returns the label of the button that moves a comment to `status`
+fn moderate_label(status: CommentStatus) -> &'static str {
+    match status {
+        CommentStatus::Pending => "Return to queue",
+        CommentStatus::Approved => "Approve",
+        CommentStatus::Rejected => "Reject",
+        CommentStatus::Spam => "Mark as spam",
+    }
+}
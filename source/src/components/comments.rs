@@ -0,0 +1,191 @@
+use crate::models::{get_post_comments, submit_comment, CommentSubmission, CommentThread};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+/// Shows the approved comments on a post as threads, with a form for leaving a new
+/// comment or replying to an existing one
+#[component]
+pub fn CommentSection(post_id: i32) -> Element {
+    let comments = use_resource(use_reactive!(|post_id| async move { get_post_comments(post_id).await }));
+    // The comment whose reply form is open, if any
+    let reply_to = use_signal(|| None::<i32>);
+
+    let comment_list = match &*comments.read() {
+        Some(Ok(threads)) if threads.is_empty() => rsx! {
+            p {
+                style: "color: #888;",
+                "No comments yet. Be the first!"
+            }
+        },
+        Some(Ok(threads)) => rsx! {
+            for thread in threads.iter().cloned() {
+                CommentThreadView { key: "{thread.comment.id}", thread, reply_to }
+            }
+        },
+        Some(Err(e)) => {
+            error!("Error loading comments for post {post_id}: {e}");
+            rsx! {
+                p { "Error loading comments." }
+            }
+        }
+        None => rsx! {
+            p { "Loading comments..." }
+        },
+    };
+
+    rsx! {
+        div {
+            class: "comment-section",
+            style: "margin-top: 40px; border-top: 1px solid #333; padding-top: 20px;",
+            h2 { "Comments" }
+            {comment_list}
+            h3 { "Leave a comment" }
+            CommentForm { post_id, parent_id: None }
+        }
+    }
+}
+
+/// A comment and its replies, indented under it
+#[component]
+fn CommentThreadView(thread: CommentThread, reply_to: Signal<Option<i32>>) -> Element {
+    let comment = thread.comment;
+    let comment_id = comment.id;
+    let replying = reply_to() == Some(comment_id);
+
+    rsx! {
+        div {
+            class: "comment",
+            id: "comment-{comment_id}",
+            style: "margin-bottom: 15px;",
+            p {
+                style: "margin: 0; font-weight: bold;",
+                "{comment.author_name}"
+                if let Some(created_at) = comment.created_at {
+                    span {
+                        style: "color: #888; font-weight: normal; margin-left: 10px;",
+                        "{created_at.format(\"%d/%m/%Y\")}"
+                    }
+                }
+            }
+            p {
+                style: "margin: 4px 0; white-space: pre-wrap;",
+                "{comment.content}"
+            }
+            button {
+                onclick: move |_| {
+                    reply_to.set(if replying { None } else { Some(comment_id) });
+                },
+                if replying { "Cancel reply" } else { "Reply" }
+            }
+            if replying {
+                CommentForm { post_id: comment.post_id, parent_id: Some(comment_id) }
+            }
+            div {
+                class: "comment-replies",
+                style: "margin-left: 25px; margin-top: 10px; border-left: 2px solid #333; padding-left: 10px;",
+                for reply in thread.replies {
+                    CommentThreadView { key: "{reply.comment.id}", thread: reply, reply_to }
+                }
+            }
+        }
+    }
+}
+
+/// Form for submitting a comment to the moderation queue
+#[component]
+fn CommentForm(post_id: i32, parent_id: Option<i32>) -> Element {
+    let mut author_name = use_signal(|| "".to_string());
+    let mut author_email = use_signal(|| "".to_string());
+    let mut content = use_signal(|| "".to_string());
+    let mut website = use_signal(|| "".to_string());
+    let mut status_message = use_signal(|| "".to_string());
+    let mut submitted = use_signal(|| false);
+
+    if submitted() {
+        return rsx! {
+            p {
+                class: "status-message",
+                "Thanks! Your comment will appear once it has been approved."
+            }
+        };
+    }
+
+    rsx! {
+        form {
+            class: "comment-form",
+            style: "display:flex; flex-direction:column; max-width: 600px;",
+            onsubmit: move |_| {
+                spawn(async move {
+                    let submission = CommentSubmission {
+                        post_id,
+                        parent_id,
+                        author_name: author_name.read().to_string(),
+                        author_email: author_email.read().to_string(),
+                        content: content.read().to_string(),
+                        website: website.read().to_string(),
+                    };
+                    match submit_comment(submission).await {
+                        Ok(()) => {
+                            info!("Comment submitted on post {post_id}");
+                            submitted.set(true);
+                        }
+                        Err(e) => {
+                            error!("Failed to submit comment: {e}");
+                            status_message.set(format!("Failed to submit comment: {e}"));
+                        }
+                    }
+                });
+            },
+            if !status_message.read().is_empty() {
+                p {
+                    class: "status-message",
+                    "{status_message}"
+                }
+            }
+            label { "Name:" }
+            input {
+                r#type: "text",
+                name: "author_name",
+                required: true,
+                value: "{author_name}",
+                oninput: move |input_event| author_name.set(input_event.value()),
+            }
+            label { "Email (not shown publicly):" }
+            input {
+                r#type: "email",
+                name: "author_email",
+                required: true,
+                value: "{author_email}",
+                oninput: move |input_event| author_email.set(input_event.value()),
+            }
+            // Honeypot, hidden from people but filled in by most spam bots
+            div {
+                style: "display: none;",
+                "aria-hidden": "true",
+                label { "Website:" }
+                input {
+                    r#type: "text",
+                    name: "website",
+                    tabindex: "-1",
+                    autocomplete: "off",
+                    value: "{website}",
+                    oninput: move |input_event| website.set(input_event.value()),
+                }
+            }
+            label { "Comment:" }
+            textarea {
+                name: "content",
+                required: true,
+                rows: "5",
+                value: "{content}",
+                oninput: move |input_event| content.set(input_event.value()),
+            }
+            button {
+                r#type: "submit",
+                if parent_id.is_some() { "Post reply" } else { "Post comment" }
+            }
+        }
+    }
+}
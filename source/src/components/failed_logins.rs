@@ -0,0 +1,82 @@
+use crate::models::{get_failed_login_attempts, LoginAttempt, LoginOutcome};
+use dioxus::{logger::tracing::error, prelude::*};
+
+/// Lists the most recent failed, throttled and locked out login attempts so admins can
+/// spot brute force attempts. Must be rendered inside an `AdminGuard`.
+#[component]
+pub fn FailedLoginAttempts() -> Element {
+    let mut attempts = use_resource(|| async move { get_failed_login_attempts().await });
+
+    let attempt_list = match &*attempts.read() {
+        Some(Ok(attempt_list)) if attempt_list.is_empty() => rsx! {
+            p { "No failed login attempts." }
+        },
+        Some(Ok(attempt_list)) => rsx! {
+            table {
+                style: "width: 100%; border-collapse: collapse;",
+                thead {
+                    tr {
+                        th { style: "text-align: left;", "Time" }
+                        th { style: "text-align: left;", "Username" }
+                        th { style: "text-align: left;", "IP address" }
+                        th { style: "text-align: left;", "Outcome" }
+                        th { style: "text-align: left;", "User agent" }
+                    }
+                }
+                tbody {
+                    for attempt in attempt_list.iter().cloned() {
+                        FailedLoginRow { key: "{attempt.id}", attempt }
+                    }
+                }
+            }
+        },
+        Some(Err(e)) => {
+            error!("Error loading failed login attempts: {e}");
+            rsx! {
+                p { "Error loading failed login attempts." }
+            }
+        }
+        None => rsx! {
+            p { "Loading failed login attempts..." }
+        },
+    };
+
+    rsx! {
+        div {
+            class: "failed-login-attempts",
+            h1 { "Failed Logins" }
+            button {
+                style: "margin-bottom: 10px;",
+                onclick: move |_| attempts.restart(),
+                "Refresh"
+            }
+            {attempt_list}
+        }
+    }
+}
+
+/// A single failed login attempt
+#[component]
+fn FailedLoginRow(attempt: LoginAttempt) -> Element {
+    let attempted_at = attempt
+        .attempted_at
+        .map(|attempted_at| attempted_at.format("%d/%m/%Y %H:%M:%S").to_string())
+        .unwrap_or_default();
+    let outcome = match attempt.outcome {
+        LoginOutcome::Success => "succeeded",
+        LoginOutcome::Failure => "wrong credentials",
+        LoginOutcome::Throttled => "throttled",
+        LoginOutcome::Locked => "account locked",
+    };
+    let ip_address = attempt.ip_address.unwrap_or_else(|| "unknown".to_string());
+    let user_agent = attempt.user_agent.unwrap_or_default();
+    rsx! {
+        tr {
+            td { "{attempted_at}" }
+            td { "{attempt.username}" }
+            td { "{ip_address}" }
+            td { "{outcome}" }
+            td { style: "color: #888; font-size: 0.9em;", "{user_agent}" }
+        }
+    }
+}
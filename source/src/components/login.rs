@@ -1,4 +1,4 @@
-use crate::auth::{login_with_session, CurrentUser};
+use crate::auth::{login_with_session, logout, verify_two_factor_login, CurrentUser};
 use dioxus::logger::tracing::{error, info};
 use dioxus::prelude::*;
 
@@ -15,6 +15,9 @@ pub fn LoginForm(props: LoginFormProps) -> Element {
     let mut login_status = use_signal(|| "".to_string());
     let mut is_loading = use_signal(|| false);
     let mut current_user = use_signal(|| None::<CurrentUser>);
+    // Set once the password was accepted for an account with two-factor authentication
+    let mut awaiting_code = use_signal(|| false);
+    let mut code = use_signal(|| "".to_string());
 
     rsx! {
         div {
@@ -42,15 +45,99 @@ pub fn LoginForm(props: LoginFormProps) -> Element {
                     h3 { "Welcome, {user.username}!" }
                     p { "Account ID: {user.account_id}" }
                     p { "Role ID: {user.role_id}" }
-                    p { "Session ID: {user.session_id}" }
                     button {
                         onclick: move |_| {
-                            current_user.set(None);
-                            login_status.set("Logged out".to_string());
+                            spawn(async move {
+                                match logout().await {
+                                    Ok(()) => login_status.set("Logged out".to_string()),
+                                    Err(e) => login_status.set(format!("Logout error: {e}")),
+                                }
+                                current_user.set(None);
+                            });
                         },
                         "Logout"
                     }
                 }
+            } else if *awaiting_code.read() {
+                form {
+                    onsubmit: move |_| {
+                        let code_val = code.read().trim().to_string();
+                        let on_success = props.on_login_success;
+
+                        if code_val.is_empty() {
+                            login_status.set("Please enter a code".to_string());
+                            return;
+                        }
+
+                        is_loading.set(true);
+                        login_status.set("".to_string());
+
+                        spawn(async move {
+                            match verify_two_factor_login(code_val).await {
+                                Ok(response) => {
+                                    is_loading.set(false);
+                                    code.set("".to_string());
+                                    if response.success {
+                                        awaiting_code.set(false);
+                                        login_status.set("Login successful!".to_string());
+                                        if let Some(user) = response.user.clone() {
+                                            current_user.set(Some(user.clone()));
+
+                                            if let Some(callback) = on_success {
+                                                callback.call(user);
+                                            }
+                                        }
+                                        info!("User logged in successfully with two-factor code");
+                                    } else {
+                                        // Back to the password step when the challenge is gone
+                                        awaiting_code.set(response.two_factor_required);
+                                        error!("Two-factor login failed: {}", response.message);
+                                        login_status.set(response.message);
+                                    }
+                                }
+                                Err(e) => {
+                                    is_loading.set(false);
+                                    login_status.set(format!("Login error: {e}"));
+                                }
+                            }
+                        });
+                    },
+
+                    div {
+                        style: "margin-bottom: 15px;",
+                        label {
+                            style: "display: block; margin-bottom: 5px;",
+                            "Authentication code:"
+                        }
+                        input {
+                            r#type: "text",
+                            autocomplete: "one-time-code",
+                            placeholder: "123456 or recovery code",
+                            value: "{code}",
+                            style: "width: 100%; padding: 8px; border: 1px solid #ddd; border-radius: 4px;",
+                            oninput: move |event| {
+                                code.set(event.value().clone());
+                            }
+                        }
+                    }
+
+                    button {
+                        r#type: "submit",
+                        disabled: *is_loading.read(),
+                        style: "width: 100%; padding: 10px; background: #007bff; color: white; border: none; border-radius: 4px; cursor: pointer;",
+                        if *is_loading.read() { "Verifying..." } else { "Verify" }
+                    }
+                    button {
+                        r#type: "button",
+                        style: "width: 100%; margin-top: 10px; padding: 10px; background: none; border: 1px solid #ddd; border-radius: 4px; cursor: pointer;",
+                        onclick: move |_| {
+                            awaiting_code.set(false);
+                            code.set("".to_string());
+                            login_status.set("".to_string());
+                        },
+                        "Cancel"
+                    }
+                }
             } else {
                 form {
                     onsubmit: move |_| {
@@ -67,12 +154,7 @@ pub fn LoginForm(props: LoginFormProps) -> Element {
                         login_status.set("".to_string());
 
                         spawn(async move {
-                            match login_with_session(
-                                username_val,
-                                password_val,
-                                Some("127.0.0.1".to_string()), // In a real app, get from request
-                                Some("Dioxus App".to_string())  // In a real app, get from request headers
-                            ).await {
+                            match login_with_session(username_val, password_val).await {
                                 Ok(response) => {
                                     is_loading.set(false);
                                     if response.success {
@@ -80,19 +162,16 @@ pub fn LoginForm(props: LoginFormProps) -> Element {
                                         if let Some(user) = response.user.clone() {
                                             current_user.set(Some(user.clone()));
 
-                                            // Store session ID for persistence
-                                            if let Some(session_id) = &response.session_id {
-                                                info!("Session ID received: {}", session_id);
-                                                // TODO: Store session ID in browser storage
-                                                // store_session_id(session_id.clone()).await;
-                                            }
-
                                             // Notify parent component if callback provided
                                             if let Some(callback) = on_success {
                                                 callback.call(user);
                                             }
                                         }
                                         info!("User logged in successfully");
+                                    } else if response.two_factor_required {
+                                        password.set("".to_string());
+                                        awaiting_code.set(true);
+                                        login_status.set(response.message);
                                     } else {
                                         let message = response.message.clone();
                                         login_status.set(response.message);
@@ -1,5 +1,9 @@
 #[cfg(feature = "server")]
-use crate::database::create_connection;
+use crate::{
+    auth::{require_scope, ApiScope},
+    database::create_connection,
+    models::Role,
+};
 #[cfg(feature = "server")]
 use chrono::{DateTime, Utc};
 use dioxus::logger::tracing::{error, info};
@@ -70,12 +74,13 @@ pub struct WebFlags {
     pub updated_date: chrono::DateTime<chrono::Utc>,
 }
 
+/// Returns whether maintenance mode is on
 #[server]
-async fn get_mode() -> Result<bool, ServerFnError> {
+pub async fn get_mode() -> Result<bool, ServerFnError> {
     match create_connection().await {
         Ok(mut conn) => {
             let result = sqlx::query_as::<_, WebFlags>(
-                "select maintenance_mode
+                "select maintenance_mode, updated_Date as updated_date
                 from web_flags
                 order by updated_Date desc
                 limit 1",
@@ -109,8 +114,16 @@ async fn get_mode() -> Result<bool, ServerFnError> {
     }
 }
 
+/// Turns maintenance mode on or off. The caller must hold an active admin session.
 #[server]
-async fn save_mode(enabled: bool) -> Result<(), ServerFnError> {
+pub async fn save_mode(enabled: bool) -> Result<(), ServerFnError> {
+    require_scope(Role::Admin, ApiScope::FlagsWrite).await?;
+    store_mode(enabled).await
+}
+
+/// Saves whether maintenance mode is on for [`save_mode`]
+#[cfg(feature = "server")]
+pub async fn store_mode(enabled: bool) -> Result<(), ServerFnError> {
     match create_connection().await {
         Ok(mut conn) => {
             let updated_date = Utc::now();
@@ -129,7 +142,7 @@ async fn save_mode(enabled: bool) -> Result<(), ServerFnError> {
                 }
                 Err(e) => {
                     return Err(ServerFnError::new(format!(
-                        "Error occurred during blog insert: {e}"
+                        "Error saving maintenance mode: {e}"
                     )));
                 }
             }
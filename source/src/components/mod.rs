@@ -3,6 +3,7 @@ pub use bio::Bio;
 
 mod projects_list;
 pub use projects_list::ProjectTable;
+pub use projects_list::fetch_github_repos;
 
 mod maintenance_banner;
 pub use maintenance_banner::MaintenanceBanner;
@@ -15,15 +16,70 @@ mod new_edit_blog;
 pub use new_edit_blog::NewEditBlog;
 pub use new_edit_blog::BlogPostFormData;
 
+mod tags;
+pub use tags::TagChips;
+pub use tags::TagCloud;
+
+mod post_summary_table;
+pub use post_summary_table::PostSummaryTable;
+
+mod archive_months;
+pub use archive_months::ArchiveMonths;
+pub use archive_months::month_name;
+
+mod revision_history;
+pub use revision_history::RevisionHistory;
+
+mod comments;
+pub use comments::CommentSection;
+
+mod comment_moderation;
+pub use comment_moderation::CommentModerationQueue;
+
+mod failed_logins;
+pub use failed_logins::FailedLoginAttempts;
+
+mod scheduled_jobs;
+pub use scheduled_jobs::ScheduledJobs;
+
+mod two_factor;
+pub use two_factor::SecuritySettingsPanel;
+pub use two_factor::TwoFactorSettings;
+
 mod maintenance;
 pub use maintenance::MaintenanceSettings;
+pub use maintenance::get_mode;
+pub use maintenance::save_mode;
+#[cfg(feature = "server")]
+pub use maintenance::store_mode;
 
 mod admin;
 pub use admin::AdminView;
 pub use admin::AdminLogin;
+pub use admin::AdminPostList;
+
+mod admin_guard;
+pub use admin_guard::AdminGuard;
 
 mod account;
 pub use account::AddAccount;
 
+mod accounts;
+pub use accounts::AccountManagement;
+
+mod api_tokens;
+pub use api_tokens::ApiTokens;
+
+mod active_sessions;
+pub use active_sessions::ActiveSessions;
+
+mod password;
+pub use password::ChangePassword;
+pub use password::PasswordResetIssuer;
+pub use password::ResetPasswordForm;
+
+mod setup;
+pub use setup::FirstRunSetup;
+
 mod login;
 pub use login::LoginForm;
@@ -1,27 +1,92 @@
-use crate::models::{save_post, BlogPost};
+use crate::{
+    components::TagChips,
+    models::{
+        delete_post, format_tag_list, parse_tag_list, preview_post, save_post, slugify, update_post,
+        BlogPost, ContentFormat, PostStatus,
+    },
+    route::Route,
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use dioxus::{
-    logger::tracing::{error, info},
+    logger::tracing::{error, info, warn},
     prelude::*,
 };
 use serde::{Deserialize, Serialize};
 
+/// Format used by `datetime-local` inputs
+const DATETIME_LOCAL_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct BlogPostFormData {
     pub title: String,
+    /// Leave empty to generate the slug from the title
+    pub slug: String,
     pub content: String,
+    pub content_format: ContentFormat,
+    pub status: PostStatus,
+    pub publish_at: Option<DateTime<Utc>>,
+    /// Comma separated tag names, e.g. "rust, web dev"
+    pub tags: String,
 }
 
 //TODO: Can find an example of form validation in dioxus here:
 //https://github.com/DioxusLabs/dioxus/blob/main/examples/form.rs
+/// Form for writing a new blog post, or editing an existing one when `post_id` is set
+///
+/// When editing, the post is loaded through `preview_post` to pre-fill the form and
+/// is saved through `update_post`. Existing posts can also be deleted from here after
+/// confirming. Must be rendered inside an `AdminGuard`.
 #[component]
-pub fn NewEditBlog() -> Element {
+pub fn NewEditBlog(post_id: Option<i32>) -> Element {
     let mut post_title = use_signal(|| "".to_string());
+    let mut post_slug = use_signal(|| "".to_string());
     let mut post_content = use_signal(|| "".to_string());
+    let mut content_format = use_signal(ContentFormat::default);
+    let mut status = use_signal(PostStatus::default);
+    let mut publish_at = use_signal(|| None::<DateTime<Utc>>);
+    let mut post_tags = use_signal(|| "".to_string());
+    let mut status_message = use_signal(|| "".to_string());
+    let mut confirm_delete = use_signal(|| false);
+
+    // Pre-fill the form when editing an existing post
+    use_effect(move || {
+        if let Some(id) = post_id {
+            spawn(async move {
+                match preview_post(id).await {
+                    Ok(Some(post)) => {
+                        post_title.set(post.title);
+                        post_slug.set(post.slug);
+                        post_content.set(post.content);
+                        content_format.set(post.content_format);
+                        status.set(post.status);
+                        publish_at.set(post.publish_at);
+                        post_tags.set(format_tag_list(&post.tags));
+                    }
+                    Ok(None) => {
+                        warn!("No post found to edit with id: {id}");
+                        status_message.set(format!("No post found with id {id}"));
+                    }
+                    Err(e) => {
+                        error!("Failed to load post {id} for editing: {e}");
+                        status_message.set(format!("Failed to load post: {e}"));
+                    }
+                }
+            });
+        }
+    });
 
     rsx! {
         div {
             class: "new-edit-blog",
-            h1 { "New/Edit Blog Post" }
+            h1 {
+                if post_id.is_some() { "Edit Blog Post" } else { "New Blog Post" }
+            }
+            if !status_message.read().is_empty() {
+                p {
+                    class: "status-message",
+                    "{status_message}"
+                }
+            }
             form {
                 id: "newEditBlogForm",
                 style: "display:flex; flex-direction:column;",
@@ -31,19 +96,46 @@ pub fn NewEditBlog() -> Element {
                     spawn(async move {
                         let form_data = BlogPostFormData {
                             title: post_title.read().to_string(),
-                            content: post_content.read().to_string()
+                            slug: post_slug.read().to_string(),
+                            content: post_content.read().to_string(),
+                            content_format: *content_format.read(),
+                            status: *status.read(),
+                            publish_at: *publish_at.read(),
+                            tags: post_tags.read().to_string(),
                         };
 
-                        let blog_post = BlogPost::from_form_data(form_data);
-                        match save_post(blog_post).await {
-                            Ok(saved_post) => {
-                                info!("saved new post: {:?}", saved_post);
-                                //TODO: Should redirect to the view blog post page on save or have
-                                //a pop up that will take the user there if clicked
+                        let mut blog_post = BlogPost::from_form_data(form_data);
+                        match post_id {
+                            Some(id) => {
+                                blog_post.id = Some(id);
+                                match update_post(blog_post).await {
+                                    Ok(Some(updated_post)) => {
+                                        info!("updated post: {:?}", updated_post);
+                                        navigator().push(Route::PreviewPost { id });
+                                    }
+                                    Ok(None) => {
+                                        warn!("Post {id} no longer exists");
+                                        status_message.set(format!("No post found with id {id}"));
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to update post: {}", e);
+                                        status_message.set(format!("Failed to update post: {e}"));
+                                    }
+                                }
                             }
-                            Err(e) => {
-                                error!("Failed to save post: {}", e);
-                                //TODO: Should display an error message/prompt here
+                            None => {
+                                match save_post(blog_post).await {
+                                    Ok(saved_post) => {
+                                        info!("saved new post: {:?}", saved_post);
+                                        if let Some(id) = saved_post.and_then(|post| post.id) {
+                                            navigator().push(Route::PreviewPost { id });
+                                        }
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to save post: {}", e);
+                                        status_message.set(format!("Failed to save post: {e}"));
+                                    }
+                                }
                             }
                         }
                     });
@@ -56,10 +148,92 @@ pub fn NewEditBlog() -> Element {
                     placeholder: "Title",
                     name: "title",
                     required: true,
+                    value: "{post_title}",
                     oninput: move |input_event| {
                         post_title.set(input_event.value().clone());
                     }
                 },
+                label {
+                    "Slug (leave empty to generate from the title):"
+                },
+                input {
+                    r#type: "text",
+                    placeholder: slugify(&post_title.read()),
+                    name: "slug",
+                    value: "{post_slug}",
+                    oninput: move |input_event| {
+                        post_slug.set(input_event.value().clone());
+                    }
+                },
+                label {
+                    "Tags (comma separated):"
+                },
+                input {
+                    r#type: "text",
+                    placeholder: "rust, web dev",
+                    name: "tags",
+                    value: "{post_tags}",
+                    oninput: move |input_event| {
+                        post_tags.set(input_event.value().clone());
+                    }
+                },
+                TagChips { tags: parse_tag_list(&post_tags.read()) },
+                label {
+                    "Content Format:"
+                },
+                select {
+                    name: "content_format",
+                    onchange: move |evt| {
+                        let format = match evt.value().as_str() {
+                            "html" => ContentFormat::Html,
+                            _ => ContentFormat::Markdown,
+                        };
+                        content_format.set(format);
+                    },
+                    option {
+                        value: "markdown",
+                        selected: *content_format.read() == ContentFormat::Markdown,
+                        "Markdown"
+                    },
+                    option {
+                        value: "html",
+                        selected: *content_format.read() == ContentFormat::Html,
+                        "HTML"
+                    },
+                },
+                label {
+                    "Status:"
+                },
+                select {
+                    name: "status",
+                    onchange: move |evt| {
+                        status.set(PostStatus::from_str_or_draft(&evt.value()));
+                    },
+                    for option_status in [PostStatus::Draft, PostStatus::Scheduled, PostStatus::Published, PostStatus::Archived] {
+                        option {
+                            value: option_status.as_str(),
+                            selected: *status.read() == option_status,
+                            "{option_status:?}"
+                        }
+                    }
+                },
+                if *status.read() == PostStatus::Scheduled {
+                    label {
+                        "Publish At (UTC):"
+                    },
+                    input {
+                        r#type: "datetime-local",
+                        name: "publish_at",
+                        required: true,
+                        value: (*publish_at.read()).map(|date| date.format(DATETIME_LOCAL_FORMAT).to_string()).unwrap_or_default(),
+                        oninput: move |input_event| {
+                            let parsed = NaiveDateTime::parse_from_str(&input_event.value(), DATETIME_LOCAL_FORMAT)
+                                .ok()
+                                .map(|date| date.and_utc());
+                            publish_at.set(parsed);
+                        }
+                    },
+                }
                 label {
                     "Post Content:"
                 },
@@ -67,6 +241,7 @@ pub fn NewEditBlog() -> Element {
                     placeholder: "Content",
                     name: "content",
                     required: true,
+                    value: "{post_content}",
                     oninput: move |input_event| {
                         post_content.set(input_event.value().clone());
                     }
@@ -76,6 +251,59 @@ pub fn NewEditBlog() -> Element {
                     "Save"
                 }
             }
+            if let Some(id) = post_id {
+                Link {
+                    to: Route::PreviewPost { id },
+                    "Preview"
+                }
+                Link {
+                    to: Route::PostRevisions { id },
+                    style: "margin-left: 15px;",
+                    "Revisions"
+                }
+            }
+            if let Some(id) = post_id {
+                div {
+                    class: "delete-post",
+                    style: "margin-top: 10px;",
+                    if *confirm_delete.read() {
+                        p { "Are you sure you want to delete this post? This cannot be undone." }
+                        button {
+                            style: "background: #dc3545; color: white; border: none; padding: 5px 10px; border-radius: 3px; cursor: pointer;",
+                            onclick: move |_| {
+                                spawn(async move {
+                                    match delete_post(id).await {
+                                        Ok(true) => {
+                                            info!("deleted post with id: {id}");
+                                            navigator().push(Route::Admin {});
+                                        }
+                                        Ok(false) => {
+                                            warn!("Post {id} was already deleted");
+                                            status_message.set(format!("No post found with id {id}"));
+                                        }
+                                        Err(e) => {
+                                            error!("Failed to delete post: {}", e);
+                                            status_message.set(format!("Failed to delete post: {e}"));
+                                        }
+                                    }
+                                    confirm_delete.set(false);
+                                });
+                            },
+                            "Yes, delete"
+                        }
+                        button {
+                            onclick: move |_| confirm_delete.set(false),
+                            "Cancel"
+                        }
+                    } else {
+                        button {
+                            style: "background: #dc3545; color: white; border: none; padding: 5px 10px; border-radius: 3px; cursor: pointer;",
+                            onclick: move |_| confirm_delete.set(true),
+                            "Delete Post"
+                        }
+                    }
+                }
+            }
         }
     }
 }
@@ -0,0 +1,215 @@
+use crate::{
+    auth::{change_password, create_password_reset, password_problem, reset_password},
+    route::Route,
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const INPUT_STYLE: &str = "display: block; width: 100%; max-width: 300px; padding: 8px; margin-bottom: 10px; border: 1px solid #ddd; border-radius: 4px;";
+
+/// Form for the logged in account to change its password. Other sessions of the account
+/// are logged out once it has changed.
+#[component]
+pub fn ChangePassword() -> Element {
+    let mut current_password = use_signal(|| "".to_string());
+    let mut new_password = use_signal(|| "".to_string());
+    let mut confirm_password = use_signal(|| "".to_string());
+    let mut status_message = use_signal(|| "".to_string());
+
+    rsx! {
+        div {
+            class: "change-password",
+            h1 { "Change Password" }
+            form {
+                onsubmit: move |_| {
+                    let current = current_password.read().clone();
+                    let new = new_password.read().clone();
+                    if new != *confirm_password.read() {
+                        status_message.set("The new passwords don't match".to_string());
+                        return;
+                    }
+                    if let Some(problem) = password_problem(&new) {
+                        status_message.set(problem);
+                        return;
+                    }
+                    spawn(async move {
+                        match change_password(current, new).await {
+                            Ok(()) => {
+                                info!("Password changed");
+                                current_password.set("".to_string());
+                                new_password.set("".to_string());
+                                confirm_password.set("".to_string());
+                                status_message.set("Password changed, your other sessions have been logged out.".to_string());
+                            }
+                            Err(e) => {
+                                error!("Error changing password: {e}");
+                                status_message.set(format!("{e}"));
+                            }
+                        }
+                    });
+                },
+                input {
+                    r#type: "password",
+                    autocomplete: "current-password",
+                    placeholder: "Current password",
+                    value: "{current_password}",
+                    style: INPUT_STYLE,
+                    oninput: move |event| current_password.set(event.value()),
+                }
+                input {
+                    r#type: "password",
+                    autocomplete: "new-password",
+                    placeholder: "New password",
+                    value: "{new_password}",
+                    style: INPUT_STYLE,
+                    oninput: move |event| new_password.set(event.value()),
+                }
+                input {
+                    r#type: "password",
+                    autocomplete: "new-password",
+                    placeholder: "Repeat new password",
+                    value: "{confirm_password}",
+                    style: INPUT_STYLE,
+                    oninput: move |event| confirm_password.set(event.value()),
+                }
+                button { r#type: "submit", "Change password" }
+            }
+            if !status_message.read().is_empty() {
+                p { "{status_message}" }
+            }
+        }
+    }
+}
+
+/// Lets an admin issue a password reset link for an account, either shown here to share
+/// or emailed to an address. Must be rendered inside an `AdminGuard`.
+///
+/// # Arguments
+/// * `account_id` - The account the link resets the password of
+/// * `username` - Its username, for display
+/// * `on_close` - Called when the panel should be closed
+#[component]
+pub fn PasswordResetIssuer(account_id: i32, username: String, on_close: EventHandler<()>) -> Element {
+    let mut send_to = use_signal(|| "".to_string());
+    let mut status_message = use_signal(|| "".to_string());
+    let mut reset_url = use_signal(|| None::<String>);
+
+    rsx! {
+        div {
+            class: "password-reset-issuer",
+            style: "background: #f0f0f0; padding: 10px; border-radius: 5px; margin-bottom: 10px;",
+            h3 { "Reset the password of {username}" }
+            p { "Leave the email address empty to get a link to share yourself." }
+            form {
+                onsubmit: move |_| {
+                    let address = send_to.read().trim().to_string();
+                    let address = (!address.is_empty()).then_some(address);
+                    spawn(async move {
+                        match create_password_reset(account_id, address).await {
+                            Ok(issued) => {
+                                let expires_at = issued.expires_at.format("%d/%m/%Y %H:%M");
+                                match &issued.emailed_to {
+                                    Some(address) => status_message.set(format!("Reset link emailed to {address}, it expires {expires_at} UTC.")),
+                                    None => status_message.set(format!("Share this link, it expires {expires_at} UTC:")),
+                                }
+                                reset_url.set(issued.reset_url);
+                            }
+                            Err(e) => {
+                                error!("Error issuing password reset for account {account_id}: {e}");
+                                reset_url.set(None);
+                                status_message.set(format!("{e}"));
+                            }
+                        }
+                    });
+                },
+                input {
+                    r#type: "email",
+                    placeholder: "Email address (optional)",
+                    value: "{send_to}",
+                    style: "margin-right: 10px;",
+                    oninput: move |event| send_to.set(event.value()),
+                }
+                button { r#type: "submit", style: "margin-right: 5px;", "Issue reset link" }
+                button { r#type: "button", onclick: move |_| on_close.call(()), "Close" }
+            }
+            if !status_message.read().is_empty() {
+                p { "{status_message}" }
+            }
+            if let Some(url) = reset_url.read().as_ref() {
+                input {
+                    r#type: "text",
+                    readonly: true,
+                    value: "{url}",
+                    style: "width: 100%; font-family: monospace;",
+                }
+            }
+        }
+    }
+}
+
+/// Form for choosing a new password with the token from a password reset link
+///
+/// # Arguments
+/// * `token` - The token from the link
+#[component]
+pub fn ResetPasswordForm(token: String) -> Element {
+    let mut new_password = use_signal(|| "".to_string());
+    let mut confirm_password = use_signal(|| "".to_string());
+    let mut status_message = use_signal(|| "".to_string());
+    let mut is_done = use_signal(|| false);
+
+    if is_done() {
+        return rsx! {
+            p { "Your password has been changed." }
+            Link { to: Route::Admin {}, "Log in" }
+        };
+    }
+
+    rsx! {
+        form {
+            onsubmit: move |_| {
+                let token = token.clone();
+                let new = new_password.read().clone();
+                if new != *confirm_password.read() {
+                    status_message.set("The passwords don't match".to_string());
+                    return;
+                }
+                if let Some(problem) = password_problem(&new) {
+                    status_message.set(problem);
+                    return;
+                }
+                spawn(async move {
+                    match reset_password(token, new).await {
+                        Ok(()) => is_done.set(true),
+                        Err(e) => {
+                            error!("Error resetting password: {e}");
+                            status_message.set(format!("{e}"));
+                        }
+                    }
+                });
+            },
+            input {
+                r#type: "password",
+                autocomplete: "new-password",
+                placeholder: "New password",
+                value: "{new_password}",
+                style: INPUT_STYLE,
+                oninput: move |event| new_password.set(event.value()),
+            }
+            input {
+                r#type: "password",
+                autocomplete: "new-password",
+                placeholder: "Repeat new password",
+                value: "{confirm_password}",
+                style: INPUT_STYLE,
+                oninput: move |event| confirm_password.set(event.value()),
+            }
+            button { r#type: "submit", "Set new password" }
+        }
+        if !status_message.read().is_empty() {
+            p { "{status_message}" }
+        }
+    }
+}
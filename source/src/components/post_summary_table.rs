@@ -0,0 +1,204 @@
+use crate::{
+    models::{get_post_summaries, PostListQuery, PostSortColumn, SortDirection},
+    route::Route,
+};
+use dioxus::{
+    logger::tracing::error,
+    prelude::*,
+};
+
+const HEADER_STYLE: &str = "
+    padding: 12px;
+    text-align: left;
+    border-bottom: 2px solid #444;
+    font-weight: bold;
+";
+
+/// Paginated table of visible blog posts whose Title, Published and Reading Time
+/// columns can be sorted by clicking their headers
+///
+/// # Arguments
+/// * `archive_month` - Only list posts published in this `(year, month)`
+#[component]
+pub fn PostSummaryTable(archive_month: Option<(i32, u32)>) -> Element {
+    let mut sort_column = use_signal(PostSortColumn::default);
+    let mut sort_direction = use_signal(SortDirection::default);
+    let mut page = use_signal(|| 1_u32);
+
+    let post_page = use_resource(use_reactive!(|archive_month| async move {
+        let query = PostListQuery {
+            page: page(),
+            sort_column: sort_column(),
+            sort_direction: sort_direction(),
+            archive_month,
+        };
+        get_post_summaries(query).await
+    }));
+
+    // Clicking the current sort column flips its direction, any other column starts
+    // with the direction that makes the most sense for it
+    let mut sort_by = move |column: PostSortColumn| {
+        if sort_column() == column {
+            sort_direction.set(sort_direction().reversed());
+        } else {
+            sort_column.set(column);
+            sort_direction.set(match column {
+                PostSortColumn::PublishedAt => SortDirection::Descending,
+                PostSortColumn::Title | PostSortColumn::ReadingTime => SortDirection::Ascending,
+            });
+        }
+        page.set(1);
+    };
+    let sort_indicator = move |column: PostSortColumn| {
+        match (sort_column() == column, sort_direction()) {
+            (false, _) => "",
+            (true, SortDirection::Ascending) => " ▲",
+            (true, SortDirection::Descending) => " ▼",
+        }
+    };
+
+    let table = match &*post_page.read() {
+        Some(Ok(post_page)) if post_page.posts.is_empty() => {
+            rsx! {
+                div {
+                    style: "text-align: center; padding: 40px; color: #888; background: #1a1a1a; border-radius: 8px;",
+                    p { "No blog posts available yet." }
+                }
+            }
+        }
+        Some(Ok(post_page)) => {
+            let page_count = post_page.page_count();
+            let current_page = post_page.page;
+            rsx! {
+                table {
+                    style: "
+                        width: 100%;
+                        border-collapse: collapse;
+                        margin-top: 20px;
+                        background-color: #1a1a1a;
+                        color: #ffffff;
+                        border-radius: 8px;
+                        overflow: hidden;
+                    ",
+                    thead {
+                        style: "background-color: #2a2a2a;",
+                        tr {
+                            th {
+                                style: "{HEADER_STYLE} width: 80px;",
+                                "ID"
+                            }
+                            th {
+                                style: "{HEADER_STYLE} cursor: pointer;",
+                                onclick: move |_| sort_by(PostSortColumn::Title),
+                                "Title{sort_indicator(PostSortColumn::Title)}"
+                            }
+                            th {
+                                style: "{HEADER_STYLE} cursor: pointer; width: 120px;",
+                                onclick: move |_| sort_by(PostSortColumn::PublishedAt),
+                                "Published{sort_indicator(PostSortColumn::PublishedAt)}"
+                            }
+                            th {
+                                style: "{HEADER_STYLE} cursor: pointer; width: 130px;",
+                                onclick: move |_| sort_by(PostSortColumn::ReadingTime),
+                                "Reading Time{sort_indicator(PostSortColumn::ReadingTime)}"
+                            }
+                        }
+                    }
+                    tbody {
+                        style: "background-color: #1a1a1a;",
+                        for post in post_page.posts.iter() {
+                            tr {
+                                key: "{post.id}",
+                                class: "blog-row",
+                                style: "
+                                    border-bottom: 1px solid #333;
+                                    transition: background-color 0.2s ease;
+                                ",
+                                td {
+                                    style: "
+                                        padding: 12px;
+                                        border-bottom: 1px solid #333;
+                                        color: #888;
+                                        font-family: monospace;
+                                    ",
+                                    "{post.id}"
+                                }
+                                td {
+                                    style: "padding: 12px; border-bottom: 1px solid #333;",
+                                    Link {
+                                        to: Route::Blog { slug: post.slug.clone() },
+                                        style: "
+                                            color: #4a9eff;
+                                            text-decoration: none;
+                                            font-weight: 500;
+                                        ",
+                                        "{post.title}"
+                                    }
+                                    p {
+                                        style: "margin: 4px 0 0 0; color: #aaa; font-size: 0.9em;",
+                                        "{post.excerpt}"
+                                    }
+                                }
+                                td {
+                                    style: "padding: 12px; border-bottom: 1px solid #333; color: #888;",
+                                    if let Some(published_at) = post.published_at {
+                                        "{published_at.format(\"%d/%m/%Y\")}"
+                                    }
+                                }
+                                td {
+                                    style: "padding: 12px; border-bottom: 1px solid #333; color: #888;",
+                                    "{post.reading_time_minutes} min"
+                                }
+                            }
+                        }
+                    }
+                }
+                if page_count > 1 {
+                    div {
+                        class: "blog-pagination",
+                        style: "display: flex; gap: 20px; align-items: center; margin-top: 20px;",
+                        if current_page > 1 {
+                            button {
+                                onclick: move |_| page.set(current_page - 1),
+                                "Previous"
+                            }
+                        }
+                        span {
+                            style: "color: #888;",
+                            "Page {current_page} of {page_count}"
+                        }
+                        if current_page < page_count {
+                            button {
+                                onclick: move |_| page.set(current_page + 1),
+                                "Next"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Some(Err(error)) => {
+            error!("Error occurred extracting blog list: {:?}", error);
+            rsx! {
+                div {
+                    style: "
+                        color: #ff6b6b;
+                        padding: 20px;
+                        background: #2a1a1a;
+                        border-radius: 8px;
+                        border-left: 4px solid #ff6b6b;
+                        margin-top: 20px;
+                    ",
+                    p { "Error loading blog posts. Please try again later." }
+                }
+            }
+        }
+        None => rsx! {
+            div {
+                style: "text-align: center; padding: 40px; color: #888;",
+                p { "Loading blog posts..." }
+            }
+        },
+    };
+    table
+}
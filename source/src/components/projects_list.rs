@@ -134,6 +134,8 @@ pub fn ProjectTable() -> Element {
     }
 }
 
+/// Lists the repositories of the GitHub account `GITHUB_TOKEN` belongs to, most recently
+/// pushed first
 #[server]
 pub async fn fetch_github_repos() -> Result<Vec<Repository>, ServerFnError> {
     //TODO: Should really move this client to be initialized in the app or higher up but fine to
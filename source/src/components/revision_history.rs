@@ -0,0 +1,263 @@
+use crate::{
+    models::{diff_revisions, get_post_revisions, restore_revision, DiffKind, DiffLine, PostRevision},
+    route::Route,
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+/// Lists the saved revisions of a post, shows the diff between any two of them, and lets
+/// an older revision be restored as the current version. Must be rendered inside an
+/// `AdminGuard`.
+#[component]
+pub fn RevisionHistory(post_id: i32) -> Element {
+    // The revisions to compare, defaulting to the two newest
+    let mut compare_from = use_signal(|| None::<i32>);
+    let mut compare_to = use_signal(|| None::<i32>);
+    let mut confirm_restore = use_signal(|| None::<i32>);
+    let mut status_message = use_signal(|| "".to_string());
+
+    let mut revisions = use_resource(move || async move {
+        let revisions = get_post_revisions(post_id).await;
+        if let Ok(revisions) = &revisions {
+            compare_to.set(revisions.first().map(|revision| revision.id));
+            compare_from.set(revisions.get(1).map(|revision| revision.id));
+        }
+        revisions
+    });
+
+    let diff = use_resource(move || async move {
+        match (compare_from(), compare_to()) {
+            (Some(from), Some(to)) if from != to => diff_revisions(from, to).await,
+            _ => Ok(None),
+        }
+    });
+
+    let restore = use_callback(move |revision_id: i32| {
+        spawn(async move {
+            match restore_revision(revision_id).await {
+                Ok(Some(post)) => {
+                    info!("Restored post {post_id} to revision {revision_id}");
+                    status_message.set(format!("Restored \"{}\" to revision #{revision_id}", post.title));
+                    revisions.restart();
+                }
+                Ok(None) => {
+                    status_message.set(format!("No revision found with id {revision_id}"));
+                }
+                Err(e) => {
+                    error!("Failed to restore revision {revision_id}: {e}");
+                    status_message.set(format!("Failed to restore revision: {e}"));
+                }
+            }
+            confirm_restore.set(None);
+        });
+    });
+
+    let revision_list = match &*revisions.read() {
+        Some(Ok(revision_list)) if revision_list.is_empty() => {
+            rsx! {
+                p { "This post has no saved revisions." }
+            }
+        }
+        Some(Ok(revision_list)) => {
+            let newest_id = revision_list.first().map(|revision| revision.id);
+            rsx! {
+                table {
+                    style: "width: 100%; border-collapse: collapse; margin-bottom: 20px;",
+                    thead {
+                        tr {
+                            th { style: "text-align: left;", "From" }
+                            th { style: "text-align: left;", "To" }
+                            th { style: "text-align: left;", "Revision" }
+                            th { style: "text-align: left;", "Saved" }
+                            th { style: "text-align: left;", "Author" }
+                            th { style: "text-align: left;", "Title" }
+                            th {}
+                        }
+                    }
+                    tbody {
+                        for revision in revision_list.iter().cloned() {
+                            RevisionRow {
+                                key: "{revision.id}",
+                                is_from: compare_from() == Some(revision.id),
+                                is_to: compare_to() == Some(revision.id),
+                                is_current: newest_id == Some(revision.id),
+                                confirming_restore: confirm_restore() == Some(revision.id),
+                                on_select_from: move |id| compare_from.set(Some(id)),
+                                on_select_to: move |id| compare_to.set(Some(id)),
+                                on_restore: move |id| restore.call(id),
+                                on_confirm_restore: move |id| confirm_restore.set(id),
+                                revision,
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Some(Err(e)) => {
+            error!("Failed to load revisions for post {post_id}: {e}");
+            rsx! {
+                p { "Failed to load revisions: {e}" }
+            }
+        }
+        None => rsx! {
+            p { "Loading revisions..." }
+        },
+    };
+
+    let diff_view = match &*diff.read() {
+        Some(Ok(Some(diff))) => {
+            rsx! {
+                h3 { "Changes from revision #{diff.from.id} to #{diff.to.id}" }
+                if diff.title_diff.iter().any(|line| line.kind != DiffKind::Equal) {
+                    h4 { "Title" }
+                    DiffView { lines: diff.title_diff.clone() }
+                }
+                h4 { "Content" }
+                if diff.content_diff.is_empty() {
+                    p { "The content is identical." }
+                } else {
+                    DiffView { lines: diff.content_diff.clone() }
+                }
+            }
+        }
+        Some(Ok(None)) => {
+            rsx! {
+                p { "Select two different revisions to compare." }
+            }
+        }
+        Some(Err(e)) => {
+            error!("Failed to diff revisions: {e}");
+            rsx! {
+                p { "Failed to compare revisions: {e}" }
+            }
+        }
+        None => rsx! {
+            p { "Comparing revisions..." }
+        },
+    };
+
+    rsx! {
+        div {
+            class: "revision-history",
+            h1 { "Revision History" }
+            Link {
+                to: Route::EditPost { id: post_id },
+                "Back to editor"
+            }
+            if !status_message.read().is_empty() {
+                p {
+                    class: "status-message",
+                    "{status_message}"
+                }
+            }
+            {revision_list}
+            {diff_view}
+        }
+    }
+}
+
+/// A single revision in the history table
+#[component]
+fn RevisionRow(
+    revision: PostRevision,
+    is_from: bool,
+    is_to: bool,
+    is_current: bool,
+    confirming_restore: bool,
+    on_select_from: EventHandler<i32>,
+    on_select_to: EventHandler<i32>,
+    on_restore: EventHandler<i32>,
+    on_confirm_restore: EventHandler<Option<i32>>,
+) -> Element {
+    let id = revision.id;
+    let author = revision.author.unwrap_or_else(|| "unknown".to_string());
+    let saved_at = revision
+        .created_at
+        .map(|created_at| created_at.format("%d/%m/%Y %H:%M").to_string())
+        .unwrap_or_default();
+    rsx! {
+        tr {
+            td {
+                input {
+                    r#type: "radio",
+                    name: "compare-from",
+                    checked: is_from,
+                    onchange: move |_| on_select_from.call(id),
+                }
+            }
+            td {
+                input {
+                    r#type: "radio",
+                    name: "compare-to",
+                    checked: is_to,
+                    onchange: move |_| on_select_to.call(id),
+                }
+            }
+            td { "#{id}" }
+            td { "{saved_at}" }
+            td { "{author}" }
+            td { "{revision.title}" }
+            td {
+                if is_current {
+                    "current"
+                } else if confirming_restore {
+                    button {
+                        style: "background: #dc3545; color: white; border: none; padding: 5px 10px; border-radius: 3px; cursor: pointer;",
+                        onclick: move |_| on_restore.call(id),
+                        "Yes, restore"
+                    }
+                    button {
+                        onclick: move |_| on_confirm_restore.call(None),
+                        "Cancel"
+                    }
+                } else {
+                    button {
+                        onclick: move |_| on_confirm_restore.call(Some(id)),
+                        "Restore"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Renders diff lines with insertions in green, deletions in red and the changed
+/// words within a line highlighted
+#[component]
+fn DiffView(lines: Vec<DiffLine>) -> Element {
+    rsx! {
+        div {
+            class: "revision-diff",
+            style: "font-family: monospace; white-space: pre-wrap; background: #1a1a1a; color: #ddd; padding: 10px; border-radius: 5px;",
+            for line in lines {
+                match line.kind {
+                    DiffKind::Separator => rsx! {
+                        div { style: "color: #888;", "⋯" }
+                    },
+                    kind => {
+                        let (prefix, background, highlight) = match kind {
+                            DiffKind::Insert => ("+", "#1e3a1e", "#2f6b2f"),
+                            DiffKind::Delete => ("-", "#3a1e1e", "#6b2f2f"),
+                            _ => (" ", "transparent", "transparent"),
+                        };
+                        rsx! {
+                            div {
+                                style: "background: {background};",
+                                "{prefix} "
+                                for segment in line.segments {
+                                    if segment.emphasized {
+                                        span { style: "background: {highlight};", "{segment.text}" }
+                                    } else {
+                                        span { "{segment.text}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::models::{get_job_statuses, run_job_now, JobOutcome, JobStatus};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+/// Lists the background jobs with their schedule and how their last run went, with a
+/// button to run any of them straight away. Must be rendered inside an `AdminGuard`.
+#[component]
+pub fn ScheduledJobs() -> Element {
+    let mut status_message = use_signal(|| "".to_string());
+    let mut jobs = use_resource(|| async move { get_job_statuses().await });
+
+    let run_now = use_callback(move |name: String| {
+        spawn(async move {
+            status_message.set(format!("Running {name}..."));
+            match run_job_now(name.clone()).await {
+                Ok(message) => {
+                    info!("Ran the {name} job: {message}");
+                    status_message.set(format!("{name}: {message}"));
+                }
+                Err(e) => {
+                    error!("Failed to run the {name} job: {e}");
+                    status_message.set(format!("{name} failed: {e}"));
+                }
+            }
+            jobs.restart();
+        });
+    });
+
+    let job_list = match &*jobs.read() {
+        Some(Ok(job_list)) if job_list.is_empty() => rsx! {
+            p { "No scheduled jobs." }
+        },
+        Some(Ok(job_list)) => rsx! {
+            table {
+                style: "width: 100%; border-collapse: collapse;",
+                thead {
+                    tr {
+                        th { style: "text-align: left;", "Job" }
+                        th { style: "text-align: left;", "Schedule" }
+                        th { style: "text-align: left;", "Last run" }
+                        th { style: "text-align: left;", "Outcome" }
+                        th { style: "text-align: left;", "Next run" }
+                        th { style: "text-align: left;", "Runs" }
+                        th {}
+                    }
+                }
+                tbody {
+                    for job in job_list.iter().cloned() {
+                        JobRow {
+                            key: "{job.name}",
+                            job,
+                            on_run: move |name| run_now.call(name),
+                        }
+                    }
+                }
+            }
+        },
+        Some(Err(e)) => {
+            error!("Error loading scheduled jobs: {e}");
+            rsx! {
+                p { "Error loading scheduled jobs." }
+            }
+        }
+        None => rsx! {
+            p { "Loading scheduled jobs..." }
+        },
+    };
+
+    rsx! {
+        div {
+            class: "scheduled-jobs",
+            h1 { "Scheduled Jobs" }
+            if !status_message.read().is_empty() {
+                p {
+                    class: "status-message",
+                    "{status_message}"
+                }
+            }
+            button {
+                style: "margin-bottom: 10px;",
+                onclick: move |_| jobs.restart(),
+                "Refresh"
+            }
+            {job_list}
+        }
+    }
+}
+
+/// A single background job
+#[component]
+fn JobRow(job: JobStatus, on_run: EventHandler<String>) -> Element {
+    let format_time = |time: Option<chrono::DateTime<chrono::Utc>>| {
+        time.map(|time| time.format("%d/%m/%Y %H:%M:%S").to_string())
+            .unwrap_or_else(|| "never".to_string())
+    };
+    let last_run = format_time(job.last_started_at);
+    let next_run = if job.is_running {
+        "running now".to_string()
+    } else {
+        format_time(job.next_run_at)
+    };
+    let outcome = match job.last_outcome {
+        Some(JobOutcome::Success) => "succeeded",
+        Some(JobOutcome::Failure) => "failed",
+        None => "",
+    };
+    let outcome_color = if job.last_outcome == Some(JobOutcome::Failure) { "red" } else { "inherit" };
+    let last_message = job.last_message.clone().unwrap_or_default();
+    let runs = if job.failure_count > 0 {
+        format!("{} ({} failed)", job.run_count, job.failure_count)
+    } else {
+        job.run_count.to_string()
+    };
+    let name = job.name.clone();
+
+    rsx! {
+        tr {
+            td { title: "{job.description}", "{job.name}" }
+            td { "{job.schedule}" }
+            td { "{last_run}" }
+            td {
+                style: "color: {outcome_color};",
+                title: "{last_message}",
+                "{outcome}"
+            }
+            td { "{next_run}" }
+            td { "{runs}" }
+            td {
+                button {
+                    disabled: job.is_running,
+                    onclick: move |_| on_run.call(name.clone()),
+                    "Run now"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    auth::{complete_setup, is_setup_required, password_problem},
+    route::Route,
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const INPUT_STYLE: &str = "display: block; width: 100%; max-width: 300px; padding: 8px; margin-bottom: 10px; border: 1px solid #ddd; border-radius: 4px;";
+
+/// Form for creating the first admin account with the setup token logged when the server
+/// started. Only shown while the site has no accounts.
+///
+/// # Arguments
+/// * `token` - The token from the setup link
+#[component]
+pub fn FirstRunSetup(token: String) -> Element {
+    let mut username = use_signal(|| "".to_string());
+    let mut password = use_signal(|| "".to_string());
+    let mut confirm_password = use_signal(|| "".to_string());
+    let mut status_message = use_signal(|| "".to_string());
+    let mut is_done = use_signal(|| false);
+    let setup_required = use_resource(|| async move { is_setup_required().await });
+
+    if is_done() {
+        return rsx! {
+            p { "The admin account has been created." }
+            Link { to: Route::Admin {}, "Log in" }
+        };
+    }
+
+    match &*setup_required.read() {
+        Some(Ok(true)) => {}
+        Some(Ok(false)) => {
+            return rsx! {
+                p { "This site has already been set up." }
+                Link { to: Route::Admin {}, "Log in" }
+            };
+        }
+        Some(Err(e)) => {
+            error!("Error checking whether setup is required: {e}");
+            return rsx! {
+                p { "Error loading the setup page." }
+            };
+        }
+        None => {
+            return rsx! {
+                p { "Loading..." }
+            };
+        }
+    }
+
+    rsx! {
+        p { "Choose the username and password of the site's first admin account." }
+        form {
+            onsubmit: move |_| {
+                let token = token.clone();
+                let new_username = username.read().trim().to_string();
+                let new_password = password.read().clone();
+                if new_username.is_empty() {
+                    status_message.set("Choose a username".to_string());
+                    return;
+                }
+                if new_password != *confirm_password.read() {
+                    status_message.set("The passwords don't match".to_string());
+                    return;
+                }
+                if let Some(problem) = password_problem(&new_password) {
+                    status_message.set(problem);
+                    return;
+                }
+                spawn(async move {
+                    match complete_setup(token, new_username.clone(), new_password).await {
+                        Ok(()) => {
+                            info!("First admin account {new_username} created");
+                            is_done.set(true);
+                        }
+                        Err(e) => {
+                            error!("Error creating the first admin account: {e}");
+                            status_message.set(format!("{e}"));
+                        }
+                    }
+                });
+            },
+            input {
+                r#type: "text",
+                autocomplete: "username",
+                placeholder: "Username",
+                value: "{username}",
+                style: INPUT_STYLE,
+                oninput: move |event| username.set(event.value()),
+            }
+            input {
+                r#type: "password",
+                autocomplete: "new-password",
+                placeholder: "Password",
+                value: "{password}",
+                style: INPUT_STYLE,
+                oninput: move |event| password.set(event.value()),
+            }
+            input {
+                r#type: "password",
+                autocomplete: "new-password",
+                placeholder: "Repeat password",
+                value: "{confirm_password}",
+                style: INPUT_STYLE,
+                oninput: move |event| confirm_password.set(event.value()),
+            }
+            button { r#type: "submit", "Create admin account" }
+        }
+        if !status_message.read().is_empty() {
+            p { "{status_message}" }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{
+    models::{get_tag_cloud, Tag},
+    route::Route,
+};
+use dioxus::{logger::tracing::error, prelude::*};
+
+const CHIP_STYLE: &str = "
+    display: inline-block;
+    padding: 2px 10px;
+    margin: 0 6px 6px 0;
+    border-radius: 12px;
+    background-color: #2a2a2a;
+    color: #4a9eff;
+    text-decoration: none;
+    font-size: 0.85em;
+";
+
+/// Renders a post's tags as chips linking to each tag's page
+#[component]
+pub fn TagChips(tags: Vec<Tag>) -> Element {
+    rsx! {
+        div {
+            class: "tag-chips",
+            for tag in tags {
+                Link {
+                    key: "{tag.slug}",
+                    to: Route::TagPosts { tag: tag.slug.clone() },
+                    style: CHIP_STYLE,
+                    "#{tag.name}"
+                }
+            }
+        }
+    }
+}
+
+/// Renders every tag with visible posts, sized by how many posts use it
+#[component]
+pub fn TagCloud() -> Element {
+    let tag_cloud = use_resource(move || async move { get_tag_cloud().await });
+
+    let cloud = match &*tag_cloud.read() {
+        Some(Ok(tags)) if !tags.is_empty() => {
+            let max_count = tags.iter().map(|tag| tag.post_count).max().unwrap_or(1);
+            rsx! {
+                div {
+                    class: "tag-cloud",
+                    style: "margin-bottom: 20px;",
+                    for tag in tags.iter() {
+                        Link {
+                            key: "{tag.slug}",
+                            to: Route::TagPosts { tag: tag.slug.clone() },
+                            // Scale from 0.85em for the least used tag up to 1.6em for the most used
+                            style: "{CHIP_STYLE} font-size: {0.85 + 0.75 * tag.post_count as f64 / max_count as f64:.2}em;",
+                            "{tag.name} ({tag.post_count})"
+                        }
+                    }
+                }
+            }
+        }
+        Some(Ok(_)) => rsx! {},
+        Some(Err(e)) => {
+            error!("Error loading tag cloud: {e}");
+            rsx! {}
+        }
+        None => rsx! {
+            p {
+                style: "color: #888;",
+                "Loading tags..."
+            }
+        },
+    };
+    cloud
+}
@@ -0,0 +1,231 @@
+use crate::auth::{
+    begin_two_factor_setup, confirm_two_factor_setup, disable_two_factor, get_security_settings,
+    get_two_factor_status, regenerate_recovery_codes, save_security_settings, SecuritySettings,
+    TwoFactorSetup,
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+/// Lets the logged in account set up, turn off and manage two-factor authentication
+///
+/// Setting up shows a QR code for an authenticator app, asks for the first code from it
+/// and then shows the recovery codes once. `on_done` is called after setup finishes, so
+/// an `AdminGuard` waiting on required setup can check the session again.
+#[component]
+pub fn TwoFactorSettings(#[props(optional)] on_done: Option<Callback<()>>) -> Element {
+    let mut status = use_resource(|| async move { get_two_factor_status().await });
+    let mut setup = use_signal(|| None::<TwoFactorSetup>);
+    let mut recovery_codes = use_signal(Vec::<String>::new);
+    let mut code = use_signal(|| "".to_string());
+    let mut message = use_signal(|| "".to_string());
+
+    let start_setup = move |_| {
+        spawn(async move {
+            match begin_two_factor_setup().await {
+                Ok(new_setup) => {
+                    message.set("".to_string());
+                    setup.set(Some(new_setup));
+                }
+                Err(e) => {
+                    error!("Error starting two-factor setup: {e}");
+                    message.set(format!("Error starting setup: {e}"));
+                }
+            }
+        });
+    };
+
+    let confirm_setup = move |_| {
+        let code_val = code.read().trim().to_string();
+        spawn(async move {
+            match confirm_two_factor_setup(code_val).await {
+                Ok(codes) => {
+                    info!("Two-factor authentication enabled");
+                    code.set("".to_string());
+                    setup.set(None);
+                    recovery_codes.set(codes);
+                    message.set("Two-factor authentication is now enabled.".to_string());
+                    status.restart();
+                }
+                Err(e) => message.set(format!("{e}")),
+            }
+        });
+    };
+
+    let disable = move |_| {
+        let code_val = code.read().trim().to_string();
+        spawn(async move {
+            match disable_two_factor(code_val).await {
+                Ok(()) => {
+                    info!("Two-factor authentication disabled");
+                    code.set("".to_string());
+                    recovery_codes.set(Vec::new());
+                    message.set("Two-factor authentication is now off.".to_string());
+                    status.restart();
+                }
+                Err(e) => message.set(format!("{e}")),
+            }
+        });
+    };
+
+    let regenerate = move |_| {
+        let code_val = code.read().trim().to_string();
+        spawn(async move {
+            match regenerate_recovery_codes(code_val).await {
+                Ok(codes) => {
+                    code.set("".to_string());
+                    recovery_codes.set(codes);
+                    message.set("New recovery codes generated, the old ones no longer work.".to_string());
+                    status.restart();
+                }
+                Err(e) => message.set(format!("{e}")),
+            }
+        });
+    };
+
+    let code_input = rsx! {
+        input {
+            r#type: "text",
+            autocomplete: "one-time-code",
+            placeholder: "Authentication code",
+            value: "{code}",
+            style: "margin-right: 10px;",
+            oninput: move |event| code.set(event.value()),
+        }
+    };
+
+    let body = match &*status.read() {
+        Some(Ok(current)) if current.enabled => {
+            let remaining = format!("{} unused recovery codes left.", current.recovery_codes_remaining);
+            rsx! {
+                p { "Two-factor authentication is enabled." }
+                p { "{remaining}" }
+                if current.required {
+                    p { style: "color: #888;", "Two-factor authentication is required for admins, so it can't be turned off." }
+                }
+                div {
+                    {code_input}
+                    button { style: "margin-right: 10px;", onclick: regenerate, "New recovery codes" }
+                    if !current.required {
+                        button { onclick: disable, "Turn off" }
+                    }
+                }
+            }
+        }
+        Some(Ok(current)) => {
+            let setup_panel = match setup.read().as_ref() {
+                Some(pending) => {
+                    let secret = format!("Or enter this key: {}", pending.secret);
+                    rsx! {
+                        p { "Scan this QR code with your authenticator app, then enter the code it shows." }
+                        div { dangerous_inner_html: "{pending.qr_svg}" }
+                        p { style: "font-family: monospace;", "{secret}" }
+                        div {
+                            {code_input}
+                            button { onclick: confirm_setup, "Confirm" }
+                        }
+                    }
+                }
+                None => rsx! {
+                    button { onclick: start_setup, "Set up two-factor authentication" }
+                },
+            };
+            rsx! {
+                if current.required {
+                    p { style: "color: red;", "Two-factor authentication is required for admins. Set it up to continue." }
+                } else {
+                    p { "Two-factor authentication is off." }
+                }
+                {setup_panel}
+            }
+        }
+        Some(Err(e)) => {
+            error!("Error loading two-factor status: {e}");
+            rsx! {
+                p { "Error loading two-factor status." }
+            }
+        }
+        None => rsx! {
+            p { "Loading two-factor status..." }
+        },
+    };
+
+    rsx! {
+        div {
+            class: "two-factor-settings",
+            h1 { "Two-Factor Authentication" }
+            if !message.read().is_empty() {
+                p { "{message}" }
+            }
+            {body}
+            if !recovery_codes.read().is_empty() {
+                div {
+                    style: "background: #f0f0f0; padding: 10px; border-radius: 5px; margin-top: 10px;",
+                    p { "Save these recovery codes somewhere safe. Each one can be used once to log in without your authenticator app, and they won't be shown again." }
+                    ul {
+                        style: "font-family: monospace;",
+                        for recovery_code in recovery_codes.read().iter() {
+                            li { key: "{recovery_code}", "{recovery_code}" }
+                        }
+                    }
+                    if let Some(on_done) = on_done {
+                        button { onclick: move |_| on_done.call(()), "Continue" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Site wide security settings, currently whether admins must use two-factor
+/// authentication. Must be rendered inside an `AdminGuard`.
+#[component]
+pub fn SecuritySettingsPanel() -> Element {
+    let mut require_admin_two_factor = use_signal(|| false);
+    let mut message = use_signal(|| "".to_string());
+
+    use_effect(move || {
+        spawn(async move {
+            match get_security_settings().await {
+                Ok(settings) => require_admin_two_factor.set(settings.require_admin_two_factor),
+                Err(e) => error!("Error loading security settings: {e}"),
+            }
+        });
+    });
+
+    rsx! {
+        div {
+            class: "security-settings",
+            h1 { "Security" }
+            form {
+                onsubmit: move |_| {
+                    let settings = SecuritySettings {
+                        require_admin_two_factor: *require_admin_two_factor.read(),
+                    };
+                    spawn(async move {
+                        match save_security_settings(settings).await {
+                            Ok(()) => message.set("Security settings saved.".to_string()),
+                            Err(e) => {
+                                error!("Error saving security settings: {e}");
+                                message.set(format!("{e}"));
+                            }
+                        }
+                    });
+                },
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: *require_admin_two_factor.read(),
+                        oninput: move |event| require_admin_two_factor.set(event.checked()),
+                    }
+                    " Require two-factor authentication for admins"
+                }
+                button { r#type: "submit", style: "margin-left: 10px;", "Save" }
+            }
+            if !message.read().is_empty() {
+                p { "{message}" }
+            }
+        }
+    }
+}
@@ -24,7 +24,7 @@ pub type DbPool = SqlitePool;
 ///
 /// # Examples
 ///
-/// ```rust
+/// ```ignore
 /// let pool = create_pool().await?;
 /// ```
 #[cfg(feature = "server")]
@@ -50,7 +50,7 @@ pub async fn create_pool() -> Result<SqlitePool, sqlx::Error> {
 ///
 /// # Examples
 ///
-/// ```rust
+/// ```ignore
 /// let pool = create_pool().await?;
 /// run_migrations(&pool).await?;
 /// ```
@@ -0,0 +1,432 @@
+/// RSS 2.0, Atom and JSON Feed syndication of the blog
+///
+/// Each feed lists the most recently published posts with their full rendered content,
+/// and is also available per tag under `/tags/:tag/`. Responses carry `ETag` and
+/// `Last-Modified` headers so feed readers polling with `If-None-Match` or
+/// `If-Modified-Since` get a `304 Not Modified` when nothing has changed.
+///
+/// Links in the feeds are absolute. They are built from the `SITE_URL` environment
+/// variable (e.g. `https://nicojudge.com`), falling back to the request's `Host` header.
+use crate::{
+    database::create_connection,
+    models::{find_tag, load_recent_posts, BlogPost, PostStatus, Tag},
+};
+use axum::{
+    extract::Path,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use chrono::{DateTime, Utc};
+use dioxus::logger::tracing::{error, info};
+use std::{
+    env,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+/// Name of the site used as the feed title
+const SITE_TITLE: &str = "nicojudge.com";
+
+/// Maximum number of posts included in a feed
+const FEED_POST_LIMIT: u32 = 20;
+
+/// Format of `Last-Modified` and `If-Modified-Since` headers
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// The syndication formats the blog is published in
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    /// Returns the `Content-Type` feeds of this format are served with
+    fn content_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+}
+
+/// Everything needed to render a feed in any format
+struct Feed {
+    title: String,
+    /// Absolute URL of the page the feed mirrors
+    home_url: String,
+    /// Absolute URL of the feed itself
+    feed_url: String,
+    /// Site URL without a trailing slash, used to build post and tag links
+    base_url: String,
+    posts: Vec<BlogPost>,
+}
+
+impl Feed {
+    fn post_url(&self, post: &BlogPost) -> String {
+        format!("{}/blog/{}", self.base_url, post.slug)
+    }
+
+    /// Returns when the most recently changed post in the feed last changed for readers
+    fn last_modified(&self) -> Option<DateTime<Utc>> {
+        self.posts.iter().filter_map(post_modified).max()
+    }
+}
+
+/// Returns when a post last changed for readers, the later of when it was last saved and
+/// when it became visible. A scheduled post going live doesn't touch its row, so it can
+/// appear long after its `updated_at`.
+fn post_modified(post: &BlogPost) -> Option<DateTime<Utc>> {
+    let went_live = match post.status {
+        PostStatus::Scheduled => post.publish_at,
+        _ => None,
+    };
+    [post.updated_at, post.published_at, went_live].into_iter().flatten().max()
+}
+
+/// Returns the router serving every feed, to be merged into the app's router
+pub fn feed_routes() -> Router {
+    Router::new()
+        .route("/feed.xml", get(|headers: HeaderMap| serve_feed(FeedFormat::Rss, None, headers)))
+        .route("/atom.xml", get(|headers: HeaderMap| serve_feed(FeedFormat::Atom, None, headers)))
+        .route("/feed.json", get(|headers: HeaderMap| serve_feed(FeedFormat::Json, None, headers)))
+        .route(
+            "/tags/:tag/feed.xml",
+            get(|Path(tag): Path<String>, headers: HeaderMap| serve_feed(FeedFormat::Rss, Some(tag), headers)),
+        )
+        .route(
+            "/tags/:tag/atom.xml",
+            get(|Path(tag): Path<String>, headers: HeaderMap| serve_feed(FeedFormat::Atom, Some(tag), headers)),
+        )
+        .route(
+            "/tags/:tag/feed.json",
+            get(|Path(tag): Path<String>, headers: HeaderMap| serve_feed(FeedFormat::Json, Some(tag), headers)),
+        )
+}
+
+/// Builds the feed in the requested format, answering conditional requests with
+/// `304 Not Modified` when the reader already has the current version
+async fn serve_feed(format: FeedFormat, tag_slug: Option<String>, headers: HeaderMap) -> Response {
+    let feed = match load_feed(format, tag_slug.as_deref(), &headers).await {
+        Ok(Some(feed)) => feed,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("Error building {format:?} feed: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let body = match format {
+        FeedFormat::Rss => render_rss(&feed),
+        FeedFormat::Atom => render_atom(&feed),
+        FeedFormat::Json => render_json_feed(&feed),
+    };
+
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    let etag = format!("\"{:016x}\"", hasher.finish());
+    let last_modified = feed.last_modified();
+
+    let mut response_headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, value);
+    }
+    if let Some(last_modified) = last_modified {
+        if let Ok(value) = HeaderValue::from_str(&last_modified.format(HTTP_DATE_FORMAT).to_string()) {
+            response_headers.insert(header::LAST_MODIFIED, value);
+        }
+    }
+
+    if is_not_modified(&headers, &etag, last_modified) {
+        return (StatusCode::NOT_MODIFIED, response_headers).into_response();
+    }
+
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(format.content_type()));
+    (StatusCode::OK, response_headers, body).into_response()
+}
+
+/// Loads the posts for a feed, or `None` if the requested tag doesn't exist
+async fn load_feed(
+    format: FeedFormat,
+    tag_slug: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<Option<Feed>, String> {
+    let mut conn = create_connection()
+        .await
+        .map_err(|e| format!("Database connection error: {e}"))?;
+
+    let tag: Option<Tag> = match tag_slug {
+        Some(tag_slug) => match find_tag(&mut conn, tag_slug).await.map_err(|e| e.to_string())? {
+            Some(tag) => Some(tag),
+            None => {
+                info!("No feed for unknown tag: {tag_slug}");
+                return Ok(None);
+            }
+        },
+        None => None,
+    };
+
+    let posts = load_recent_posts(&mut conn, tag.as_ref().and_then(|tag| tag.id), FEED_POST_LIMIT)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let base_url = site_url(headers);
+    let file_name = match format {
+        FeedFormat::Rss => "feed.xml",
+        FeedFormat::Atom => "atom.xml",
+        FeedFormat::Json => "feed.json",
+    };
+    let feed = match tag {
+        Some(tag) => Feed {
+            title: format!("{SITE_TITLE} - posts tagged {}", tag.name),
+            home_url: format!("{base_url}/tags/{}", tag.slug),
+            feed_url: format!("{base_url}/tags/{}/{file_name}", tag.slug),
+            base_url,
+            posts,
+        },
+        None => Feed {
+            title: SITE_TITLE.to_string(),
+            home_url: format!("{base_url}/blogContents"),
+            feed_url: format!("{base_url}/{file_name}"),
+            base_url,
+            posts,
+        },
+    };
+    Ok(Some(feed))
+}
+
+/// Returns the absolute URL of the site without a trailing slash, from `SITE_URL` or
+/// else the request's `Host` header
+pub fn site_url(headers: &HeaderMap) -> String {
+    if let Ok(site_url) = env::var("SITE_URL") {
+        return site_url.trim_end_matches('/').to_string();
+    }
+
+    let host = headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .unwrap_or("localhost");
+    format!("http://{host}")
+}
+
+/// Returns true if the reader's cached copy, identified by the conditional request
+/// headers, is still current. `If-None-Match` takes precedence over `If-Modified-Since`.
+fn is_not_modified(headers: &HeaderMap, etag: &str, last_modified: Option<DateTime<Utc>>) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH).and_then(|value| value.to_str().ok()) {
+        return if_none_match
+            .split(',')
+            .map(|candidate| candidate.trim().trim_start_matches("W/"))
+            .any(|candidate| candidate == etag || candidate == "*");
+    }
+
+    let if_modified_since = headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok());
+    match (if_modified_since, last_modified) {
+        // HTTP dates only have second precision
+        (Some(since), Some(last_modified)) => last_modified.timestamp() <= since.timestamp(),
+        _ => false,
+    }
+}
+
+/// Escapes text for use in XML element content and attribute values
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Renders the feed as RSS 2.0
+fn render_rss(feed: &Feed) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n<channel>\n");
+    xml.push_str(&format!("<title>{}</title>\n", escape_xml(&feed.title)));
+    xml.push_str(&format!("<link>{}</link>\n", escape_xml(&feed.home_url)));
+    xml.push_str(&format!("<description>{}</description>\n", escape_xml(&feed.title)));
+    xml.push_str(&format!(
+        "<atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>\n",
+        escape_xml(&feed.feed_url)
+    ));
+    if let Some(last_modified) = feed.last_modified() {
+        xml.push_str(&format!("<lastBuildDate>{}</lastBuildDate>\n", last_modified.to_rfc2822()));
+    }
+
+    for post in &feed.posts {
+        let url = escape_xml(&feed.post_url(post));
+        xml.push_str("<item>\n");
+        xml.push_str(&format!("<title>{}</title>\n", escape_xml(&post.title)));
+        xml.push_str(&format!("<link>{url}</link>\n"));
+        xml.push_str(&format!("<guid isPermaLink=\"true\">{url}</guid>\n"));
+        if let Some(published_at) = post.published_at {
+            xml.push_str(&format!("<pubDate>{}</pubDate>\n", published_at.to_rfc2822()));
+        }
+        for tag in &post.tags {
+            xml.push_str(&format!("<category>{}</category>\n", escape_xml(&tag.name)));
+        }
+        xml.push_str(&format!("<description>{}</description>\n", escape_xml(&post.rendered_html)));
+        xml.push_str("</item>\n");
+    }
+
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+/// Renders the feed as Atom 1.0
+fn render_atom(feed: &Feed) -> String {
+    // Atom requires an updated date even for a feed with no entries
+    let updated = feed.last_modified().unwrap_or(DateTime::UNIX_EPOCH);
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("<title>{}</title>\n", escape_xml(&feed.title)));
+    xml.push_str(&format!("<id>{}</id>\n", escape_xml(&feed.feed_url)));
+    xml.push_str(&format!("<link rel=\"alternate\" href=\"{}\"/>\n", escape_xml(&feed.home_url)));
+    xml.push_str(&format!("<link rel=\"self\" href=\"{}\"/>\n", escape_xml(&feed.feed_url)));
+    xml.push_str(&format!("<updated>{}</updated>\n", updated.to_rfc3339()));
+    xml.push_str(&format!("<author><name>{}</name></author>\n", escape_xml(SITE_TITLE)));
+
+    for post in &feed.posts {
+        let url = escape_xml(&feed.post_url(post));
+        let entry_updated = post_modified(post).unwrap_or(updated);
+        xml.push_str("<entry>\n");
+        xml.push_str(&format!("<title>{}</title>\n", escape_xml(&post.title)));
+        xml.push_str(&format!("<id>{url}</id>\n"));
+        xml.push_str(&format!("<link rel=\"alternate\" href=\"{url}\"/>\n"));
+        if let Some(published_at) = post.published_at {
+            xml.push_str(&format!("<published>{}</published>\n", published_at.to_rfc3339()));
+        }
+        xml.push_str(&format!("<updated>{}</updated>\n", entry_updated.to_rfc3339()));
+        for tag in &post.tags {
+            xml.push_str(&format!(
+                "<category term=\"{}\" label=\"{}\"/>\n",
+                escape_xml(&tag.slug),
+                escape_xml(&tag.name)
+            ));
+        }
+        xml.push_str(&format!("<content type=\"html\">{}</content>\n", escape_xml(&post.rendered_html)));
+        xml.push_str("</entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// Renders the feed as JSON Feed 1.1
+fn render_json_feed(feed: &Feed) -> String {
+    let items: Vec<serde_json::Value> = feed
+        .posts
+        .iter()
+        .map(|post| {
+            let url = feed.post_url(post);
+            serde_json::json!({
+                "id": url,
+                "url": url,
+                "title": post.title,
+                "content_html": post.rendered_html,
+                "date_published": post.published_at.map(|date| date.to_rfc3339()),
+                "date_modified": post_modified(post).map(|date| date.to_rfc3339()),
+                "tags": post.tags.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": feed.title,
+        "home_page_url": feed.home_url,
+        "feed_url": feed.feed_url,
+        "items": items,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn feed(posts: Vec<BlogPost>) -> Feed {
+        Feed {
+            title: SITE_TITLE.to_string(),
+            home_url: "https://example.com/blogContents".to_string(),
+            feed_url: "https://example.com/feed.xml".to_string(),
+            base_url: "https://example.com".to_string(),
+            posts,
+        }
+    }
+
+    fn if_modified_since(date: DateTime<Utc>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let value = date.format(HTTP_DATE_FORMAT).to_string();
+        headers.insert(header::IF_MODIFIED_SINCE, HeaderValue::from_str(&value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn scheduled_post_going_live_changes_last_modified() {
+        let edited = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let polled = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let goes_live = Utc.with_ymd_and_hms(2024, 3, 2, 8, 0, 0).unwrap();
+        let older_post = BlogPost {
+            status: PostStatus::Published,
+            updated_at: Some(edited),
+            published_at: Some(edited),
+            ..BlogPost::default()
+        };
+        let scheduled_post = BlogPost {
+            status: PostStatus::Scheduled,
+            publish_at: Some(goes_live),
+            updated_at: Some(edited),
+            published_at: Some(goes_live),
+            ..BlogPost::default()
+        };
+
+        // Before publish_at the scheduled post isn't loaded into the feed
+        let before = feed(vec![older_post.clone()]);
+        assert_eq!(before.last_modified(), Some(edited));
+        assert!(is_not_modified(&if_modified_since(polled), "\"etag\"", before.last_modified()));
+
+        // Once it is, a reader that polled in between must get the new feed
+        let after = feed(vec![scheduled_post.clone(), older_post]);
+        assert_eq!(after.last_modified(), Some(goes_live));
+        assert!(!is_not_modified(&if_modified_since(polled), "\"etag\"", after.last_modified()));
+        assert_eq!(post_modified(&scheduled_post), Some(goes_live));
+    }
+
+    #[test]
+    fn edits_after_publication_change_last_modified() {
+        let published = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let edited = Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap();
+        let post = BlogPost {
+            status: PostStatus::Published,
+            updated_at: Some(edited),
+            published_at: Some(published),
+            ..BlogPost::default()
+        };
+        assert_eq!(post_modified(&post), Some(edited));
+    }
+
+    #[test]
+    fn if_none_match_takes_precedence() {
+        let mut headers = if_modified_since(Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap());
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"stale\""));
+        let last_modified = Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+        assert!(!is_not_modified(&headers, "\"current\"", last_modified));
+        assert!(is_not_modified(&headers, "\"stale\"", last_modified));
+    }
+}
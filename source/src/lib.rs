@@ -14,4 +14,39 @@ pub mod auth;
 #[cfg(feature = "server")]
 pub mod database;
 
+/// Markdown rendering and HTML sanitization for blog post content.
+/// Only included for server builds.
+#[cfg(feature = "server")]
+pub mod markdown;
+
+/// Permanent redirects for legacy and renamed blog post URLs.
+/// Only included for server builds.
+#[cfg(feature = "server")]
+pub mod redirects;
+
+/// Client IP address lookup for server functions.
+/// Only included for server builds.
+#[cfg(feature = "server")]
+pub mod client_info;
+
+/// RSS, Atom and JSON Feed endpoints for the blog.
+/// Only included for server builds.
+#[cfg(feature = "server")]
+pub mod feeds;
+
+/// Versioned JSON REST API served under `/api/v1`.
+/// Only included for server builds.
+#[cfg(feature = "server")]
+pub mod api;
+
+/// Outgoing email through a configurable file or SMTP backend.
+/// Only included for server builds.
+#[cfg(feature = "server")]
+pub mod mailer;
+
+/// Background jobs run on intervals or cron schedules.
+/// Only included for server builds.
+#[cfg(feature = "server")]
+pub mod scheduler;
+
 pub mod route;
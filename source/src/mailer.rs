@@ -0,0 +1,240 @@
+/// Outgoing email
+///
+/// Mail is sent through a [`Mailer`], picked at runtime from the `MAILER` environment
+/// variable by [`configured_mailer`]:
+///
+/// * `file` writes each message as an `.eml` file into `MAIL_DIR` (default `mail`)
+/// * `smtp` hands each message to the SMTP server at `SMTP_HOST`:`SMTP_PORT` (default
+///   `127.0.0.1:1025`) without TLS or authentication, meant for a local sink or relay
+///
+/// Messages are sent from `MAIL_FROM` (default `noreply@localhost`). When `MAILER` isn't
+/// set no mail is sent at all.
+use chrono::Utc;
+use dioxus::logger::tracing::{error, info};
+use std::{env, fmt, future::Future, path::PathBuf, pin::Pin};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+/// Sender address used when `MAIL_FROM` isn't set
+const DEFAULT_FROM: &str = "noreply@localhost";
+
+/// A plain text email
+#[derive(Debug, Clone, PartialEq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Why an email couldn't be sent
+#[derive(Debug)]
+pub enum MailerError {
+    /// The recipient or subject would break the message headers
+    InvalidMessage(String),
+    Io(std::io::Error),
+    /// The SMTP server answered with an unexpected reply
+    Smtp(String),
+}
+
+impl fmt::Display for MailerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MailerError::InvalidMessage(reason) => write!(f, "Invalid email: {reason}"),
+            MailerError::Io(e) => write!(f, "Error sending email: {e}"),
+            MailerError::Smtp(reply) => write!(f, "SMTP server refused the email: {reply}"),
+        }
+    }
+}
+
+impl std::error::Error for MailerError {}
+
+impl From<std::io::Error> for MailerError {
+    fn from(error: std::io::Error) -> Self {
+        MailerError::Io(error)
+    }
+}
+
+/// Future returned by [`Mailer::send`]
+pub type SendFuture<'a> = Pin<Box<dyn Future<Output = Result<(), MailerError>> + Send + 'a>>;
+
+/// A way of delivering email
+pub trait Mailer: Send + Sync {
+    /// Delivers a single email
+    fn send<'a>(&'a self, email: &'a Email) -> SendFuture<'a>;
+}
+
+/// Returns the mailer configured through the environment, or `None` when sending mail
+/// isn't set up
+///
+/// # Examples
+/// ```ignore
+/// if let Some(mailer) = configured_mailer() {
+///     mailer.send(&email).await?;
+/// }
+/// ```
+pub fn configured_mailer() -> Option<Box<dyn Mailer>> {
+    let from = env::var("MAIL_FROM").unwrap_or_else(|_| DEFAULT_FROM.to_string());
+    match env::var("MAILER").ok()?.as_str() {
+        "file" => Some(Box::new(FileMailer {
+            directory: env::var("MAIL_DIR").unwrap_or_else(|_| "mail".to_string()).into(),
+            from,
+        })),
+        "smtp" => Some(Box::new(SmtpMailer {
+            host: env::var("SMTP_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
+            port: env::var("SMTP_PORT")
+                .ok()
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(1025),
+            from,
+        })),
+        other => {
+            error!("Unknown MAILER {other:?}, expected \"file\" or \"smtp\"");
+            None
+        }
+    }
+}
+
+/// Writes each email into a directory as an `.eml` file, for development and for sites
+/// that pick up outgoing mail some other way
+pub struct FileMailer {
+    pub directory: PathBuf,
+    pub from: String,
+}
+
+impl Mailer for FileMailer {
+    fn send<'a>(&'a self, email: &'a Email) -> SendFuture<'a> {
+        Box::pin(async move {
+            let message = format_message(&self.from, email)?;
+            tokio::fs::create_dir_all(&self.directory).await?;
+            let file_name = format!(
+                "{}-{}.eml",
+                Utc::now().format("%Y%m%dT%H%M%S"),
+                uuid::Uuid::new_v4()
+            );
+            let path = self.directory.join(file_name);
+            tokio::fs::write(&path, message).await?;
+            info!("Wrote email to {} into {}", email.to, path.display());
+            Ok(())
+        })
+    }
+}
+
+/// Hands each email to an SMTP server over a plain connection, without TLS or
+/// authentication. Meant for a local SMTP sink or a relay on the same host.
+pub struct SmtpMailer {
+    pub host: String,
+    pub port: u16,
+    pub from: String,
+}
+
+impl Mailer for SmtpMailer {
+    fn send<'a>(&'a self, email: &'a Email) -> SendFuture<'a> {
+        Box::pin(async move {
+            let message = format_message(&self.from, email)?;
+            let stream = TcpStream::connect((self.host.as_str(), self.port)).await?;
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+
+            expect_reply(&mut reader, 220).await?;
+            smtp_command(&mut writer, &mut reader, "EHLO localhost", 250).await?;
+            smtp_command(&mut writer, &mut reader, &format!("MAIL FROM:<{}>", self.from), 250).await?;
+            smtp_command(&mut writer, &mut reader, &format!("RCPT TO:<{}>", email.to), 250).await?;
+            smtp_command(&mut writer, &mut reader, "DATA", 354).await?;
+
+            // Lines starting with a dot get another one so they can't end the message early
+            let mut data = String::with_capacity(message.len() + 5);
+            for line in message.split("\r\n") {
+                if line.starts_with('.') {
+                    data.push('.');
+                }
+                data.push_str(line);
+                data.push_str("\r\n");
+            }
+            data.push_str(".\r\n");
+            writer.write_all(data.as_bytes()).await?;
+            expect_reply(&mut reader, 250).await?;
+
+            smtp_command(&mut writer, &mut reader, "QUIT", 221).await?;
+            info!("Sent email to {} through {}:{}", email.to, self.host, self.port);
+            Ok(())
+        })
+    }
+}
+
+/// Sends an SMTP command and waits for the expected reply code
+async fn smtp_command<W, R>(
+    writer: &mut W,
+    reader: &mut R,
+    command: &str,
+    expected_code: u16,
+) -> Result<(), MailerError>
+where
+    W: AsyncWriteExt + Unpin,
+    R: AsyncBufReadExt + Unpin,
+{
+    writer.write_all(format!("{command}\r\n").as_bytes()).await?;
+    expect_reply(reader, expected_code).await
+}
+
+/// Reads an SMTP reply, which may span several lines, and checks its code
+async fn expect_reply<R>(reader: &mut R, expected_code: u16) -> Result<(), MailerError>
+where
+    R: AsyncBufReadExt + Unpin,
+{
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Err(MailerError::Smtp("connection closed".to_string()));
+        }
+        let code = line.get(..3).and_then(|code| code.parse::<u16>().ok());
+        if code != Some(expected_code) {
+            return Err(MailerError::Smtp(line.trim_end().to_string()));
+        }
+        // "250-" continues the reply, "250 " ends it
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+/// Checks that an address can be used as a recipient, so it can be validated before
+/// doing anything that depends on the mail going out
+pub fn validate_address(address: &str) -> Result<(), MailerError> {
+    let is_plausible = address
+        .split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && !domain.is_empty());
+    if !is_plausible || address.contains(|c: char| c.is_whitespace() || c == '<' || c == '>') {
+        return Err(MailerError::InvalidMessage(format!(
+            "{address:?} is not an email address"
+        )));
+    }
+    Ok(())
+}
+
+/// Builds the RFC 5322 message for an email, with CRLF line endings
+fn format_message(from: &str, email: &Email) -> Result<String, MailerError> {
+    validate_address(&email.to)?;
+    if [from, &email.subject].iter().any(|value| value.contains(['\r', '\n'])) {
+        return Err(MailerError::InvalidMessage(
+            "headers can't contain line breaks".to_string(),
+        ));
+    }
+
+    let body = email.body.replace("\r\n", "\n").replace('\n', "\r\n");
+    Ok(format!(
+        "From: {from}\r\n\
+        To: {}\r\n\
+        Subject: {}\r\n\
+        Date: {}\r\n\
+        MIME-Version: 1.0\r\n\
+        Content-Type: text/plain; charset=utf-8\r\n\
+        Content-Transfer-Encoding: 8bit\r\n\
+        \r\n\
+        {body}",
+        email.to,
+        email.subject,
+        Utc::now().to_rfc2822()
+    ))
+}
@@ -1,17 +1,22 @@
 use dioxus::{
-    dioxus_core::LaunchConfig,
     logger::{
         self,
-        tracing::{debug, error, field::debug, warn, Level},
+        tracing::{debug, warn, Level},
     },
     prelude::*,
-    web::Config,
 };
 use web::{components::MaintenanceBanner, route::Route};
 
 // Server-only imports for Axum integration
 #[cfg(feature = "server")]
-use dioxus::fullstack::prelude::ServeConfigBuilder;
+use dioxus::fullstack::prelude::{DioxusRouterExt, ServeConfigBuilder};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::error;
+#[cfg(feature = "server")]
+use web::{
+    api::api_routes, auth::{apply_error_status, bootstrap_first_admin, verify_csrf_token}, feeds::feed_routes,
+    models::{backfill_post_slugs, backfill_post_text}, redirects::redirect_legacy_blog_urls, scheduler::start_scheduler,
+};
 
 // We can import assets in dioxus with the `asset!` macro. This macro takes a path to an asset relative to the crate root.
 // The macro returns an `Asset` type that will display as the path to the asset in the browser or a local path in desktop bundles.
@@ -19,6 +24,8 @@ const FAVICON: Asset = asset!("/assets/favicon.ico");
 // The asset macro also minifies some assets like CSS and JS to make bundled smaller
 const MAIN_CSS: Asset = asset!("/assets/styling/main.css");
 const TAILWIND_CSS: Asset = asset!("/assets/tailwind.css");
+// Attaches the CSRF token to server function calls, see `auth::verify_csrf_token`
+const CSRF_JS: Asset = asset!("/assets/scripts/csrf.js");
 // Do something better with this but for now fine with keeping it
 // as a constant
 const MAINTENANCE_MODE: bool = false;
@@ -34,27 +41,52 @@ fn main() {
         }
     }
 
-    // Configure the server to serve static assets
     #[cfg(feature = "server")]
-    let config = ServeConfigBuilder::default().build().unwrap();
-
-    #[cfg(feature = "server")]
-    dioxus::LaunchBuilder::new().with_cfg(config).launch(app);
+    launch_server();
 
     #[cfg(not(feature = "server"))]
     dioxus::LaunchBuilder::new().launch(app);
+}
+
+/// Serves the Dioxus app from our own Axum router so we can mount additional routes
+/// and middleware alongside it
+#[cfg(feature = "server")]
+#[tokio::main]
+async fn launch_server() {
+    // Get the address the server should run on. If the CLI is running, the CLI proxies fullstack into the main address
+    // and we use the generated address the CLI gives us
+    let address = dioxus_cli_config::fullstack_address_or_localhost();
+
+    // Configure the server to serve static assets
+    let config = ServeConfigBuilder::default();
+
+    let router = axum::Router::new()
+        .merge(feed_routes())
+        .merge(api_routes())
+        .serve_dioxus_application(config, app)
+        .layer(axum::middleware::from_fn(redirect_legacy_blog_urls))
+        .layer(axum::middleware::from_fn(apply_error_status))
+        .layer(axum::middleware::from_fn(verify_csrf_token))
+        // Make the client's address available to server functions
+        .into_make_service_with_connect_info::<std::net::SocketAddr>();
+
+    // Give posts from before slugs existed their permalinks
+    if let Err(e) = backfill_post_slugs().await {
+        error!("Could not give every post a slug: {e}");
+    }
+    // Index the text of posts from before search ignored markup
+    if let Err(e) = backfill_post_text().await {
+        error!("Could not index the text of every post: {e}");
+    }
+
+    // Create the first admin, or log a setup link, when there are no accounts yet
+    bootstrap_first_admin().await;
+
+    // Run background jobs such as session cleanup alongside the server
+    start_scheduler();
 
-    LaunchBuilder::new()
-        // Only set the server config if the server feature is enabled
-        .with_cfg(server_only! {
-            ServeConfigBuilder::default()
-                .root_id("app")
-        })
-        // You also need to set the root id in your web config
-        .with_cfg(web! {
-            dioxus::web::Config::default().rootname("app")
-        })
-        .launch(app);
+    let listener = tokio::net::TcpListener::bind(address).await.unwrap();
+    axum::serve(listener, router).await.unwrap();
 }
 
 fn app() -> Element {
@@ -68,6 +100,7 @@ fn app() -> Element {
             document::Link { rel: "icon", href: FAVICON },
             document::Link { rel: "stylesheet", href: MAIN_CSS },
             document::Link { rel: "stylesheet", href: TAILWIND_CSS },
+            document::Script { src: CSRF_JS },
             Router::<Route> {}
         }
     }
@@ -0,0 +1,158 @@
+/// Server-side rendering of blog post content into sanitized HTML
+///
+/// Posts can be authored either in Markdown (CommonMark plus the GFM table,
+/// task list, footnote and strikethrough extensions) or as legacy raw HTML.
+/// Both formats are passed through an allow-list sanitizer before they are
+/// handed to the client, so the view never injects untrusted markup.
+use crate::models::ContentFormat;
+use ammonia::Builder;
+use pulldown_cmark::{html, Options, Parser};
+use std::collections::{HashMap, HashSet};
+
+/// Renders post content in the given format into sanitized HTML
+///
+/// # Arguments
+/// * `content` - The raw content as stored in the database
+/// * `format` - The format the content was authored in
+///
+/// # Examples
+///
+/// ```rust
+/// use web::markdown::render_content;
+/// use web::models::ContentFormat;
+///
+/// let html = render_content("# Hello", &ContentFormat::Markdown);
+/// assert_eq!(html.trim(), "<h1>Hello</h1>");
+/// ```
+pub fn render_content(content: &str, format: &ContentFormat) -> String {
+    match format {
+        ContentFormat::Markdown => sanitize_html(&markdown_to_html(content)),
+        ContentFormat::Html => sanitize_html(content),
+    }
+}
+
+/// Renders post content in the given format into plain text with all markup removed,
+/// used for excerpts and word counts
+///
+/// # Examples
+///
+/// ```rust
+/// use web::markdown::plain_text;
+/// use web::models::ContentFormat;
+///
+/// let text = plain_text("Some **bold** text", &ContentFormat::Markdown);
+/// assert_eq!(text.trim(), "Some bold text");
+/// ```
+pub fn plain_text(content: &str, format: &ContentFormat) -> String {
+    let html = match format {
+        ContentFormat::Markdown => markdown_to_html(content),
+        ContentFormat::Html => content.to_string(),
+    };
+
+    // An empty allow-list strips every tag but keeps the text, dropping the contents of
+    // elements like <script> entirely. The text comes back HTML-escaped, so undo that.
+    Builder::empty()
+        .clean(&html)
+        .to_string()
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Converts Markdown into unsanitized HTML
+///
+/// The output of this function must not be sent to the client without first
+/// being passed through [`sanitize_html`], since raw HTML embedded in the
+/// Markdown source is passed through untouched.
+pub fn markdown_to_html(source: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH;
+
+    let parser = Parser::new_ext(source, options);
+    let mut output = String::with_capacity(source.len() * 3 / 2);
+    html::push_html(&mut output, parser);
+    output
+}
+
+/// Sanitizes an HTML fragment against the allow-list used for blog posts
+///
+/// On top of ammonia's defaults this allows the markup produced by the
+/// Markdown extensions we enable: disabled task list checkboxes and the
+/// footnote reference/definition elements.
+pub fn sanitize_html(html: &str) -> String {
+    let mut allowed_classes: HashMap<&str, HashSet<&str>> = HashMap::new();
+    allowed_classes.insert("sup", HashSet::from(["footnote-reference", "footnote-definition-label"]));
+    allowed_classes.insert("div", HashSet::from(["footnote-definition"]));
+
+    Builder::default()
+        .add_tags(["input"])
+        .add_tag_attribute_values("input", "type", ["checkbox"])
+        .add_tag_attributes("input", ["checked", "disabled"])
+        .add_tag_attributes("div", ["id"])
+        .allowed_classes(allowed_classes)
+        .clean(html)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_scripts_and_event_handlers() {
+        let html = sanitize_html(r#"<p onclick="steal()">Hi</p><script>alert(1)</script>"#);
+        assert_eq!(html, "<p>Hi</p>");
+    }
+
+    #[test]
+    fn strips_javascript_links() {
+        let html = render_content("[click](javascript:alert(1))", &ContentFormat::Markdown);
+        assert!(!html.contains("javascript:"), "{html}");
+        assert!(html.contains("click"), "{html}");
+    }
+
+    #[test]
+    fn strips_raw_html_embedded_in_markdown() {
+        let html = render_content("Text\n\n<iframe src=\"https://example.com\"></iframe>", &ContentFormat::Markdown);
+        assert!(!html.contains("<iframe"), "{html}");
+    }
+
+    #[test]
+    fn keeps_task_list_checkboxes() {
+        let html = render_content("- [x] done\n- [ ] todo", &ContentFormat::Markdown);
+        assert!(html.contains(r#"<input disabled="" type="checkbox" checked="">"#), "{html}");
+        assert!(html.contains(r#"<input disabled="" type="checkbox">"#), "{html}");
+    }
+
+    #[test]
+    fn only_allows_checkbox_inputs() {
+        let html = sanitize_html(r#"<input type="text" value="x" disabled>"#);
+        assert!(!html.contains("text"), "{html}");
+        assert!(!html.contains("value"), "{html}");
+    }
+
+    #[test]
+    fn keeps_footnotes() {
+        let html = render_content("Claim[^1]\n\n[^1]: Source", &ContentFormat::Markdown);
+        assert!(html.contains(r#"<sup class="footnote-reference">"#), "{html}");
+        assert!(html.contains(r#"<div class="footnote-definition" id="1">"#), "{html}");
+        assert!(html.contains(r##"href="#1""##), "{html}");
+    }
+
+    #[test]
+    fn drops_classes_outside_the_allow_list() {
+        let html = sanitize_html(r#"<div class="footnote-definition overlay">x</div>"#);
+        assert_eq!(html, r#"<div class="footnote-definition">x</div>"#);
+    }
+
+    #[test]
+    fn plain_text_unescapes_entities() {
+        let text = plain_text("Tom &amp; Jerry <b>\"quoted\"</b>", &ContentFormat::Html);
+        assert_eq!(text, "Tom & Jerry \"quoted\"");
+    }
+}
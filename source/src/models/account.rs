@@ -1,32 +1,118 @@
+use chrono::{DateTime, Utc};
 use dioxus::prelude::{ServerFnError, *};
 use serde::{Serialize, Deserialize};
 #[cfg(feature = "server")]
 use crate::database::create_connection;
 #[cfg(feature = "server")]
-use sqlx::{FromRow, Row};
-use crate::auth;
-use dioxus::logger::tracing::{info, error};
-#[derive(Clone, Serialize, Deserialize, Debug)]
+use sqlx::FromRow;
+#[cfg(feature = "server")]
+use crate::auth::{self, invalidate_all_user_sessions, request_error, require_role, require_scope, ApiScope, CurrentUser};
+#[cfg(feature = "server")]
+use axum::http::StatusCode;
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{info, error, warn};
+
+/// Number of accounts shown per page of the accounts console
+pub const ACCOUNT_LIST_PAGE_SIZE: u32 = 20;
+
+/// Account roles, from most to least privileged
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
 pub enum Role {
     Admin = 1,
     User = 2,
     Guest = 3
 }
 
+impl Role {
+    /// Every role, from most to least privileged
+    pub const ALL: [Role; 3] = [Role::Admin, Role::User, Role::Guest];
+
+    /// Returns the role with the given `roles.role_id`, if there is one
+    pub fn from_id(role_id: i32) -> Option<Role> {
+        Role::ALL.into_iter().find(|role| *role as i32 == role_id)
+    }
+
+    /// Returns the name of the role as stored in `roles.role_name`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::User => "user",
+            Role::Guest => "guest",
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 #[cfg_attr(feature = "server", derive(FromRow))]
 pub struct Account {
     pub account_id: Option<i32>,
     pub username: String,
     pub password_hash: String,
-    pub role_id: i32
+    pub role_id: i32,
+    /// Whether logging in also needs a TOTP code
+    #[serde(default)]
+    pub totp_enabled: bool,
+    /// Disabled accounts can't log in
+    pub is_active: bool,
+}
+
+/// An account as listed in the accounts console, without its password hash
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "server", derive(FromRow))]
+pub struct AccountSummary {
+    pub account_id: i32,
+    pub username: String,
+    pub role_id: i32,
+    pub is_active: bool,
+    pub totp_enabled: bool,
+    /// Set while the account is locked after too many failed logins
+    pub locked_until: Option<DateTime<Utc>>,
+    pub active_sessions: i64,
+}
+
+/// One page of accounts
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AccountPage {
+    pub accounts: Vec<AccountSummary>,
+    /// Total number of accounts across all pages
+    pub total: i64,
+    /// 1-based page number of these accounts
+    pub page: u32,
+}
+
+impl AccountPage {
+    /// Returns the number of pages needed to list every account
+    pub fn page_count(&self) -> u32 {
+        (self.total as u32).div_ceil(ACCOUNT_LIST_PAGE_SIZE)
+    }
 }
 
+/// Creates a new account. The caller must hold an active admin session.
+///
+/// Usernames are unique regardless of case, taking one that is already in use fails with
+/// a 409 saying so.
 #[server]
 pub async fn save_new_account(username: String, password: String, role: Role) -> Result<Account, ServerFnError> {
+    require_role(Role::Admin).await?;
+    insert_account(username, password, role).await
+}
+
+/// Creates a new account with a hashed password, the work done by [`save_new_account`]
+///
+/// # Arguments
+/// * `username` - The new account's username, unique regardless of case
+/// * `password` - The new account's password
+/// * `role` - The new account's role
+#[cfg(feature = "server")]
+pub async fn insert_account(username: String, password: String, role: Role) -> Result<Account, ServerFnError> {
+    let username = username.trim().to_string();
+    if username.is_empty() {
+        return Err(request_error(StatusCode::UNPROCESSABLE_ENTITY, "Username cannot be empty"));
+    }
+
     // Use random salt for better security
     let password_hash = auth::hash_password(password).await?;
-    let role_id = role.clone() as i32;
+    let role_id = role as i32;
     match create_connection().await {
         Ok(mut conn) => {
             let result = sqlx::query!(
@@ -46,12 +132,22 @@ pub async fn save_new_account(username: String, password: String, role: Role) ->
                         account_id: Some(inserted_id),
                         username,
                         password_hash,
-                        role_id
+                        role_id,
+                        totp_enabled: false,
+                        is_active: true,
                     })
                 }
+                Err(e) if is_unique_violation(&e) => {
+                    warn!("Account not created, username {username} is already taken");
+                    Err(request_error(
+                        StatusCode::CONFLICT,
+                        format!("The username \"{username}\" is already taken"),
+                    ))
+                }
                 Err(e) => {
+                    error!("Error creating account {username}: {e}");
                     Err(ServerFnError::new(format!(
-                        "Error occurred during blog insert: {e}"
+                        "Error creating account: {e}"
                     )))
                 }
             }
@@ -64,12 +160,13 @@ pub async fn save_new_account(username: String, password: String, role: Role) ->
     }
 }
 
-#[server]
+/// Loads an account, including its password hash, so this is only available on the server
+#[cfg(feature = "server")]
 pub async fn get_account_by_id(account_id: i32) -> Result<Option<Account>, ServerFnError>{
     match create_connection().await {
         Ok(mut conn) => {
             let result = sqlx::query_as::<_, Account>(
-                "SELECT account_id, username, password_hash, role_id
+                "SELECT account_id, username, password_hash, role_id, totp_enabled, is_active
                 FROM accounts
                 WHERE account_id = ?1",
             )
@@ -95,12 +192,13 @@ pub async fn get_account_by_id(account_id: i32) -> Result<Option<Account>, Serve
     }
 }
 
-#[server]
+/// Loads an account, including its password hash, so this is only available on the server
+#[cfg(feature = "server")]
 pub async fn get_account_by_username(username: String) -> Result<Option<Account>, ServerFnError>{
     match create_connection().await {
         Ok(mut conn) => {
             let result = sqlx::query_as::<_, Account>(
-                "SELECT account_id, username, password_hash, role_id
+                "SELECT account_id, username, password_hash, role_id, totp_enabled, is_active
                 FROM accounts
                 WHERE username = ?1",
             )
@@ -130,3 +228,209 @@ pub async fn get_account_by_username(username: String) -> Result<Option<Account>
         }
     }
 }
+
+/// Lists the accounts one page at a time, ordered by username. The caller must hold an
+/// active admin session.
+///
+/// # Arguments
+/// * `page` - 1-based page number, 0 is treated as the first page
+#[server]
+pub async fn get_accounts(page: u32) -> Result<AccountPage, ServerFnError> {
+    require_scope(Role::Admin, ApiScope::AccountsRead).await?;
+    load_accounts(page).await
+}
+
+/// Loads one page of accounts ordered by username for [`get_accounts`]
+///
+/// # Arguments
+/// * `page` - 1-based page number, 0 is treated as the first page
+#[cfg(feature = "server")]
+pub async fn load_accounts(page: u32) -> Result<AccountPage, ServerFnError> {
+    let page = page.max(1);
+    let now = Utc::now();
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let total = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM accounts")
+                .fetch_one(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error counting accounts: {e}");
+                    ServerFnError::new(format!("Error loading accounts: {e}"))
+                })?;
+
+            let accounts = sqlx::query_as::<_, AccountSummary>(
+                "SELECT account_id, username, role_id, is_active, totp_enabled,
+                    CASE WHEN datetime(locked_until) > datetime(?1) THEN locked_until END AS locked_until,
+                    (SELECT COUNT(*) FROM sessions
+                        WHERE sessions.account_id = accounts.account_id
+                        AND is_active = 1 AND datetime(expires_at) > datetime(?1)) AS active_sessions
+                FROM accounts
+                ORDER BY username COLLATE NOCASE, account_id
+                LIMIT ?2 OFFSET ?3",
+            )
+            .bind(now)
+            .bind(ACCOUNT_LIST_PAGE_SIZE)
+            .bind(i64::from(page - 1) * i64::from(ACCOUNT_LIST_PAGE_SIZE))
+            .fetch_all(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error loading accounts: {e}");
+                ServerFnError::new(format!("Error loading accounts: {e}"))
+            })?;
+
+            Ok(AccountPage {
+                accounts,
+                total,
+                page,
+            })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Changes the role of an account. The caller must hold an active admin session and
+/// can't change their own role, so there is always an admin left.
+///
+/// # Arguments
+/// * `account_id` - The account to change
+/// * `role` - Its new role
+#[server]
+pub async fn update_account_role(account_id: i32, role: Role) -> Result<(), ServerFnError> {
+    let user = require_role(Role::Admin).await?;
+    ensure_not_own_account(&user, account_id, "change the role of")?;
+
+    store_account_role(account_id, role).await?;
+    info!("{} changed the role of account {account_id} to {}", user.username, role.as_str());
+    Ok(())
+}
+
+/// Stores the new role of an account for [`update_account_role`]
+///
+/// # Arguments
+/// * `account_id` - The account to change
+/// * `role` - Its new role
+#[cfg(feature = "server")]
+pub async fn store_account_role(account_id: i32, role: Role) -> Result<(), ServerFnError> {
+    let role_id = role as i32;
+    match create_connection().await {
+        Ok(mut conn) => {
+            let result = sqlx::query!(
+                "UPDATE accounts SET role_id = ?1 WHERE account_id = ?2",
+                role_id,
+                account_id
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error changing role of account {account_id}: {e}");
+                ServerFnError::new(format!("Error changing role: {e}"))
+            })?;
+            if result.rows_affected() == 0 {
+                return Err(ServerFnError::new(format!("Account {account_id} not found")));
+            }
+            Ok(())
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Enables or disables an account. Disabling it also logs it out everywhere. The caller
+/// must hold an active admin session and can't disable their own account.
+///
+/// # Arguments
+/// * `account_id` - The account to change
+/// * `is_active` - Whether the account may log in
+#[server]
+pub async fn set_account_active(account_id: i32, is_active: bool) -> Result<(), ServerFnError> {
+    let user = require_role(Role::Admin).await?;
+    ensure_not_own_account(&user, account_id, "disable")?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let result = sqlx::query!(
+                "UPDATE accounts SET is_active = ?1 WHERE account_id = ?2",
+                is_active,
+                account_id
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error updating account {account_id}: {e}");
+                ServerFnError::new(format!("Error updating account: {e}"))
+            })?;
+            if result.rows_affected() == 0 {
+                return Err(ServerFnError::new(format!("Account {account_id} not found")));
+            }
+        }
+        Err(e) => {
+            return Err(ServerFnError::new(format!(
+                "Database connection error: {e}"
+            )))
+        }
+    }
+
+    if !is_active {
+        invalidate_all_user_sessions(account_id).await?;
+    }
+    info!(
+        "{} {} account {account_id}",
+        user.username,
+        if is_active { "enabled" } else { "disabled" }
+    );
+    Ok(())
+}
+
+/// Deletes an account along with its sessions, two-factor settings and recovery codes.
+/// Revisions and login attempts it made are kept without it. The caller must hold an
+/// active admin session and can't delete their own account.
+///
+/// # Arguments
+/// * `account_id` - The account to delete
+#[server]
+pub async fn delete_account(account_id: i32) -> Result<(), ServerFnError> {
+    let user = require_role(Role::Admin).await?;
+    ensure_not_own_account(&user, account_id, "delete")?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let result = sqlx::query!("DELETE FROM accounts WHERE account_id = ?1", account_id)
+                .execute(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error deleting account {account_id}: {e}");
+                    ServerFnError::new(format!("Error deleting account: {e}"))
+                })?;
+            if result.rows_affected() == 0 {
+                return Err(ServerFnError::new(format!("Account {account_id} not found")));
+            }
+            info!("{} deleted account {account_id}", user.username);
+            Ok(())
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Refuses changes an admin makes to their own account, which could lock them out
+#[cfg(feature = "server")]
+fn ensure_not_own_account(user: &CurrentUser, account_id: i32, action: &str) -> Result<(), ServerFnError> {
+    if user.account_id == account_id {
+        warn!("{} tried to {action} their own account", user.username);
+        return Err(ServerFnError::new(format!("You can't {action} your own account")));
+    }
+    Ok(())
+}
+
+/// Whether an insert failed because of a UNIQUE constraint, such as a taken username
+#[cfg(feature = "server")]
+fn is_unique_violation(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .is_some_and(|error| error.is_unique_violation())
+}
@@ -1,21 +1,157 @@
+use super::tag::{parse_tag_list, Tag};
+#[cfg(feature = "server")]
+use super::revision::record_revision;
+#[cfg(feature = "server")]
+use super::tag::{load_post_tags, save_post_tags};
 use crate::components::BlogPostFormData;
-use dioxus::{
-    logger::tracing::{error, info},
-    prelude::*,
-};
+#[cfg(feature = "server")]
+use crate::{auth::{request_error, require_scope, ApiScope}, models::Role};
+#[cfg(feature = "server")]
+use axum::http::StatusCode;
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info};
+use chrono::{DateTime, Utc};
+use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "server")]
 use crate::database::create_connection;
 #[cfg(feature = "server")]
-use sqlx::{FromRow, Row};
+use crate::markdown::{plain_text, render_content};
+#[cfg(feature = "server")]
+use sqlx::{Connection, FromRow};
+
+/// The format a blog post's content was authored in
+///
+/// Stored as lowercase text in the `blog_posts.content_format` column.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "server", derive(sqlx::Type))]
+#[cfg_attr(feature = "server", sqlx(rename_all = "lowercase"))]
+pub enum ContentFormat {
+    /// Raw HTML, used by posts written before Markdown support
+    Html,
+    /// CommonMark with the GFM table, task list, footnote and strikethrough extensions
+    #[default]
+    Markdown,
+}
+
+impl ContentFormat {
+    /// Returns the value stored in the database for this format
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentFormat::Html => "html",
+            ContentFormat::Markdown => "markdown",
+        }
+    }
+}
+
+/// Where a blog post is in its lifecycle
+///
+/// Only published posts, and scheduled posts whose `publish_at` has passed, are
+/// visible to anonymous readers. Stored as lowercase text in `blog_posts.status`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "server", derive(sqlx::Type))]
+#[cfg_attr(feature = "server", sqlx(rename_all = "lowercase"))]
+pub enum PostStatus {
+    /// Work in progress, only visible to admins
+    #[default]
+    Draft,
+    /// Becomes visible once `publish_at` has passed
+    Scheduled,
+    /// Visible to everyone
+    Published,
+    /// Retired post that is no longer listed or viewable by readers
+    Archived,
+}
+
+impl PostStatus {
+    /// Returns the value stored in the database for this status
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostStatus::Draft => "draft",
+            PostStatus::Scheduled => "scheduled",
+            PostStatus::Published => "published",
+            PostStatus::Archived => "archived",
+        }
+    }
+
+    /// Parses a status from its database/form value, defaulting to a draft
+    pub fn from_str_or_draft(value: &str) -> PostStatus {
+        match value {
+            "scheduled" => PostStatus::Scheduled,
+            "published" => PostStatus::Published,
+            "archived" => PostStatus::Archived,
+            _ => PostStatus::Draft,
+        }
+    }
+}
+
+/// Result of looking up a blog post by the key in its permalink
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum PostLookup {
+    /// The key is the post's current slug
+    Found(BlogPost),
+    /// The key is a legacy numeric ID or a previous slug, the post now lives at this slug
+    Redirect(String),
+    NotFound,
+}
+
+/// Generates a URL-safe slug from a title, e.g. "Hello, World!" becomes "hello-world"
+///
+/// Slugs are lowercase ASCII letters and digits separated by single dashes. Purely
+/// numeric slugs are prefixed with `post-` so they can never be mistaken for the
+/// legacy numeric permalinks.
+pub fn slugify(title: &str) -> String {
+    let slug = dash_separated(title);
+    if slug.is_empty() {
+        "post".to_string()
+    } else if slug.chars().all(|character| character.is_ascii_digit()) {
+        format!("post-{slug}")
+    } else {
+        slug
+    }
+}
+
+/// Lowercases `text` and joins its runs of ASCII letters and digits with single dashes,
+/// e.g. "Rust & WebAssembly" becomes "rust-and-webassembly". Returns an empty string
+/// if `text` contains no letters or digits.
+pub(crate) fn dash_separated(text: &str) -> String {
+    let mut dashed = String::with_capacity(text.len());
+    for character in text.trim().to_lowercase().chars() {
+        if character.is_ascii_alphanumeric() {
+            dashed.push(character);
+        } else if character == '&' {
+            dashed.push_str("-and-");
+        } else if !matches!(character, '\'' | '"') && !dashed.ends_with('-') {
+            dashed.push('-');
+        }
+    }
+
+    dashed.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+/// Columns selected whenever a full `BlogPost` row is loaded
+#[cfg(feature = "server")]
+pub(super) const BLOG_POST_COLUMNS: &str =
+    "id, title, slug, content, content_format, status, publish_at, created_at, updated_at, published_at";
+
+/// SQL condition matching the posts anonymous readers are allowed to see
+#[cfg(feature = "server")]
+pub(super) const VISIBLE_POST_FILTER: &str =
+    "(status = 'published' OR (status = 'scheduled' AND datetime(publish_at) <= datetime('now')))";
 
 /// This is a separation of the model used for the database and the model used for the API.
 /// This allows us to eliminate the SQLx dependency from the frontend layer and ensures
 /// that database-specific code only exists in server builds.
+#[derive(Debug, Clone, PartialEq)]
 pub struct BlogPostModel {
     pub id: Option<i32>,
     pub title: String,
-    pub content: String,
+    pub slug: String,
+    /// Sanitized HTML rendered on the server, safe to inject into the page
+    pub content_html: String,
+    pub status: PostStatus,
+    pub published_at: Option<DateTime<Utc>>,
+    pub tags: Vec<Tag>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
@@ -23,7 +159,27 @@ pub struct BlogPostModel {
 pub struct BlogPost {
     pub id: Option<i32>,
     pub title: String,
+    /// Unique permalink segment, generated from the title by the server when left empty
+    pub slug: String,
     pub content: String,
+    pub content_format: ContentFormat,
+    /// Sanitized HTML rendered from `content`, only populated by the server
+    /// when a post is loaded for display
+    #[cfg_attr(feature = "server", sqlx(default))]
+    pub rendered_html: String,
+    pub status: PostStatus,
+    /// When a scheduled post becomes visible
+    pub publish_at: Option<DateTime<Utc>>,
+    /// Set by the server when the post is first saved
+    pub created_at: Option<DateTime<Utc>>,
+    /// Set by the server every time the post is saved
+    pub updated_at: Option<DateTime<Utc>>,
+    /// When the post went (or goes) public, set by the server
+    pub published_at: Option<DateTime<Utc>>,
+    /// Tags the post is filed under, stored in the `blog_post_tags` link table and only
+    /// populated by the server when a single post is loaded
+    #[cfg_attr(feature = "server", sqlx(skip))]
+    pub tags: Vec<Tag>,
 }
 
 impl BlogPost {
@@ -35,7 +191,36 @@ impl BlogPost {
         BlogPostModel {
             id: self.id,
             title: self.title.clone(),
-            content: self.content.clone(),
+            slug: self.slug.clone(),
+            content_html: self.rendered_html.clone(),
+            status: self.status,
+            published_at: self.published_at,
+            tags: self.tags.clone(),
+        }
+    }
+
+    /// Returns true if anonymous readers are allowed to see this post
+    pub fn is_visible(&self) -> bool {
+        match self.status {
+            PostStatus::Published => true,
+            PostStatus::Scheduled => self.publish_at.is_some_and(|publish_at| publish_at <= Utc::now()),
+            PostStatus::Draft | PostStatus::Archived => false,
+        }
+    }
+
+    /// Works out the `published_at` timestamp a post should have after being saved
+    /// with its current status, given the value it had before the save
+    pub fn resolve_published_at(
+        &self,
+        previous: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        match self.status {
+            // Keep the original publication date when re-saving a published post
+            PostStatus::Published => previous.filter(|published_at| *published_at <= now).or(Some(now)),
+            PostStatus::Scheduled => self.publish_at,
+            PostStatus::Archived => previous,
+            PostStatus::Draft => None,
         }
     }
 
@@ -48,36 +233,25 @@ impl BlogPost {
         BlogPost {
             id: None,
             title: form_data.title,
+            slug: form_data.slug,
             content: form_data.content,
+            content_format: form_data.content_format,
+            rendered_html: String::new(),
+            status: form_data.status,
+            publish_at: form_data.publish_at,
+            created_at: None,
+            updated_at: None,
+            published_at: None,
+            tags: parse_tag_list(&form_data.tags),
         }
     }
 }
 
-#[server]
-pub async fn get_post_list() -> Result<Vec<BlogPost>, ServerFnError> {
-    let mut return_list: Vec<BlogPost> = Vec::new();
-    match create_connection().await {
-        Ok(mut conn) => {
-            let mut stream =
-                sqlx::query_as::<_, BlogPost>("SELECT id, title, content FROM blog_posts")
-                    .fetch_all(&mut conn)
-                    .await;
-
-            stream
-                .into_iter()
-                .for_each(|mut post| return_list.append(&mut post));
-
-            Ok(return_list)
-        }
-        Err(error) => Err(ServerFnError::new(format!(
-            "uh oh error occurred extrating list of posts:{:?}",
-            error
-        ))),
-    }
-}
-
 /// Retrieves a blog post by ID by fetching a blog post from the database using its ID.
 /// It uses SQLx for async database operations and provides proper error handling.
+/// The returned post has `rendered_html` populated with sanitized HTML ready for display.
+/// Posts that are not visible to anonymous readers are treated as not found, admins can
+/// use [`preview_post`] to load those.
 ///
 /// # Arguments
 /// * `post_id` - The ID of the blog post to retrieve
@@ -87,29 +261,86 @@ pub async fn get_post_list() -> Result<Vec<BlogPost>, ServerFnError> {
 /// or a `ServerFnError` if an error occurs.
 ///
 /// # Examples
-/// ```rust
+/// ```ignore
 /// let post = get_post_by_id(1).await?;
 /// ```
 #[server]
 pub async fn get_post_by_id(post_id: i32) -> Result<Option<BlogPost>, ServerFnError> {
     match create_connection().await {
         Ok(mut conn) => {
-            let result = sqlx::query_as::<_, BlogPost>(
-                "SELECT id, title, content FROM blog_posts WHERE id = ?1",
-            )
+            let query = format!(
+                "SELECT {BLOG_POST_COLUMNS} FROM blog_posts WHERE id = ?1 AND {VISIBLE_POST_FILTER}"
+            );
+            let result = sqlx::query_as::<_, BlogPost>(&query)
             .bind(post_id)
             .fetch_optional(&mut conn)
             .await;
 
             match result {
-                Ok(post) => {
-                    if post.is_some() {
-                        info!("Post found with id: {post_id}");
-                    } else {
-                        info!("No post found with id: {post_id}");
+                Ok(Some(mut post)) => {
+                    info!("Post found with id: {post_id}");
+                    post.rendered_html = render_content(&post.content, &post.content_format);
+                    post.tags = load_post_tags(&mut conn, post_id).await?;
+                    Ok(Some(post))
+                }
+                Ok(None) => {
+                    info!("No post found with id: {post_id}");
+                    Ok(None)
+                }
+                Err(e) => {
+                    error!("Error loading blog post: {e}");
+                    Err(ServerFnError::new(format!("Error loading blog post: {e}")))
+                }
+            }
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Retrieves a visible blog post by the key in its permalink, with `rendered_html` populated.
+///
+/// Legacy numeric IDs and slugs the post had before being renamed resolve to
+/// [`PostLookup::Redirect`] with the post's current slug so old links keep working.
+///
+/// # Arguments
+/// * `slug` - The slug, previous slug or legacy numeric ID from the URL
+///
+/// # Examples
+/// ```ignore
+/// let lookup = get_post_by_slug("hello-world".to_string()).await?;
+/// ```
+#[server]
+pub async fn get_post_by_slug(slug: String) -> Result<PostLookup, ServerFnError> {
+    match create_connection().await {
+        Ok(mut conn) => {
+            let query = format!(
+                "SELECT {BLOG_POST_COLUMNS} FROM blog_posts WHERE slug = ?1 AND {VISIBLE_POST_FILTER}"
+            );
+            let result = sqlx::query_as::<_, BlogPost>(&query)
+                .bind(&slug)
+                .fetch_optional(&mut conn)
+                .await;
+
+            match result {
+                Ok(Some(mut post)) => {
+                    post.rendered_html = render_content(&post.content, &post.content_format);
+                    if let Some(post_id) = post.id {
+                        post.tags = load_post_tags(&mut conn, post_id).await?;
                     }
-                    Ok(post)
+                    Ok(PostLookup::Found(post))
                 }
+                Ok(None) => match find_post_redirect(&mut conn, &slug).await? {
+                    Some(current_slug) => {
+                        info!("Redirecting blog post {slug} to {current_slug}");
+                        Ok(PostLookup::Redirect(current_slug))
+                    }
+                    None => {
+                        info!("No post found with slug: {slug}");
+                        Ok(PostLookup::NotFound)
+                    }
+                },
                 Err(e) => {
                     error!("Error loading blog post: {e}");
                     Err(ServerFnError::new(format!("Error loading blog post: {e}")))
@@ -122,8 +353,242 @@ pub async fn get_post_by_id(post_id: i32) -> Result<Option<BlogPost>, ServerFnEr
     }
 }
 
+/// Finds the current slug for a permalink key that is not itself a current slug,
+/// either a legacy numeric ID or a slug the post had before it was renamed.
+/// Only visible posts are resolved.
+#[cfg(feature = "server")]
+pub async fn find_post_redirect(
+    conn: &mut sqlx::SqliteConnection,
+    key: &str,
+) -> Result<Option<String>, ServerFnError> {
+    let result = match key.parse::<i32>() {
+        Ok(post_id) => {
+            let query = format!("SELECT slug FROM blog_posts WHERE id = ?1 AND {VISIBLE_POST_FILTER}");
+            sqlx::query_scalar::<_, String>(&query)
+                .bind(post_id)
+                .fetch_optional(conn)
+                .await
+        }
+        Err(_) => {
+            let query = format!(
+                "SELECT blog_posts.slug
+                FROM blog_post_slug_history
+                JOIN blog_posts ON blog_posts.id = blog_post_slug_history.post_id
+                WHERE blog_post_slug_history.slug = ?1 AND {VISIBLE_POST_FILTER}"
+            );
+            sqlx::query_scalar::<_, String>(&query)
+                .bind(key)
+                .fetch_optional(conn)
+                .await
+        }
+    };
+
+    result.map_err(|e| {
+        error!("Error resolving blog post redirect: {e}");
+        ServerFnError::new(format!("Error resolving blog post redirect: {e}"))
+    })
+}
+
+/// Returns `slug`, or `slug` with the first free numeric suffix (`-2`, `-3`, ...) if it is
+/// already used by another post, either as its current slug or one it had in the past
+#[cfg(feature = "server")]
+async fn unique_slug(
+    conn: &mut sqlx::SqliteConnection,
+    slug: &str,
+    post_id: Option<i32>,
+) -> Result<String, ServerFnError> {
+    let mut candidate = slug.to_string();
+    let mut suffix = 2;
+    loop {
+        let taken = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM blog_posts WHERE slug = ?1 AND id IS NOT ?2)
+                OR EXISTS(SELECT 1 FROM blog_post_slug_history WHERE slug = ?1 AND post_id IS NOT ?2)",
+        )
+        .bind(&candidate)
+        .bind(post_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("Error checking slug availability: {e}");
+            ServerFnError::new(format!("Error checking slug availability: {e}"))
+        })?;
+
+        if !taken {
+            return Ok(candidate);
+        }
+        candidate = format!("{slug}-{suffix}");
+        suffix += 1;
+    }
+}
+
+/// Gives every post without a slug one generated from its title, as the editor would
+///
+/// Posts written before slugs existed are left without one by the migration adding the
+/// column. Runs when the server starts and after `web-admin migrate`, and does nothing
+/// once every post has a slug.
+///
+/// # Returns
+/// A `Result` containing the number of posts given a slug, or a `ServerFnError` if an
+/// error occurs.
+#[cfg(feature = "server")]
+pub async fn backfill_post_slugs() -> Result<u64, ServerFnError> {
+    let mut conn = create_connection()
+        .await
+        .map_err(|e| ServerFnError::new(format!("Database connection error: {e}")))?;
+    let posts = sqlx::query_as::<_, (i32, String)>("SELECT id, title FROM blog_posts WHERE slug IS NULL ORDER BY id")
+        .fetch_all(&mut conn)
+        .await
+        .map_err(|e| {
+            error!("Error loading posts without a slug: {e}");
+            ServerFnError::new(format!("Error loading posts without a slug: {e}"))
+        })?;
+
+    for (post_id, title) in &posts {
+        let slug = unique_slug(&mut conn, &slugify(title), Some(*post_id)).await?;
+        sqlx::query("UPDATE blog_posts SET slug = ?1 WHERE id = ?2")
+            .bind(&slug)
+            .bind(post_id)
+            .execute(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error saving the slug of post {post_id}: {e}");
+                ServerFnError::new(format!("Error saving the slug of post {post_id}: {e}"))
+            })?;
+        info!("Post {post_id} given the slug {slug}");
+    }
+    Ok(posts.len() as u64)
+}
+
+/// Stores the plain text and word count of every post that doesn't have them yet, as
+/// saving it would
+///
+/// Posts from before search indexed plain text only have their titles indexed, and sort
+/// first by reading time, until this runs. Runs when the server starts and after
+/// `web-admin migrate`, and does nothing once every post has its text.
+///
+/// # Returns
+/// A `Result` containing the number of posts whose text was filled in, or a
+/// `ServerFnError` if an error occurs.
+#[cfg(feature = "server")]
+pub async fn backfill_post_text() -> Result<u64, ServerFnError> {
+    let mut conn = create_connection()
+        .await
+        .map_err(|e| ServerFnError::new(format!("Database connection error: {e}")))?;
+    let post_ids = sqlx::query_scalar::<_, i32>("SELECT id FROM blog_posts WHERE search_text IS NULL OR word_count IS NULL ORDER BY id")
+        .fetch_all(&mut conn)
+        .await
+        .map_err(|e| {
+            error!("Error loading posts without search text: {e}");
+            ServerFnError::new(format!("Error loading posts without search text: {e}"))
+        })?;
+
+    for post_id in &post_ids {
+        store_post_text(&mut conn, *post_id).await?;
+    }
+    Ok(post_ids.len() as u64)
+}
+
+/// Stores the plain text of a post's content in `search_text`, which the search index is
+/// built from, and its number of words in `word_count`, which the post list sorts by
+/// reading time on. Called after every change to a post's content.
+#[cfg(feature = "server")]
+pub(super) async fn store_post_text(conn: &mut sqlx::SqliteConnection, post_id: i32) -> Result<(), ServerFnError> {
+    let post = sqlx::query_as::<_, (String, ContentFormat)>("SELECT content, content_format FROM blog_posts WHERE id = ?1")
+        .bind(post_id)
+        .fetch_optional(&mut *conn)
+        .await;
+    let result = match post {
+        Ok(Some((content, content_format))) => {
+            let text = plain_text(&content, &content_format);
+            sqlx::query("UPDATE blog_posts SET search_text = ?1, word_count = ?2 WHERE id = ?3")
+                .bind(&text)
+                .bind(text.split_whitespace().count() as i64)
+                .bind(post_id)
+                .execute(&mut *conn)
+                .await
+                .map(|_| ())
+        }
+        Ok(None) => Ok(()),
+        Err(e) => Err(e),
+    };
+    result.map_err(|e| {
+        error!("Error storing the text of post {post_id}: {e}");
+        ServerFnError::new(format!("Error storing the text of post {post_id}: {e}"))
+    })
+}
+
+/// Converts an error saving a post into a 409 if another post took its slug in the
+/// meantime, or else a server error starting with `context`
+#[cfg(feature = "server")]
+fn slug_conflict_or(e: sqlx::Error, context: &str) -> ServerFnError {
+    if e.as_database_error().is_some_and(|e| e.is_unique_violation()) {
+        return request_error(StatusCode::CONFLICT, "Another post is already using this slug");
+    }
+    error!("{context}: {e}");
+    ServerFnError::new(format!("{context}: {e}"))
+}
+
+/// Starts the transaction a post is saved in, so its slug history, tags, text and
+/// revisions only change along with the post itself
+#[cfg(feature = "server")]
+pub(super) async fn begin_save(
+    conn: &mut sqlx::SqliteConnection,
+) -> Result<sqlx::Transaction<'_, sqlx::Sqlite>, ServerFnError> {
+    conn.begin().await.map_err(|e| {
+        error!("Error starting to save a blog post: {e}");
+        ServerFnError::new(format!("Error starting to save a blog post: {e}"))
+    })
+}
+
+/// Commits a transaction started by [`begin_save`]
+#[cfg(feature = "server")]
+pub(super) async fn commit_save(transaction: sqlx::Transaction<'_, sqlx::Sqlite>) -> Result<(), ServerFnError> {
+    transaction.commit().await.map_err(|e| {
+        error!("Error saving blog post: {e}");
+        ServerFnError::new(format!("Error saving blog post: {e}"))
+    })
+}
+
+/// Remembers a post's previous slug so the old permalink redirects to the new one
+#[cfg(feature = "server")]
+async fn record_slug_change(
+    conn: &mut sqlx::SqliteConnection,
+    post_id: i32,
+    old_slug: &str,
+    new_slug: &str,
+) -> Result<(), ServerFnError> {
+    let result = sqlx::query!(
+        "INSERT OR REPLACE INTO blog_post_slug_history (slug, post_id) VALUES (?1, ?2)",
+        old_slug,
+        post_id
+    )
+    .execute(&mut *conn)
+    .await;
+    if let Err(e) = result {
+        error!("Error recording previous slug: {e}");
+        return Err(ServerFnError::new(format!("Error recording previous slug: {e}")));
+    }
+
+    // The post may be moving back to a slug it used before
+    let result = sqlx::query!(
+        "DELETE FROM blog_post_slug_history WHERE slug = ?1",
+        new_slug
+    )
+    .execute(&mut *conn)
+    .await;
+    if let Err(e) = result {
+        error!("Error clearing reused slug: {e}");
+        return Err(ServerFnError::new(format!("Error clearing reused slug: {e}")));
+    }
+
+    info!("Post {post_id} slug changed from {old_slug} to {new_slug}");
+    Ok(())
+}
+
 /// Saves a new blog post to the database by inserting a new blog post into
 /// the database and returns the created post with its assigned ID.
+/// The caller must hold an active admin session, who is recorded as the author
+/// of the post's first revision.
 ///
 /// # Arguments
 /// * `blog_post_to_save` - The blog post data to save
@@ -134,35 +599,368 @@ pub async fn get_post_by_id(post_id: i32) -> Result<Option<BlogPost>, ServerFnEr
 ///
 /// # Examples
 ///
-/// ```rust
+/// ```ignore
 /// let new_post = BlogPost::from_form_data(form_data);
 /// let saved_post = save_post(new_post).await?;
 /// ```
 #[server]
 pub async fn save_post(blog_post_to_save: BlogPost) -> Result<Option<BlogPost>, ServerFnError> {
+    let user = require_scope(Role::Admin, ApiScope::PostsWrite).await?;
+
+    if blog_post_to_save.status == PostStatus::Scheduled && blog_post_to_save.publish_at.is_none() {
+        return Err(request_error(StatusCode::UNPROCESSABLE_ENTITY, "A scheduled post needs a publish date"));
+    }
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            // The server decides when a new post was created and published
+            let blog_post_to_save = BlogPost {
+                created_at: None,
+                updated_at: None,
+                published_at: None,
+                ..blog_post_to_save
+            };
+            insert_post(&mut conn, blog_post_to_save, Some(user.account_id)).await.map(Some)
+        }
+        Err(e) => {
+            Err(ServerFnError::new(format!(
+                "Database connection error: {e}"
+            )))
+        }
+    }
+}
+
+/// Inserts a blog post with a new ID, its tags and its first revision. Used by
+/// [`save_post`] and post imports.
+///
+/// The slug is made unique, and `created_at`, `updated_at` and `published_at` are kept
+/// when they are set, so imported posts keep their dates. Nothing is saved unless the
+/// post, its tags and its revision all are.
+///
+/// # Arguments
+/// * `conn` - An open database connection
+/// * `blog_post_to_save` - The post to insert, its `id` is ignored
+/// * `author_id` - The account recorded as the author of the first revision, if any
+///
+/// # Returns
+/// A `Result` containing the inserted post, or a `ServerFnError` if an error occurs.
+#[cfg(feature = "server")]
+pub async fn insert_post(
+    conn: &mut sqlx::SqliteConnection,
+    blog_post_to_save: BlogPost,
+    author_id: Option<i32>,
+) -> Result<BlogPost, ServerFnError> {
+    let requested_slug = if blog_post_to_save.slug.trim().is_empty() {
+        slugify(&blog_post_to_save.title)
+    } else {
+        slugify(&blog_post_to_save.slug)
+    };
+    let mut transaction = begin_save(conn).await?;
+    let slug = unique_slug(&mut transaction, &requested_slug, None).await?;
+    let now = Utc::now();
+    let created_at = blog_post_to_save.created_at.unwrap_or(now);
+    let updated_at = blog_post_to_save.updated_at.unwrap_or(now);
+    let published_at = blog_post_to_save.resolve_published_at(blog_post_to_save.published_at, now);
+    let content_format = blog_post_to_save.content_format.as_str();
+    let status = blog_post_to_save.status.as_str();
+    let result = sqlx::query!(
+        "INSERT INTO blog_posts (title, slug, content, content_format, status, publish_at, created_at, updated_at, published_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        blog_post_to_save.title,
+        slug,
+        blog_post_to_save.content,
+        content_format,
+        status,
+        blog_post_to_save.publish_at,
+        created_at,
+        updated_at,
+        published_at
+    )
+    .execute(&mut *transaction)
+    .await;
+
+    match result {
+        Ok(query_result) => {
+            let inserted_id = query_result.last_insert_rowid() as i32;
+            store_post_text(&mut transaction, inserted_id).await?;
+            save_post_tags(&mut transaction, inserted_id, &blog_post_to_save.tags).await?;
+            record_revision(&mut transaction, inserted_id, author_id).await?;
+            commit_save(transaction).await?;
+            info!("Blog post saved with id: {inserted_id}");
+            Ok(BlogPost {
+                id: Some(inserted_id),
+                slug,
+                created_at: Some(created_at),
+                updated_at: Some(updated_at),
+                published_at,
+                ..blog_post_to_save
+            })
+        }
+        Err(e) => Err(slug_conflict_or(e, "Error occurred during blog insert")),
+    }
+}
+
+/// Updates an existing blog post's title, content, format, tags and lifecycle status.
+/// The caller must hold an active admin session, and every update is recorded as a
+/// new revision of the post.
+///
+/// # Arguments
+/// * `blog_post_to_update` - The post to save, `id` must be set
+///
+/// # Returns
+/// A `Result` containing either `Some(BlogPost)` with the updated post data,
+/// `None` if no post exists with that ID, or a `ServerFnError` if an error occurs.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut post = BlogPost::from_form_data(form_data);
+/// post.id = Some(3);
+/// let updated_post = update_post(post).await?;
+/// ```
+#[server]
+pub async fn update_post(
+    blog_post_to_update: BlogPost,
+) -> Result<Option<BlogPost>, ServerFnError> {
+    let user = require_scope(Role::Admin, ApiScope::PostsWrite).await?;
+
+    let Some(post_id) = blog_post_to_update.id else {
+        return Err(request_error(StatusCode::UNPROCESSABLE_ENTITY, "Cannot update a blog post without an id"));
+    };
+    if blog_post_to_update.status == PostStatus::Scheduled && blog_post_to_update.publish_at.is_none() {
+        return Err(request_error(StatusCode::UNPROCESSABLE_ENTITY, "A scheduled post needs a publish date"));
+    }
+
     match create_connection().await {
         Ok(mut conn) => {
+            let mut transaction = begin_save(&mut conn).await?;
+            let Some(existing_post) = load_post(&mut transaction, post_id).await? else {
+                info!("No post found to update with id: {post_id}");
+                return Ok(None);
+            };
+
+            // A manually entered slug wins, otherwise the slug follows the title
+            let requested_slug = blog_post_to_update.slug.trim();
+            let requested_slug = if !requested_slug.is_empty() && requested_slug != existing_post.slug {
+                slugify(requested_slug)
+            } else if requested_slug.is_empty() || blog_post_to_update.title != existing_post.title {
+                slugify(&blog_post_to_update.title)
+            } else {
+                existing_post.slug.clone()
+            };
+            let slug = unique_slug(&mut transaction, &requested_slug, Some(post_id)).await?;
+            if slug != existing_post.slug {
+                record_slug_change(&mut transaction, post_id, &existing_post.slug, &slug).await?;
+            }
+
+            let now = Utc::now();
+            let published_at = blog_post_to_update.resolve_published_at(existing_post.published_at, now);
+            let content_format = blog_post_to_update.content_format.as_str();
+            let status = blog_post_to_update.status.as_str();
             let result = sqlx::query!(
-                "INSERT INTO blog_posts (title, content) VALUES (?1, ?2)",
-                blog_post_to_save.title,
-                blog_post_to_save.content
+                "UPDATE blog_posts
+                SET title = ?1, slug = ?2, content = ?3, content_format = ?4, status = ?5, publish_at = ?6,
+                    updated_at = ?7, published_at = ?8
+                WHERE id = ?9",
+                blog_post_to_update.title,
+                slug,
+                blog_post_to_update.content,
+                content_format,
+                status,
+                blog_post_to_update.publish_at,
+                now,
+                published_at,
+                post_id
             )
-            .execute(&mut conn)
+            .execute(&mut *transaction)
             .await;
 
             match result {
-                Ok(query_result) => {
-                    let inserted_id = query_result.last_insert_rowid() as i32;
-                    info!("Blog post saved with id: {inserted_id}");
+                Ok(_) => {
+                    store_post_text(&mut transaction, post_id).await?;
+                    save_post_tags(&mut transaction, post_id, &blog_post_to_update.tags).await?;
+                    record_revision(&mut transaction, post_id, Some(user.account_id)).await?;
+                    commit_save(transaction).await?;
+                    info!("Blog post updated with id: {post_id}");
                     Ok(Some(BlogPost {
-                        id: Some(inserted_id),
-                        title: blog_post_to_save.title,
-                        content: blog_post_to_save.content,
+                        slug,
+                        created_at: existing_post.created_at,
+                        updated_at: Some(now),
+                        published_at,
+                        ..blog_post_to_update
                     }))
                 }
+                Err(e) => Err(slug_conflict_or(e, "Error occurred during blog update")),
+            }
+        }
+        Err(e) => {
+            Err(ServerFnError::new(format!(
+                "Database connection error: {e}"
+            )))
+        }
+    }
+}
+
+/// Retrieves any blog post by ID regardless of its status, with `rendered_html` populated,
+/// so admins can edit and preview drafts and scheduled posts. The caller must hold an
+/// admin session.
+///
+/// # Arguments
+/// * `post_id` - The ID of the blog post to retrieve
+#[server]
+pub async fn preview_post(post_id: i32) -> Result<Option<BlogPost>, ServerFnError> {
+    require_scope(Role::Admin, ApiScope::PostsRead).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let Some(mut post) = load_post(&mut conn, post_id).await? else {
+                return Ok(None);
+            };
+            post.rendered_html = render_content(&post.content, &post.content_format);
+            post.tags = load_post_tags(&mut conn, post_id).await?;
+            Ok(Some(post))
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Retrieves every blog post regardless of status for the admin post list, most
+/// recently updated first. The caller must hold an active admin session.
+#[server]
+pub async fn get_admin_post_list() -> Result<Vec<BlogPost>, ServerFnError> {
+    require_scope(Role::Admin, ApiScope::PostsRead).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let query = format!("SELECT {BLOG_POST_COLUMNS} FROM blog_posts ORDER BY updated_at DESC");
+            sqlx::query_as::<_, BlogPost>(&query)
+                .fetch_all(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error loading admin post list: {e}");
+                    ServerFnError::new(format!("Error loading admin post list: {e}"))
+                })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Loads the most recently published visible posts, optionally only those filed under
+/// a tag, with `rendered_html` and `tags` populated. Used to build the syndication feeds.
+///
+/// # Arguments
+/// * `conn` - An open database connection
+/// * `tag_id` - Only include posts filed under the tag with this ID
+/// * `limit` - The maximum number of posts to load
+#[cfg(feature = "server")]
+pub async fn load_recent_posts(
+    conn: &mut sqlx::SqliteConnection,
+    tag_id: Option<i32>,
+    limit: u32,
+) -> Result<Vec<BlogPost>, ServerFnError> {
+    let query = format!(
+        "SELECT {BLOG_POST_COLUMNS} FROM blog_posts
+        WHERE {VISIBLE_POST_FILTER}
+            AND (?1 IS NULL OR id IN (SELECT post_id FROM blog_post_tags WHERE tag_id = ?1))
+        ORDER BY datetime(published_at) DESC
+        LIMIT ?2"
+    );
+    let mut posts = sqlx::query_as::<_, BlogPost>(&query)
+        .bind(tag_id)
+        .bind(limit)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("Error loading recent posts: {e}");
+            ServerFnError::new(format!("Error loading recent posts: {e}"))
+        })?;
+
+    for post in posts.iter_mut() {
+        post.rendered_html = render_content(&post.content, &post.content_format);
+        if let Some(post_id) = post.id {
+            post.tags = load_post_tags(&mut *conn, post_id).await?;
+        }
+    }
+    Ok(posts)
+}
+
+/// Loads every blog post regardless of its status, oldest first, with `tags` populated,
+/// for exporting them.
+///
+/// # Arguments
+/// * `conn` - An open database connection
+#[cfg(feature = "server")]
+pub async fn load_all_posts(conn: &mut sqlx::SqliteConnection) -> Result<Vec<BlogPost>, ServerFnError> {
+    let query = format!("SELECT {BLOG_POST_COLUMNS} FROM blog_posts ORDER BY id");
+    let mut posts = sqlx::query_as::<_, BlogPost>(&query)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("Error loading blog posts: {e}");
+            ServerFnError::new(format!("Error loading blog posts: {e}"))
+        })?;
+
+    for post in posts.iter_mut() {
+        if let Some(post_id) = post.id {
+            post.tags = load_post_tags(&mut *conn, post_id).await?;
+        }
+    }
+    Ok(posts)
+}
+
+/// Loads a blog post by ID regardless of its status
+#[cfg(feature = "server")]
+pub(super) async fn load_post(
+    conn: &mut sqlx::SqliteConnection,
+    post_id: i32,
+) -> Result<Option<BlogPost>, ServerFnError> {
+    let query = format!("SELECT {BLOG_POST_COLUMNS} FROM blog_posts WHERE id = ?1");
+    sqlx::query_as::<_, BlogPost>(&query)
+        .bind(post_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| {
+            error!("Error loading blog post: {e}");
+            ServerFnError::new(format!("Error loading blog post: {e}"))
+        })
+}
+
+/// Deletes a blog post by ID. The caller must hold an active admin session.
+///
+/// # Arguments
+/// * `post_id` - The ID of the blog post to delete
+///
+/// # Returns
+/// A `Result` containing `true` if the post was deleted, `false` if no post
+/// exists with that ID, or a `ServerFnError` if an error occurs.
+#[server]
+pub async fn delete_post(post_id: i32) -> Result<bool, ServerFnError> {
+    require_scope(Role::Admin, ApiScope::PostsWrite).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let result = sqlx::query!("DELETE FROM blog_posts WHERE id = ?1", post_id)
+                .execute(&mut conn)
+                .await;
+
+            match result {
+                Ok(query_result) => {
+                    let deleted = query_result.rows_affected() > 0;
+                    if deleted {
+                        info!("Blog post deleted with id: {post_id}");
+                    } else {
+                        info!("No post found to delete with id: {post_id}");
+                    }
+                    Ok(deleted)
+                }
                 Err(e) => {
+                    error!("Error occurred during blog delete: {e}");
                     Err(ServerFnError::new(format!(
-                        "Error occurred during blog insert: {e}"
+                        "Error occurred during blog delete: {e}"
                     )))
                 }
             }
@@ -174,3 +972,40 @@ pub async fn save_post(blog_post_to_save: BlogPost) -> Result<Option<BlogPost>,
         }
     }
 }
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use sqlx::SqliteConnection;
+
+    async fn count(conn: &mut SqliteConnection, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(conn)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn failed_insert_leaves_nothing_behind() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&mut conn).await.unwrap();
+        let post = BlogPost {
+            title: "Half saved".to_string(),
+            content: "Some text".to_string(),
+            tags: parse_tag_list("rust, web"),
+            ..BlogPost::default()
+        };
+
+        // The revision can't name an account that doesn't exist, and fails last
+        let error = insert_post(&mut conn, post.clone(), Some(999)).await.unwrap_err();
+        assert!(error.to_string().contains("Error recording revision"), "{error}");
+        for table in ["blog_posts", "blog_post_tags", "tags", "blog_post_revisions"] {
+            assert_eq!(count(&mut conn, table).await, 0, "{table}");
+        }
+
+        let saved = insert_post(&mut conn, post, None).await.unwrap();
+        assert_eq!(saved.slug, "half-saved");
+        assert_eq!(count(&mut conn, "blog_post_tags").await, 2);
+        assert_eq!(count(&mut conn, "blog_post_revisions").await, 1);
+    }
+}
@@ -0,0 +1,409 @@
+#[cfg(feature = "server")]
+use super::blog_post::VISIBLE_POST_FILTER;
+#[cfg(feature = "server")]
+use crate::{auth::{require_scope, ApiScope}, models::Role};
+#[cfg(feature = "server")]
+use crate::client_info::client_ip;
+#[cfg(feature = "server")]
+use crate::database::create_connection;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info, warn};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use sqlx::FromRow;
+
+/// Maximum number of comments a single IP address may submit within
+/// [`COMMENT_RATE_LIMIT_WINDOW_MINUTES`]
+#[cfg(feature = "server")]
+const COMMENT_RATE_LIMIT: i64 = 5;
+
+#[cfg(feature = "server")]
+const COMMENT_RATE_LIMIT_WINDOW_MINUTES: i64 = 10;
+
+/// Maximum lengths of the fields of a submitted comment, in characters
+#[cfg(feature = "server")]
+const MAX_AUTHOR_NAME_LENGTH: usize = 100;
+#[cfg(feature = "server")]
+const MAX_AUTHOR_EMAIL_LENGTH: usize = 200;
+#[cfg(feature = "server")]
+const MAX_COMMENT_LENGTH: usize = 5000;
+
+/// Where a comment is in moderation
+///
+/// New comments start out pending and are only shown under a post once approved.
+/// Stored as lowercase text in `comments.status`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "server", derive(sqlx::Type))]
+#[cfg_attr(feature = "server", sqlx(rename_all = "lowercase"))]
+pub enum CommentStatus {
+    /// Waiting in the moderation queue
+    #[default]
+    Pending,
+    /// Shown under the post
+    Approved,
+    /// Hidden, but not spam
+    Rejected,
+    /// Hidden and marked as spam
+    Spam,
+}
+
+impl CommentStatus {
+    /// Returns the value stored in the database for this status
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommentStatus::Pending => "pending",
+            CommentStatus::Approved => "approved",
+            CommentStatus::Rejected => "rejected",
+            CommentStatus::Spam => "spam",
+        }
+    }
+}
+
+/// An approved comment as shown to readers, without the author's email or IP address
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "server", derive(FromRow))]
+pub struct Comment {
+    pub id: i32,
+    pub post_id: i32,
+    pub parent_id: Option<i32>,
+    pub author_name: String,
+    pub content: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// A comment along with its approved replies, oldest first
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommentThread {
+    pub comment: Comment,
+    pub replies: Vec<CommentThread>,
+}
+
+/// A comment as submitted by a reader
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CommentSubmission {
+    pub post_id: i32,
+    /// The comment being replied to, `None` for a top level comment
+    pub parent_id: Option<i32>,
+    pub author_name: String,
+    pub author_email: String,
+    pub content: String,
+    /// Honeypot field hidden from people by the form, bots that fill it in are ignored
+    pub website: String,
+}
+
+/// A comment with everything moderators need to judge it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "server", derive(FromRow))]
+pub struct ModerationComment {
+    pub id: i32,
+    pub post_id: i32,
+    pub post_title: String,
+    pub post_slug: String,
+    pub parent_id: Option<i32>,
+    pub author_name: String,
+    pub author_email: String,
+    pub content: String,
+    pub status: CommentStatus,
+    pub ip_address: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Retrieves the approved comments on a visible post as threads of replies, oldest first
+///
+/// # Arguments
+/// * `post_id` - The ID of the blog post
+#[server]
+pub async fn get_post_comments(post_id: i32) -> Result<Vec<CommentThread>, ServerFnError> {
+    match create_connection().await {
+        Ok(mut conn) => {
+            let query = format!(
+                "SELECT id, post_id, parent_id, author_name, content, created_at
+                FROM comments
+                WHERE post_id = ?1 AND status = 'approved'
+                    AND post_id IN (SELECT id FROM blog_posts WHERE {VISIBLE_POST_FILTER})
+                ORDER BY datetime(created_at), id"
+            );
+            let comments = sqlx::query_as::<_, Comment>(&query)
+                .bind(post_id)
+                .fetch_all(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error loading comments for post {post_id}: {e}");
+                    ServerFnError::new(format!("Error loading comments: {e}"))
+                })?;
+
+            Ok(build_threads(&comments, None))
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Submits a reader's comment to the moderation queue
+///
+/// Submissions with the honeypot field filled in are silently dropped, and each IP
+/// address can only submit a handful of comments every few minutes.
+///
+/// # Arguments
+/// * `submission` - The comment as entered by the reader
+///
+/// # Returns
+/// A `Result` that is `Ok` once the comment is waiting for moderation, or a
+/// `ServerFnError` explaining why it was refused.
+#[server]
+pub async fn submit_comment(submission: CommentSubmission) -> Result<(), ServerFnError> {
+    if !submission.website.is_empty() {
+        // Pretend everything went fine so the bot doesn't try again
+        warn!("Dropping comment on post {} that filled in the honeypot", submission.post_id);
+        return Ok(());
+    }
+
+    let author_name = submission.author_name.trim();
+    let author_email = submission.author_email.trim();
+    let content = submission.content.trim();
+    if author_name.is_empty() || author_name.chars().count() > MAX_AUTHOR_NAME_LENGTH {
+        return Err(ServerFnError::new(format!(
+            "Please enter a name of at most {MAX_AUTHOR_NAME_LENGTH} characters"
+        )));
+    }
+    if !author_email.contains('@') || author_email.chars().count() > MAX_AUTHOR_EMAIL_LENGTH {
+        return Err(ServerFnError::new("Please enter a valid email address"));
+    }
+    if content.is_empty() || content.chars().count() > MAX_COMMENT_LENGTH {
+        return Err(ServerFnError::new(format!(
+            "Comments must be between 1 and {MAX_COMMENT_LENGTH} characters"
+        )));
+    }
+
+    let ip_address = client_ip().await;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            if let Some(ip_address) = &ip_address {
+                let window_start = Utc::now() - chrono::Duration::minutes(COMMENT_RATE_LIMIT_WINDOW_MINUTES);
+                let recent_comments = sqlx::query_scalar!(
+                    "SELECT COUNT(*) FROM comments WHERE ip_address = ?1 AND datetime(created_at) > datetime(?2)",
+                    ip_address,
+                    window_start
+                )
+                .fetch_one(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error checking comment rate limit: {e}");
+                    ServerFnError::new(format!("Error submitting comment: {e}"))
+                })?;
+                if recent_comments >= COMMENT_RATE_LIMIT {
+                    warn!("Rate limiting comments from {ip_address}");
+                    return Err(ServerFnError::new(
+                        "You're commenting too quickly, please try again in a few minutes",
+                    ));
+                }
+            }
+
+            // Comments can only be left on visible posts, and replies only to approved
+            // comments on the same post
+            let query = format!("SELECT EXISTS(SELECT 1 FROM blog_posts WHERE id = ?1 AND {VISIBLE_POST_FILTER})");
+            let post_visible = sqlx::query_scalar::<_, bool>(&query)
+                .bind(submission.post_id)
+                .fetch_one(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error checking post for comment: {e}");
+                    ServerFnError::new(format!("Error submitting comment: {e}"))
+                })?;
+            if !post_visible {
+                return Err(ServerFnError::new("This post is not accepting comments"));
+            }
+            if let Some(parent_id) = submission.parent_id {
+                let parent_approved = sqlx::query_scalar!(
+                    "SELECT EXISTS(SELECT 1 FROM comments WHERE id = ?1 AND post_id = ?2 AND status = 'approved')",
+                    parent_id,
+                    submission.post_id
+                )
+                .fetch_one(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error checking parent comment: {e}");
+                    ServerFnError::new(format!("Error submitting comment: {e}"))
+                })?;
+                if parent_approved == 0 {
+                    return Err(ServerFnError::new("The comment you are replying to no longer exists"));
+                }
+            }
+
+            let now = Utc::now();
+            let result = sqlx::query!(
+                "INSERT INTO comments (post_id, parent_id, author_name, author_email, content, ip_address, created_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                submission.post_id,
+                submission.parent_id,
+                author_name,
+                author_email,
+                content,
+                ip_address,
+                now
+            )
+            .execute(&mut conn)
+            .await;
+
+            match result {
+                Ok(query_result) => {
+                    info!(
+                        "Comment {} on post {} queued for moderation",
+                        query_result.last_insert_rowid(),
+                        submission.post_id
+                    );
+                    Ok(())
+                }
+                Err(e) => {
+                    error!("Error saving comment: {e}");
+                    Err(ServerFnError::new(format!("Error submitting comment: {e}")))
+                }
+            }
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Retrieves the comments with the given moderation status, oldest first so the
+/// queue is worked through in order. The caller must hold an active admin session.
+///
+/// # Arguments
+/// * `status` - Which comments to list, usually [`CommentStatus::Pending`]
+#[server]
+pub async fn get_moderation_queue(
+    status: CommentStatus,
+) -> Result<Vec<ModerationComment>, ServerFnError> {
+    require_scope(Role::Admin, ApiScope::CommentsModerate).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            sqlx::query_as::<_, ModerationComment>(
+                "SELECT comments.id, comments.post_id, blog_posts.title AS post_title, blog_posts.slug AS post_slug,
+                    comments.parent_id, comments.author_name, comments.author_email, comments.content,
+                    comments.status, comments.ip_address, comments.created_at
+                FROM comments
+                JOIN blog_posts ON blog_posts.id = comments.post_id
+                WHERE comments.status = ?1
+                ORDER BY datetime(comments.created_at), comments.id",
+            )
+            .bind(status.as_str())
+            .fetch_all(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error loading moderation queue: {e}");
+                ServerFnError::new(format!("Error loading moderation queue: {e}"))
+            })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Approves, rejects or marks a comment as spam. The caller must hold an active admin session.
+///
+/// # Arguments
+/// * `comment_id` - The ID of the comment
+/// * `status` - The comment's new status
+///
+/// # Returns
+/// A `Result` containing `true` if the comment was updated, `false` if no comment
+/// exists with that ID, or a `ServerFnError` if an error occurs.
+#[server]
+pub async fn moderate_comment(
+    comment_id: i32,
+    status: CommentStatus,
+) -> Result<bool, ServerFnError> {
+    let user = require_scope(Role::Admin, ApiScope::CommentsModerate).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let now = Utc::now();
+            let status_value = status.as_str();
+            let result = sqlx::query!(
+                "UPDATE comments SET status = ?1, moderated_at = ?2 WHERE id = ?3",
+                status_value,
+                now,
+                comment_id
+            )
+            .execute(&mut conn)
+            .await;
+
+            match result {
+                Ok(query_result) => {
+                    let updated = query_result.rows_affected() > 0;
+                    if updated {
+                        info!("Comment {comment_id} marked {status_value} by {}", user.username);
+                    }
+                    Ok(updated)
+                }
+                Err(e) => {
+                    error!("Error moderating comment {comment_id}: {e}");
+                    Err(ServerFnError::new(format!("Error moderating comment: {e}")))
+                }
+            }
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Deletes a comment along with all replies to it. The caller must hold an active
+/// admin session.
+///
+/// # Arguments
+/// * `comment_id` - The ID of the comment
+///
+/// # Returns
+/// A `Result` containing `true` if the comment was deleted, `false` if no comment
+/// exists with that ID, or a `ServerFnError` if an error occurs.
+#[server]
+pub async fn delete_comment(comment_id: i32) -> Result<bool, ServerFnError> {
+    let user = require_scope(Role::Admin, ApiScope::CommentsModerate).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let result = sqlx::query!("DELETE FROM comments WHERE id = ?1", comment_id)
+                .execute(&mut conn)
+                .await;
+
+            match result {
+                Ok(query_result) => {
+                    let deleted = query_result.rows_affected() > 0;
+                    if deleted {
+                        info!("Comment {comment_id} deleted by {}", user.username);
+                    }
+                    Ok(deleted)
+                }
+                Err(e) => {
+                    error!("Error deleting comment {comment_id}: {e}");
+                    Err(ServerFnError::new(format!("Error deleting comment: {e}")))
+                }
+            }
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Arranges comments into threads under `parent_id`, keeping their order. Replies to
+/// comments that aren't in `comments` (e.g. because the parent was rejected) are dropped.
+#[cfg(feature = "server")]
+fn build_threads(comments: &[Comment], parent_id: Option<i32>) -> Vec<CommentThread> {
+    comments
+        .iter()
+        .filter(|comment| comment.parent_id == parent_id)
+        .map(|comment| CommentThread {
+            comment: comment.clone(),
+            replies: build_threads(comments, Some(comment.id)),
+        })
+        .collect()
+}
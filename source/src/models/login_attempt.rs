@@ -0,0 +1,86 @@
+#[cfg(feature = "server")]
+use crate::{auth::require_role, models::Role};
+#[cfg(feature = "server")]
+use crate::database::create_connection;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::error;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use sqlx::FromRow;
+
+/// Number of failed attempts shown to admins
+#[cfg(feature = "server")]
+const FAILED_LOGIN_LIST_LIMIT: i64 = 50;
+
+/// What happened to a login attempt
+///
+/// Stored as lowercase text in `login_attempts.outcome`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "server", derive(sqlx::Type))]
+#[cfg_attr(feature = "server", sqlx(rename_all = "lowercase"))]
+pub enum LoginOutcome {
+    /// The credentials were valid and a session was created
+    Success,
+    /// The username or password was wrong
+    Failure,
+    /// Refused without checking the password because of recent failures
+    Throttled,
+    /// Refused without checking the password because the account is locked
+    Locked,
+}
+
+impl LoginOutcome {
+    /// Returns the value stored in the database for this outcome
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginOutcome::Success => "success",
+            LoginOutcome::Failure => "failure",
+            LoginOutcome::Throttled => "throttled",
+            LoginOutcome::Locked => "locked",
+        }
+    }
+}
+
+/// A recorded login attempt
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "server", derive(FromRow))]
+pub struct LoginAttempt {
+    pub id: i32,
+    /// The username as it was typed, which may not belong to any account
+    pub username: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub outcome: LoginOutcome,
+    pub attempted_at: Option<DateTime<Utc>>,
+}
+
+/// Lists the most recent login attempts that didn't succeed, newest first. The caller
+/// must hold an active admin session.
+#[server]
+pub async fn get_failed_login_attempts() -> Result<Vec<LoginAttempt>, ServerFnError> {
+    require_role(Role::Admin).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            sqlx::query_as::<_, LoginAttempt>(
+                "SELECT id, username, ip_address, user_agent, outcome, attempted_at
+                FROM login_attempts
+                WHERE outcome != 'success'
+                ORDER BY datetime(attempted_at) DESC, id DESC
+                LIMIT ?1",
+            )
+            .bind(FAILED_LOGIN_LIST_LIMIT)
+            .fetch_all(&mut conn)
+            .await
+            .map_err(|e| {
+                error!("Error loading failed login attempts: {e}");
+                ServerFnError::new(format!("Error loading failed login attempts: {e}"))
+            })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
@@ -4,13 +4,109 @@ pub use repo::Repository;
 mod blog_post;
 pub use blog_post::BlogPost;
 pub use blog_post::BlogPostModel;
+pub use blog_post::ContentFormat;
+pub use blog_post::PostStatus;
+pub use blog_post::PostLookup;
+pub use blog_post::slugify;
 pub use blog_post::get_post_by_id;
-pub use blog_post::get_post_list;
+pub use blog_post::get_post_by_slug;
+#[cfg(feature = "server")]
+pub use blog_post::find_post_redirect;
+#[cfg(feature = "server")]
+pub use blog_post::load_recent_posts;
 pub use blog_post::save_post;
+pub use blog_post::update_post;
+pub use blog_post::delete_post;
+pub use blog_post::preview_post;
+pub use blog_post::get_admin_post_list;
+#[cfg(feature = "server")]
+pub use blog_post::insert_post;
+#[cfg(feature = "server")]
+pub use blog_post::load_all_posts;
+#[cfg(feature = "server")]
+pub use blog_post::backfill_post_slugs;
+#[cfg(feature = "server")]
+pub use blog_post::backfill_post_text;
+
+mod post_summary;
+pub use post_summary::PostSummary;
+pub use post_summary::PostSummaryPage;
+pub use post_summary::PostListQuery;
+pub use post_summary::PostSortColumn;
+pub use post_summary::SortDirection;
+pub use post_summary::ArchiveMonth;
+pub use post_summary::POST_LIST_PAGE_SIZE;
+pub use post_summary::get_post_summaries;
+pub use post_summary::get_archive_months;
+
+mod tag;
+pub use tag::Tag;
+pub use tag::TagCount;
+pub use tag::TagPage;
+pub use tag::parse_tag_list;
+pub use tag::format_tag_list;
+pub use tag::get_tag_cloud;
+pub use tag::get_tag_page;
+#[cfg(feature = "server")]
+pub use tag::find_tag;
+
+mod search;
+pub use search::SearchResult;
+pub use search::SearchResults;
+pub use search::SEARCH_PAGE_SIZE;
+pub use search::search_posts;
+
+mod revision;
+pub use revision::PostRevision;
+pub use revision::RevisionDiff;
+pub use revision::DiffLine;
+pub use revision::DiffKind;
+pub use revision::DiffSegment;
+pub use revision::get_post_revisions;
+pub use revision::diff_revisions;
+pub use revision::restore_revision;
+
+mod comment;
+pub use comment::Comment;
+pub use comment::CommentStatus;
+pub use comment::CommentThread;
+pub use comment::CommentSubmission;
+pub use comment::ModerationComment;
+pub use comment::get_post_comments;
+pub use comment::submit_comment;
+pub use comment::get_moderation_queue;
+pub use comment::moderate_comment;
+pub use comment::delete_comment;
+
+mod login_attempt;
+pub use login_attempt::LoginAttempt;
+pub use login_attempt::LoginOutcome;
+pub use login_attempt::get_failed_login_attempts;
 
 mod account;
 pub use account::Role;
 pub use account::Account;
+pub use account::AccountSummary;
+pub use account::AccountPage;
+pub use account::ACCOUNT_LIST_PAGE_SIZE;
+#[cfg(feature = "server")]
 pub use account::get_account_by_id;
+#[cfg(feature = "server")]
 pub use account::get_account_by_username;
 pub use account::save_new_account;
+#[cfg(feature = "server")]
+pub use account::insert_account;
+pub use account::get_accounts;
+#[cfg(feature = "server")]
+pub use account::load_accounts;
+pub use account::update_account_role;
+#[cfg(feature = "server")]
+pub use account::store_account_role;
+pub use account::set_account_active;
+pub use account::delete_account;
+
+mod scheduled_job;
+pub use scheduled_job::JobOutcome;
+pub use scheduled_job::JobStatus;
+pub use scheduled_job::get_job_statuses;
+pub use scheduled_job::run_job_now;
@@ -0,0 +1,279 @@
+#[cfg(feature = "server")]
+use super::blog_post::{BLOG_POST_COLUMNS, VISIBLE_POST_FILTER};
+#[cfg(feature = "server")]
+use super::BlogPost;
+#[cfg(feature = "server")]
+use crate::database::create_connection;
+#[cfg(feature = "server")]
+use crate::markdown::plain_text;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::error;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use sqlx::FromRow;
+
+/// Number of posts shown per page of the table of contents
+pub const POST_LIST_PAGE_SIZE: u32 = 20;
+
+/// Maximum length of a post excerpt in characters, not counting the ellipsis
+#[cfg(feature = "server")]
+const EXCERPT_LENGTH: usize = 200;
+
+/// Average reading speed used to estimate reading times
+#[cfg(feature = "server")]
+const WORDS_PER_MINUTE: usize = 200;
+
+/// The parts of a blog post needed to list it, without the full content
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PostSummary {
+    pub id: i32,
+    pub title: String,
+    pub slug: String,
+    /// The start of the post as plain text
+    pub excerpt: String,
+    pub published_at: Option<DateTime<Utc>>,
+    /// Estimated minutes needed to read the whole post, at least 1
+    pub reading_time_minutes: u32,
+}
+
+/// Columns the post list can be sorted by
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum PostSortColumn {
+    #[default]
+    PublishedAt,
+    Title,
+    ReadingTime,
+}
+
+/// Direction to sort the post list in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum SortDirection {
+    Ascending,
+    #[default]
+    Descending,
+}
+
+impl SortDirection {
+    /// Returns the opposite direction
+    pub fn reversed(&self) -> SortDirection {
+        match self {
+            SortDirection::Ascending => SortDirection::Descending,
+            SortDirection::Descending => SortDirection::Ascending,
+        }
+    }
+}
+
+/// A month with at least one visible post, linked to from the archive list
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "server", derive(FromRow))]
+pub struct ArchiveMonth {
+    pub year: i32,
+    pub month: u32,
+    pub post_count: i64,
+}
+
+/// Which page of posts to list, in what order, and optionally for which month
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PostListQuery {
+    /// 1-based page number, 0 is treated as the first page
+    pub page: u32,
+    pub sort_column: PostSortColumn,
+    pub sort_direction: SortDirection,
+    /// Only list posts published in this `(year, month)`
+    pub archive_month: Option<(i32, u32)>,
+}
+
+/// One page of post summaries
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PostSummaryPage {
+    pub posts: Vec<PostSummary>,
+    /// Total number of posts across all pages
+    pub total: i64,
+    /// 1-based page number of these posts
+    pub page: u32,
+}
+
+impl PostSummaryPage {
+    /// Returns the number of pages needed to list every post
+    pub fn page_count(&self) -> u32 {
+        (self.total as u32).div_ceil(POST_LIST_PAGE_SIZE)
+    }
+}
+
+/// Lists summaries of the blog posts visible to anonymous readers, one page at a time
+///
+/// Only the fields needed for listing are sent to the client, the full content of each
+/// post is reduced to a short excerpt and a reading time on the server.
+///
+/// # Arguments
+/// * `query` - The page, sort order and optional month to list
+///
+/// # Examples
+/// ```ignore
+/// let first_page = get_post_summaries(PostListQuery::default()).await?;
+/// ```
+#[server]
+pub async fn get_post_summaries(query: PostListQuery) -> Result<PostSummaryPage, ServerFnError> {
+    let page = query.page.max(1);
+    let order_by = order_by(query.sort_column, query.sort_direction);
+    let month_filter = query
+        .archive_month
+        .map(|(year, month)| format!("{year:04}-{month:02}"));
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let count_query = format!(
+                "SELECT COUNT(*) FROM blog_posts
+                WHERE {VISIBLE_POST_FILTER} AND (?1 IS NULL OR strftime('%Y-%m', published_at) = ?1)"
+            );
+            let total = sqlx::query_scalar::<_, i64>(&count_query)
+                .bind(&month_filter)
+                .fetch_one(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error counting blog posts: {e}");
+                    ServerFnError::new(format!("Error loading blog posts: {e}"))
+                })?;
+
+            let posts_query = format!(
+                "SELECT {BLOG_POST_COLUMNS} FROM blog_posts
+                WHERE {VISIBLE_POST_FILTER} AND (?1 IS NULL OR strftime('%Y-%m', published_at) = ?1)
+                ORDER BY {order_by}
+                LIMIT ?2 OFFSET ?3"
+            );
+            let posts = sqlx::query_as::<_, BlogPost>(&posts_query)
+                .bind(&month_filter)
+                .bind(POST_LIST_PAGE_SIZE)
+                .bind(i64::from(page - 1) * i64::from(POST_LIST_PAGE_SIZE))
+                .fetch_all(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error loading blog posts: {e}");
+                    ServerFnError::new(format!("Error loading blog posts: {e}"))
+                })?;
+
+            Ok(PostSummaryPage {
+                posts: posts.iter().map(summarize).collect(),
+                total,
+                page,
+            })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Lists every month that has visible posts, newest first, with the number of posts
+/// published in it
+#[server]
+pub async fn get_archive_months() -> Result<Vec<ArchiveMonth>, ServerFnError> {
+    match create_connection().await {
+        Ok(mut conn) => {
+            let query = format!(
+                "SELECT CAST(strftime('%Y', published_at) AS INTEGER) AS year,
+                    CAST(strftime('%m', published_at) AS INTEGER) AS month,
+                    COUNT(*) AS post_count
+                FROM blog_posts
+                WHERE {VISIBLE_POST_FILTER} AND published_at IS NOT NULL
+                GROUP BY year, month
+                ORDER BY year DESC, month DESC"
+            );
+            sqlx::query_as::<_, ArchiveMonth>(&query)
+                .fetch_all(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error loading archive months: {e}");
+                    ServerFnError::new(format!("Error loading archive months: {e}"))
+                })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Returns the `ORDER BY` clause listing posts by the given column, with the newest post
+/// first among equal values
+#[cfg(feature = "server")]
+fn order_by(column: PostSortColumn, direction: SortDirection) -> String {
+    let direction = match direction {
+        SortDirection::Ascending => "ASC",
+        SortDirection::Descending => "DESC",
+    };
+    match column {
+        PostSortColumn::PublishedAt => format!("datetime(published_at) {direction}, id {direction}"),
+        PostSortColumn::Title => format!("title COLLATE NOCASE {direction}, id {direction}"),
+        // Reading times are rounded from the word count, so sorting on it keeps them in order
+        PostSortColumn::ReadingTime => format!("word_count {direction}, id {direction}"),
+    }
+}
+
+/// Reduces a full blog post to its summary
+#[cfg(feature = "server")]
+pub(super) fn summarize(post: &BlogPost) -> PostSummary {
+    let text = plain_text(&post.content, &post.content_format);
+    let word_count = text.split_whitespace().count();
+
+    // Collapse whitespace so the excerpt reads as a single paragraph
+    let mut excerpt = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if let Some((cut_at, _)) = excerpt.char_indices().nth(EXCERPT_LENGTH) {
+        excerpt.truncate(cut_at);
+        // Avoid cutting a word in half
+        if let Some(last_space) = excerpt.rfind(' ') {
+            excerpt.truncate(last_space);
+        }
+        excerpt.push('…');
+    }
+
+    PostSummary {
+        id: post.id.unwrap_or(-1),
+        title: post.title.clone(),
+        slug: post.slug.clone(),
+        excerpt,
+        published_at: post.published_at,
+        reading_time_minutes: word_count.div_ceil(WORDS_PER_MINUTE).max(1) as u32,
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::models::{insert_post, ContentFormat, PostStatus};
+    use sqlx::{Connection, SqliteConnection};
+
+    #[tokio::test]
+    async fn reading_time_sort_matches_the_reading_times_shown() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&mut conn).await.unwrap();
+        let link = format!("[Link](https://example.com/{}) ", "long-path/".repeat(100));
+        let contents = [
+            "word ".repeat(450),
+            link.repeat(300),
+            "<p>word</p> ".repeat(250),
+        ];
+        for content in contents {
+            let post = BlogPost {
+                title: "Post".to_string(),
+                content,
+                content_format: ContentFormat::Markdown,
+                status: PostStatus::Published,
+                ..BlogPost::default()
+            };
+            insert_post(&mut conn, post, None).await.unwrap();
+        }
+
+        let query = format!(
+            "SELECT {BLOG_POST_COLUMNS} FROM blog_posts ORDER BY {}",
+            order_by(PostSortColumn::ReadingTime, SortDirection::Ascending)
+        );
+        let posts = sqlx::query_as::<_, BlogPost>(&query).fetch_all(&mut conn).await.unwrap();
+        let minutes: Vec<u32> = posts.iter().map(|post| summarize(post).reading_time_minutes).collect();
+        assert_eq!(minutes, [2, 2, 3]);
+        // Sorting on the length of the source would put the shortest Markdown first
+        let ids: Vec<Option<i32>> = posts.iter().map(|post| post.id).collect();
+        assert_eq!(ids, [Some(3), Some(2), Some(1)]);
+    }
+}
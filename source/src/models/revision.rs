@@ -0,0 +1,306 @@
+use super::BlogPost;
+#[cfg(feature = "server")]
+use super::blog_post::{begin_save, commit_save, load_post, store_post_text};
+#[cfg(feature = "server")]
+use crate::{auth::{require_scope, ApiScope}, models::Role};
+#[cfg(feature = "server")]
+use crate::database::create_connection;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use similar::{ChangeTag, TextDiff};
+#[cfg(feature = "server")]
+use sqlx::FromRow;
+
+/// Number of unchanged lines shown around each change in a diff
+#[cfg(feature = "server")]
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// A saved version of a blog post, without its content
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "server", derive(FromRow))]
+pub struct PostRevision {
+    pub id: i32,
+    pub post_id: i32,
+    /// Username of the admin who saved this version, if known
+    pub author: Option<String>,
+    pub title: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// How a line in a diff changed between the two revisions
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum DiffKind {
+    Equal,
+    Insert,
+    Delete,
+    /// Stands in for a run of unchanged lines that were left out
+    Separator,
+}
+
+/// Part of a diff line, `emphasized` marks the words that changed within the line
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DiffSegment {
+    pub text: String,
+    pub emphasized: bool,
+}
+
+/// A single line of a diff
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub segments: Vec<DiffSegment>,
+}
+
+/// The differences between two revisions of the same post
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RevisionDiff {
+    pub from: PostRevision,
+    pub to: PostRevision,
+    pub title_diff: Vec<DiffLine>,
+    pub content_diff: Vec<DiffLine>,
+}
+
+/// Columns selected whenever a `PostRevision` row is loaded
+#[cfg(feature = "server")]
+const POST_REVISION_COLUMNS: &str = "blog_post_revisions.id, blog_post_revisions.post_id,
+    accounts.username AS author, blog_post_revisions.title, blog_post_revisions.created_at";
+
+/// Lists every saved version of a post, newest first. The caller must hold an active
+/// admin session.
+///
+/// # Arguments
+/// * `post_id` - The ID of the blog post
+#[server]
+pub async fn get_post_revisions(post_id: i32) -> Result<Vec<PostRevision>, ServerFnError> {
+    require_scope(Role::Admin, ApiScope::PostsRead).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let query = format!(
+                "SELECT {POST_REVISION_COLUMNS}
+                FROM blog_post_revisions
+                LEFT JOIN accounts ON accounts.account_id = blog_post_revisions.account_id
+                WHERE blog_post_revisions.post_id = ?1
+                ORDER BY blog_post_revisions.id DESC"
+            );
+            sqlx::query_as::<_, PostRevision>(&query)
+                .bind(post_id)
+                .fetch_all(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error loading revisions for post {post_id}: {e}");
+                    ServerFnError::new(format!("Error loading revisions: {e}"))
+                })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Computes a line diff, with the changed words within each line emphasized, between
+/// two revisions of the same post. The caller must hold an active admin session.
+///
+/// # Arguments
+/// * `from_revision_id` - The older revision
+/// * `to_revision_id` - The newer revision
+///
+/// # Returns
+/// A `Result` containing either `Some(RevisionDiff)`, `None` if either revision doesn't
+/// exist or they belong to different posts, or a `ServerFnError` if an error occurs.
+#[server]
+pub async fn diff_revisions(
+    from_revision_id: i32,
+    to_revision_id: i32,
+) -> Result<Option<RevisionDiff>, ServerFnError> {
+    require_scope(Role::Admin, ApiScope::PostsRead).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let Some((from, from_content)) = load_revision(&mut conn, from_revision_id).await? else {
+                return Ok(None);
+            };
+            let Some((to, to_content)) = load_revision(&mut conn, to_revision_id).await? else {
+                return Ok(None);
+            };
+            if from.post_id != to.post_id {
+                info!("Refusing to diff revisions {from_revision_id} and {to_revision_id} of different posts");
+                return Ok(None);
+            }
+
+            Ok(Some(RevisionDiff {
+                title_diff: diff_text(&from.title, &to.title),
+                content_diff: diff_text(&from_content, &to_content),
+                from,
+                to,
+            }))
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Makes an older revision the current version of its post by copying its title, content
+/// and format back onto the post. The post keeps its slug and status, and the restore is
+/// itself recorded as a new revision. The caller must hold an active admin session.
+///
+/// # Arguments
+/// * `revision_id` - The revision to restore
+///
+/// # Returns
+/// A `Result` containing either `Some(BlogPost)` with the restored post, `None` if the
+/// revision doesn't exist, or a `ServerFnError` if an error occurs.
+#[server]
+pub async fn restore_revision(revision_id: i32) -> Result<Option<BlogPost>, ServerFnError> {
+    let user = require_scope(Role::Admin, ApiScope::PostsWrite).await?;
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let mut transaction = begin_save(&mut conn).await?;
+            let now = Utc::now();
+            let result = sqlx::query!(
+                "UPDATE blog_posts
+                SET title = blog_post_revisions.title,
+                    content = blog_post_revisions.content,
+                    content_format = blog_post_revisions.content_format,
+                    updated_at = ?1
+                FROM blog_post_revisions
+                WHERE blog_post_revisions.id = ?2 AND blog_posts.id = blog_post_revisions.post_id
+                RETURNING blog_posts.id",
+                now,
+                revision_id
+            )
+            .fetch_optional(&mut *transaction)
+            .await;
+
+            match result {
+                Ok(Some(restored)) => {
+                    let post_id = restored.id as i32;
+                    store_post_text(&mut transaction, post_id).await?;
+                    record_revision(&mut transaction, post_id, Some(user.account_id)).await?;
+                    commit_save(transaction).await?;
+                    info!("Post {post_id} restored to revision {revision_id} by {}", user.username);
+                    load_post(&mut conn, post_id).await
+                }
+                Ok(None) => {
+                    info!("No revision found to restore with id: {revision_id}");
+                    Ok(None)
+                }
+                Err(e) => {
+                    error!("Error restoring revision {revision_id}: {e}");
+                    Err(ServerFnError::new(format!("Error restoring revision: {e}")))
+                }
+            }
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Records the current state of a post as a new revision
+///
+/// # Arguments
+/// * `conn` - An open database connection
+/// * `post_id` - The post that was just saved
+/// * `account_id` - The admin who saved it
+#[cfg(feature = "server")]
+pub(super) async fn record_revision(
+    conn: &mut sqlx::SqliteConnection,
+    post_id: i32,
+    account_id: Option<i32>,
+) -> Result<(), ServerFnError> {
+    let now = Utc::now();
+    let result = sqlx::query!(
+        "INSERT INTO blog_post_revisions (post_id, account_id, title, content, content_format, created_at)
+        SELECT id, ?2, title, content, content_format, ?3 FROM blog_posts WHERE id = ?1",
+        post_id,
+        account_id,
+        now
+    )
+    .execute(conn)
+    .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Error recording revision for post {post_id}: {e}");
+            Err(ServerFnError::new(format!("Error recording revision: {e}")))
+        }
+    }
+}
+
+/// Loads a revision along with its content
+#[cfg(feature = "server")]
+async fn load_revision(
+    conn: &mut sqlx::SqliteConnection,
+    revision_id: i32,
+) -> Result<Option<(PostRevision, String)>, ServerFnError> {
+    let query = format!(
+        "SELECT {POST_REVISION_COLUMNS}, blog_post_revisions.content
+        FROM blog_post_revisions
+        LEFT JOIN accounts ON accounts.account_id = blog_post_revisions.account_id
+        WHERE blog_post_revisions.id = ?1"
+    );
+    let row = sqlx::query(&query)
+        .bind(revision_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| {
+            error!("Error loading revision {revision_id}: {e}");
+            ServerFnError::new(format!("Error loading revision: {e}"))
+        })?;
+
+    match row {
+        Some(row) => {
+            use sqlx::Row;
+            let revision = PostRevision::from_row(&row)?;
+            let content: String = row.try_get("content")?;
+            Ok(Some((revision, content)))
+        }
+        None => {
+            info!("No revision found with id: {revision_id}");
+            Ok(None)
+        }
+    }
+}
+
+/// Diffs two texts line by line, keeping only a few unchanged lines of context around
+/// each change
+#[cfg(feature = "server")]
+fn diff_text(old: &str, new: &str) -> Vec<DiffLine> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut lines = Vec::new();
+
+    for (index, group) in diff.grouped_ops(DIFF_CONTEXT_LINES).iter().enumerate() {
+        if index > 0 {
+            lines.push(DiffLine {
+                kind: DiffKind::Separator,
+                segments: Vec::new(),
+            });
+        }
+        for op in group {
+            for change in diff.iter_inline_changes(op) {
+                let kind = match change.tag() {
+                    ChangeTag::Equal => DiffKind::Equal,
+                    ChangeTag::Insert => DiffKind::Insert,
+                    ChangeTag::Delete => DiffKind::Delete,
+                };
+                let segments = change
+                    .iter_strings_lossy()
+                    .map(|(emphasized, text)| DiffSegment {
+                        text: text.trim_end_matches(['\r', '\n']).to_string(),
+                        emphasized,
+                    })
+                    .collect();
+                lines.push(DiffLine { kind, segments });
+            }
+        }
+    }
+    lines
+}
@@ -0,0 +1,133 @@
+#[cfg(feature = "server")]
+use crate::{auth::require_role, models::Role, scheduler};
+#[cfg(feature = "server")]
+use crate::database::create_connection;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use sqlx::FromRow;
+
+/// How the last run of a background job ended
+///
+/// Stored as lowercase text in `scheduled_jobs.last_outcome`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "server", derive(sqlx::Type))]
+#[cfg_attr(feature = "server", sqlx(rename_all = "lowercase"))]
+pub enum JobOutcome {
+    Success,
+    Failure,
+}
+
+impl JobOutcome {
+    /// Returns the value stored in the database for this outcome
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobOutcome::Success => "success",
+            JobOutcome::Failure => "failure",
+        }
+    }
+}
+
+/// A background job and how its runs have gone, as shown in the admin panel
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobStatus {
+    pub name: String,
+    pub description: String,
+    /// The schedule in words, e.g. "every 1h" or "cron 30 3 * * *"
+    pub schedule: String,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_finished_at: Option<DateTime<Utc>>,
+    pub last_outcome: Option<JobOutcome>,
+    /// What the last run did, or why it failed
+    pub last_message: Option<String>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub run_count: i64,
+    pub failure_count: i64,
+    /// Whether the job is running right now
+    pub is_running: bool,
+}
+
+/// The recorded runs of a job, as stored in `scheduled_jobs`
+#[cfg(feature = "server")]
+#[derive(FromRow)]
+struct JobRunRecord {
+    name: String,
+    last_started_at: Option<DateTime<Utc>>,
+    last_finished_at: Option<DateTime<Utc>>,
+    last_outcome: Option<JobOutcome>,
+    last_message: Option<String>,
+    next_run_at: Option<DateTime<Utc>>,
+    run_count: i64,
+    failure_count: i64,
+}
+
+/// Lists every background job registered with the scheduler along with its last run.
+/// The caller must hold an active admin session.
+#[server]
+pub async fn get_job_statuses() -> Result<Vec<JobStatus>, ServerFnError> {
+    require_role(Role::Admin).await?;
+
+    let records = match create_connection().await {
+        Ok(mut conn) => sqlx::query_as::<_, JobRunRecord>(
+            "SELECT name, last_started_at, last_finished_at, last_outcome, last_message,
+                next_run_at, run_count, failure_count
+            FROM scheduled_jobs",
+        )
+        .fetch_all(&mut conn)
+        .await
+        .map_err(|e| {
+            error!("Error loading scheduled jobs: {e}");
+            ServerFnError::new(format!("Error loading scheduled jobs: {e}"))
+        })?,
+        Err(e) => {
+            return Err(ServerFnError::new(format!(
+                "Database connection error: {e}"
+            )))
+        }
+    };
+
+    Ok(scheduler::registered_jobs()
+        .iter()
+        .map(|job| {
+            let record = records.iter().find(|record| record.name == job.name());
+            JobStatus {
+                name: job.name().to_string(),
+                description: job.description().to_string(),
+                schedule: job.schedule().to_string(),
+                last_started_at: record.and_then(|record| record.last_started_at),
+                last_finished_at: record.and_then(|record| record.last_finished_at),
+                last_outcome: record.and_then(|record| record.last_outcome),
+                last_message: record.and_then(|record| record.last_message.clone()),
+                next_run_at: record.and_then(|record| record.next_run_at),
+                run_count: record.map_or(0, |record| record.run_count),
+                failure_count: record.map_or(0, |record| record.failure_count),
+                is_running: job.is_running(),
+            }
+        })
+        .collect())
+}
+
+/// Runs a background job straight away, outside its schedule. The caller must hold an
+/// active admin session.
+///
+/// # Arguments
+/// * `name` - The name of the job, from [`get_job_statuses`]
+///
+/// # Returns
+/// A `Result` containing what the run did, or a `ServerFnError` if the job failed, is
+/// already running or doesn't exist.
+#[server]
+pub async fn run_job_now(name: String) -> Result<String, ServerFnError> {
+    let user = require_role(Role::Admin).await?;
+    let Some(job) = scheduler::find_job(&name) else {
+        return Err(ServerFnError::new(format!("No job named {name:?}")));
+    };
+
+    info!("{} started the {} job", user.username, job.name());
+    scheduler::run_job(job)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
@@ -0,0 +1,243 @@
+#[cfg(feature = "server")]
+use super::blog_post::VISIBLE_POST_FILTER;
+#[cfg(feature = "server")]
+use crate::database::create_connection;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use sqlx::FromRow;
+
+/// Number of results shown per page of search results
+pub const SEARCH_PAGE_SIZE: u32 = 10;
+
+/// Markers placed around matched terms by SQLite's `snippet()`, replaced with `<mark>`
+/// tags once the rest of the snippet has been escaped
+#[cfg(feature = "server")]
+const HIGHLIGHT_START: char = '\u{2}';
+#[cfg(feature = "server")]
+const HIGHLIGHT_END: char = '\u{3}';
+
+/// A single blog post matching a search query
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "server", derive(FromRow))]
+pub struct SearchResult {
+    pub id: i32,
+    pub title: String,
+    pub slug: String,
+    /// Excerpt of the post around the matched terms as escaped HTML, with the
+    /// matches wrapped in `<mark>` tags
+    pub snippet_html: String,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+/// One page of search results, best matches first
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SearchResults {
+    pub results: Vec<SearchResult>,
+    /// Total number of matching posts across all pages
+    pub total: i64,
+    /// 1-based page number of these results
+    pub page: u32,
+}
+
+impl SearchResults {
+    /// Returns the number of pages needed to show every matching post
+    pub fn page_count(&self) -> u32 {
+        (self.total as u32).div_ceil(SEARCH_PAGE_SIZE)
+    }
+}
+
+/// Searches the titles and content of visible blog posts, ranking title matches above
+/// content matches
+///
+/// Every word in the query must appear in the post, and the last word also matches as a
+/// prefix so results show up while the reader is still typing, e.g. "rust asyn" finds
+/// posts mentioning "rust" and "async".
+///
+/// # Arguments
+/// * `query` - The words to search for as typed by the reader
+/// * `page` - The 1-based page of results to return
+///
+/// # Examples
+/// ```ignore
+/// let results = search_posts("rust async".to_string(), 1).await?;
+/// ```
+#[server]
+pub async fn search_posts(query: String, page: u32) -> Result<SearchResults, ServerFnError> {
+    let page = page.max(1);
+    let Some(match_expression) = to_match_expression(&query) else {
+        return Ok(SearchResults {
+            page,
+            ..SearchResults::default()
+        });
+    };
+
+    match create_connection().await {
+        Ok(mut conn) => {
+            let count_query = format!(
+                "SELECT COUNT(*)
+                FROM blog_posts_fts
+                JOIN blog_posts ON blog_posts.id = blog_posts_fts.rowid
+                WHERE blog_posts_fts MATCH ?1 AND {VISIBLE_POST_FILTER}"
+            );
+            let total = sqlx::query_scalar::<_, i64>(&count_query)
+                .bind(&match_expression)
+                .fetch_one(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error counting search results: {e}");
+                    ServerFnError::new(format!("Error searching posts: {e}"))
+                })?;
+
+            let results_query = format!(
+                "SELECT blog_posts.id, blog_posts.title, blog_posts.slug, blog_posts.published_at,
+                    snippet(blog_posts_fts, 1, ?2, ?3, '…', 24) AS snippet_html
+                FROM blog_posts_fts
+                JOIN blog_posts ON blog_posts.id = blog_posts_fts.rowid
+                WHERE blog_posts_fts MATCH ?1 AND {VISIBLE_POST_FILTER}
+                ORDER BY bm25(blog_posts_fts, 10.0, 1.0)
+                LIMIT ?4 OFFSET ?5"
+            );
+            let mut results = sqlx::query_as::<_, SearchResult>(&results_query)
+                .bind(&match_expression)
+                .bind(HIGHLIGHT_START.to_string())
+                .bind(HIGHLIGHT_END.to_string())
+                .bind(SEARCH_PAGE_SIZE)
+                .bind(i64::from(page - 1) * i64::from(SEARCH_PAGE_SIZE))
+                .fetch_all(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error searching posts: {e}");
+                    ServerFnError::new(format!("Error searching posts: {e}"))
+                })?;
+
+            for result in results.iter_mut() {
+                result.snippet_html = highlight_snippet(&result.snippet_html);
+            }
+
+            info!("Search for {query:?} matched {total} posts");
+            Ok(SearchResults {
+                results,
+                total,
+                page,
+            })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Turns the reader's query into an FTS5 match expression, quoting every word so that
+/// FTS5 operators and punctuation in the query are matched literally rather than
+/// causing syntax errors. Returns `None` if the query has no words.
+#[cfg(feature = "server")]
+fn to_match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{word}\""))
+        .collect();
+
+    // Match the last word as a prefix
+    terms.last().map(|_| format!("{}*", terms.join(" ")))
+}
+
+/// Escapes a raw snippet of post content for display and swaps the highlight markers
+/// for `<mark>` tags
+#[cfg(feature = "server")]
+fn highlight_snippet(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len() + 32);
+    for character in snippet.chars() {
+        match character {
+            HIGHLIGHT_START => html.push_str("<mark>"),
+            HIGHLIGHT_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            _ => html.push(character),
+        }
+    }
+    html
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::models::{insert_post, BlogPost, ContentFormat, PostStatus};
+    use sqlx::{Connection, SqliteConnection};
+
+    #[test]
+    fn quotes_every_word_and_matches_the_last_as_a_prefix() {
+        assert_eq!(to_match_expression("rust async"), Some("\"rust\" \"async\"*".to_string()));
+        assert_eq!(to_match_expression("  rust\t"), Some("\"rust\"*".to_string()));
+        assert_eq!(to_match_expression(""), None);
+        assert_eq!(to_match_expression("   "), None);
+    }
+
+    #[test]
+    fn strips_quotes_from_words() {
+        assert_eq!(to_match_expression("say \"hi\""), Some("\"say\" \"hi\"*".to_string()));
+        assert_eq!(to_match_expression("\"\" \""), None);
+    }
+
+    #[test]
+    fn escapes_snippets_and_marks_matches() {
+        let snippet = format!("a <b> & {HIGHLIGHT_START}rust{HIGHLIGHT_END}'s \"x\"");
+        assert_eq!(
+            highlight_snippet(&snippet),
+            "a &lt;b&gt; &amp; <mark>rust</mark>&#39;s &quot;x&quot;"
+        );
+    }
+
+    async fn search(conn: &mut SqliteConnection, query: &str) -> Vec<String> {
+        let Some(match_expression) = to_match_expression(query) else {
+            return Vec::new();
+        };
+        sqlx::query_scalar::<_, String>(
+            "SELECT snippet(blog_posts_fts, 1, ?2, ?3, '…', 24) FROM blog_posts_fts WHERE blog_posts_fts MATCH ?1",
+        )
+        .bind(match_expression)
+        .bind(HIGHLIGHT_START.to_string())
+        .bind(HIGHLIGHT_END.to_string())
+        .fetch_all(conn)
+        .await
+        .unwrap()
+        .iter()
+        .map(|snippet| highlight_snippet(snippet))
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn searches_plain_text_and_survives_operators() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&mut conn).await.unwrap();
+        let post = BlogPost {
+            title: "Notes".to_string(),
+            content: "Writing **async** Rust, see [the book](https://example.com/tokio) <em>today</em>".to_string(),
+            content_format: ContentFormat::Markdown,
+            status: PostStatus::Published,
+            ..BlogPost::default()
+        };
+        insert_post(&mut conn, post, None).await.unwrap();
+
+        assert_eq!(
+            search(&mut conn, "rust asyn").await,
+            ["Writing <mark>async</mark> <mark>Rust</mark>, see the book today\n"]
+        );
+        // Link targets and tags aren't part of the text
+        assert!(search(&mut conn, "tokio").await.is_empty());
+        assert!(search(&mut conn, "em").await.is_empty());
+        // FTS5 syntax in the query is matched literally instead of failing
+        for query in ["AND", "rust OR", "NEAR(rust", "-rust", "*", "^rust", "title:rust", "\"rust"] {
+            search(&mut conn, query).await;
+        }
+        assert_eq!(search(&mut conn, "\"rust").await.len(), 1);
+    }
+}
@@ -0,0 +1,237 @@
+use super::blog_post::dash_separated;
+use super::PostSummary;
+#[cfg(feature = "server")]
+use super::blog_post::{BLOG_POST_COLUMNS, VISIBLE_POST_FILTER};
+#[cfg(feature = "server")]
+use super::post_summary::summarize;
+#[cfg(feature = "server")]
+use super::BlogPost;
+#[cfg(feature = "server")]
+use crate::database::create_connection;
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{error, info};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use sqlx::FromRow;
+
+/// A tag blog posts can be filed under
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "server", derive(FromRow))]
+pub struct Tag {
+    pub id: Option<i32>,
+    /// Display name as first entered in the editor
+    pub name: String,
+    /// URL segment used by the `/tags/:tag` page
+    pub slug: String,
+}
+
+impl Tag {
+    /// Creates an unsaved tag from its display name, or `None` if the name has no
+    /// letters or digits to build a slug from
+    pub fn from_name(name: &str) -> Option<Tag> {
+        let slug = dash_separated(name);
+        if slug.is_empty() {
+            return None;
+        }
+        Some(Tag {
+            id: None,
+            name: name.trim().to_string(),
+            slug,
+        })
+    }
+}
+
+/// A tag together with the number of visible posts filed under it, used by the tag cloud
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "server", derive(FromRow))]
+pub struct TagCount {
+    pub name: String,
+    pub slug: String,
+    pub post_count: i64,
+}
+
+/// A tag and the visible posts filed under it, newest first
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TagPage {
+    pub tag: Tag,
+    pub posts: Vec<PostSummary>,
+}
+
+/// Parses the comma separated tag list typed into the editor, e.g. "rust, web dev"
+///
+/// Blank entries are skipped, as are entries that map to the same slug as an earlier one.
+pub fn parse_tag_list(input: &str) -> Vec<Tag> {
+    let mut tags: Vec<Tag> = Vec::new();
+    for tag in input.split(',').filter_map(Tag::from_name) {
+        if !tags.iter().any(|existing| existing.slug == tag.slug) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Formats tags back into the comma separated list used by the editor
+pub fn format_tag_list(tags: &[Tag]) -> String {
+    tags.iter()
+        .map(|tag| tag.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Retrieves every tag that has at least one visible post, with its post count,
+/// ordered by name
+#[server]
+pub async fn get_tag_cloud() -> Result<Vec<TagCount>, ServerFnError> {
+    match create_connection().await {
+        Ok(mut conn) => {
+            let query = format!(
+                "SELECT tags.name, tags.slug, COUNT(*) AS post_count
+                FROM tags
+                JOIN blog_post_tags ON blog_post_tags.tag_id = tags.id
+                JOIN blog_posts ON blog_posts.id = blog_post_tags.post_id
+                WHERE {VISIBLE_POST_FILTER}
+                GROUP BY tags.id
+                ORDER BY tags.name COLLATE NOCASE"
+            );
+            sqlx::query_as::<_, TagCount>(&query)
+                .fetch_all(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error loading tag cloud: {e}");
+                    ServerFnError::new(format!("Error loading tag cloud: {e}"))
+                })
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Retrieves a tag by its slug along with the visible posts filed under it
+///
+/// # Arguments
+/// * `tag_slug` - The tag's slug from the `/tags/:tag` URL
+///
+/// # Returns
+/// A `Result` containing either `Some(TagPage)` if the tag exists, `None` if not,
+/// or a `ServerFnError` if an error occurs.
+#[server]
+pub async fn get_tag_page(tag_slug: String) -> Result<Option<TagPage>, ServerFnError> {
+    match create_connection().await {
+        Ok(mut conn) => {
+            let Some(tag) = find_tag(&mut conn, &tag_slug).await? else {
+                info!("No tag found with slug: {tag_slug}");
+                return Ok(None);
+            };
+
+            let query = format!(
+                "SELECT {BLOG_POST_COLUMNS} FROM blog_posts
+                WHERE id IN (SELECT post_id FROM blog_post_tags WHERE tag_id = ?1) AND {VISIBLE_POST_FILTER}
+                ORDER BY published_at DESC"
+            );
+            let posts = sqlx::query_as::<_, BlogPost>(&query)
+                .bind(tag.id)
+                .fetch_all(&mut conn)
+                .await
+                .map_err(|e| {
+                    error!("Error loading posts for tag {tag_slug}: {e}");
+                    ServerFnError::new(format!("Error loading posts for tag: {e}"))
+                })?;
+
+            Ok(Some(TagPage {
+                tag,
+                posts: posts.iter().map(summarize).collect(),
+            }))
+        }
+        Err(e) => Err(ServerFnError::new(format!(
+            "Database connection error: {e}"
+        ))),
+    }
+}
+
+/// Loads a tag by its slug
+#[cfg(feature = "server")]
+pub async fn find_tag(
+    conn: &mut sqlx::SqliteConnection,
+    tag_slug: &str,
+) -> Result<Option<Tag>, ServerFnError> {
+    sqlx::query_as::<_, Tag>("SELECT id, name, slug FROM tags WHERE slug = ?1")
+        .bind(tag_slug)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| {
+            error!("Error loading tag: {e}");
+            ServerFnError::new(format!("Error loading tag: {e}"))
+        })
+}
+
+/// Loads the tags a post is filed under, ordered by name
+#[cfg(feature = "server")]
+pub(super) async fn load_post_tags(
+    conn: &mut sqlx::SqliteConnection,
+    post_id: i32,
+) -> Result<Vec<Tag>, ServerFnError> {
+    sqlx::query_as::<_, Tag>(
+        "SELECT tags.id, tags.name, tags.slug
+        FROM tags
+        JOIN blog_post_tags ON blog_post_tags.tag_id = tags.id
+        WHERE blog_post_tags.post_id = ?1
+        ORDER BY tags.name COLLATE NOCASE",
+    )
+    .bind(post_id)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        error!("Error loading tags for post {post_id}: {e}");
+        ServerFnError::new(format!("Error loading post tags: {e}"))
+    })
+}
+
+/// Replaces the tags a post is filed under, creating any tags that don't exist yet
+/// and removing tags that are no longer used by any post
+#[cfg(feature = "server")]
+pub(super) async fn save_post_tags(
+    conn: &mut sqlx::SqliteConnection,
+    post_id: i32,
+    tags: &[Tag],
+) -> Result<(), ServerFnError> {
+    let map_error = |e: sqlx::Error| {
+        error!("Error saving tags for post {post_id}: {e}");
+        ServerFnError::new(format!("Error saving post tags: {e}"))
+    };
+
+    sqlx::query!("DELETE FROM blog_post_tags WHERE post_id = ?1", post_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(map_error)?;
+
+    for tag in tags {
+        sqlx::query!(
+            "INSERT INTO tags (name, slug) VALUES (?1, ?2) ON CONFLICT(slug) DO NOTHING",
+            tag.name,
+            tag.slug
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(map_error)?;
+
+        sqlx::query!(
+            "INSERT OR IGNORE INTO blog_post_tags (post_id, tag_id)
+            SELECT ?1, id FROM tags WHERE slug = ?2",
+            post_id,
+            tag.slug
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(map_error)?;
+    }
+
+    sqlx::query!("DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM blog_post_tags)")
+        .execute(&mut *conn)
+        .await
+        .map_err(map_error)?;
+
+    info!("Saved {} tags for post {post_id}", tags.len());
+    Ok(())
+}
@@ -0,0 +1,47 @@
+/// Permanent redirects for old blog permalinks
+///
+/// Posts used to be served at `/blog/:id`, and a post's slug changes when its title is
+/// renamed. This middleware answers requests for either kind of old URL with a
+/// `301 Moved Permanently` to the post's current slug before the Dioxus app renders,
+/// so shared links and search engines follow the move.
+use crate::{database::create_connection, models::find_post_redirect};
+use axum::{
+    extract::Request,
+    http::{header::LOCATION, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dioxus::logger::tracing::{error, info};
+
+/// Axum middleware that redirects legacy numeric and renamed blog post URLs
+pub async fn redirect_legacy_blog_urls(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+
+    let key = match request.uri().path().strip_prefix("/blog/") {
+        Some(key) if !key.is_empty() && !key.contains('/') => key.to_string(),
+        _ => return next.run(request).await,
+    };
+
+    let redirect = match create_connection().await {
+        Ok(mut conn) => find_post_redirect(&mut conn, &key).await,
+        Err(e) => {
+            error!("Database connection error while resolving blog redirect: {e}");
+            return next.run(request).await;
+        }
+    };
+
+    match redirect {
+        Ok(Some(current_slug)) => {
+            info!("Permanently redirecting /blog/{key} to /blog/{current_slug}");
+            // Not `Redirect::permanent`, which answers with a 308
+            (StatusCode::MOVED_PERMANENTLY, [(LOCATION, format!("/blog/{current_slug}"))]).into_response()
+        }
+        Ok(None) => next.run(request).await,
+        Err(e) => {
+            error!("Error resolving blog redirect for {key}: {e}");
+            next.run(request).await
+        }
+    }
+}
@@ -1,7 +1,7 @@
 // The dioxus prelude contains a ton of common items used in dioxus apps. It's a good idea to import wherever you
 // need dioxus
 use dioxus::{prelude::*};
-use crate::views::{Admin, Blog,BlogTableOfContents ,Home, Navbar, Projects};
+use crate::views::{Admin, AdminAccounts, AdminApiTokens, Archive, Blog, BlogTableOfContents, EditPost, Home, Navbar, PostRevisions, PreviewPost, Projects, ResetPassword, Search, Sessions, Setup, TagPosts};
 
 /// The Route enum is used to define the structure of internal routes in our app. All route enums need to derive
 /// the [`Routable`] trait, which provides the necessary methods for the router to work.
@@ -24,13 +24,55 @@ pub enum Route {
     BlogTableOfContents {},
 
     // The route attribute can include dynamic parameters that implement [`std::str::FromStr`] and [`std::fmt::Display`] with the `:` syntax.
-    // In this case, id will match any integer like `/blog/123` or `/blog/-456`.
-    #[route("/blog/:id")]
-    Blog { id: i32 },
+    // In this case, slug will match any segment like `/blog/hello-world`. Legacy numeric URLs like `/blog/123`
+    // also land here and are redirected to the post's slug.
+    #[route("/blog/:slug")]
+    Blog { slug: String },
+
+    // Lists the posts published in a month, e.g. `/archive/2024/5`
+    #[route("/archive/:year/:month")]
+    Archive { year: i32, month: u32 },
+
+    // Lists the posts filed under the tag with this slug
+    #[route("/tags/:tag")]
+    TagPosts { tag: String },
+
+    // Query parameters use the `?:` syntax, e.g. `/search?q=rust&page=2`. A missing page defaults to the first
+    #[route("/search?:q&:page")]
+    Search { q: String, page: u32 },
 
     #[route("/projects")]
     Projects {},
 
+    // Password reset links look like `/reset-password?token=...`
+    #[route("/reset-password?:token")]
+    ResetPassword { token: String },
+
+    // The first admin is created here while there are no accounts, e.g. `/setup?token=...`
+    // with the token logged at startup
+    #[route("/setup?:token")]
+    Setup { token: String },
+
+    // Where any logged in user can see and sign out their sessions
+    #[route("/sessions")]
+    Sessions {},
+
     #[route("/admin")]
-    Admin {}
+    Admin {},
+
+    #[route("/admin/accounts")]
+    AdminAccounts {},
+
+    // Where admins mint and revoke API tokens for scripts
+    #[route("/admin/tokens")]
+    AdminApiTokens {},
+
+    #[route("/admin/posts/:id/edit")]
+    EditPost { id: i32 },
+
+    #[route("/admin/posts/:id/preview")]
+    PreviewPost { id: i32 },
+
+    #[route("/admin/posts/:id/revisions")]
+    PostRevisions { id: i32 }
 }
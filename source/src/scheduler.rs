@@ -0,0 +1,674 @@
+/// Background jobs
+///
+/// The scheduler is started once at launch by [`start_scheduler`] and runs each job of
+/// [`registered_jobs`] in its own task, either on a fixed interval or on a cron schedule.
+/// Cron expressions have the usual five fields (minute, hour, day of month, month, day of
+/// week) with `*`, lists, ranges and steps, and are evaluated in UTC.
+///
+/// Every run is recorded in the `scheduled_jobs` table with its start and finish times,
+/// whether it succeeded and what it did, so admins can check on the jobs from the admin
+/// panel. Interval jobs pick up where they left off after a restart, and run straight
+/// away when they are overdue.
+use crate::{
+    auth::{cleanup_expired_sessions, delete_stale_password_resets, prune_login_attempts, SessionConfig},
+    database::create_connection,
+    models::JobOutcome,
+};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+use dioxus::{
+    logger::tracing::{error, info, warn},
+    prelude::ServerFnError,
+};
+use sqlx::SqliteConnection;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+};
+
+/// How often expired sessions are deleted
+const SESSION_CLEANUP_INTERVAL_MINUTES: i64 = 60;
+
+/// How far ahead a cron schedule is searched for its next run before giving up
+const CRON_SEARCH_DAYS: i64 = 5 * 366;
+
+/// Future returned by a job's run function, resolving to a short summary of what the run
+/// did
+pub type JobFuture = Pin<Box<dyn Future<Output = Result<String, ServerFnError>> + Send>>;
+
+/// When a job runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    /// Repeatedly, this long after the previous run started
+    Interval(Duration),
+    Cron(CronSchedule),
+}
+
+impl Schedule {
+    /// Returns the first time after `after` the job should run, or `None` if it never runs
+    /// again
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Interval(interval) => Some(after + *interval),
+            Schedule::Cron(cron) => cron.next_after(after),
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Interval(interval) => {
+                let seconds = interval.num_seconds();
+                if seconds % 3600 == 0 {
+                    write!(f, "every {}h", seconds / 3600)
+                } else if seconds % 60 == 0 {
+                    write!(f, "every {}m", seconds / 60)
+                } else {
+                    write!(f, "every {seconds}s")
+                }
+            }
+            Schedule::Cron(cron) => write!(f, "cron {}", cron.expression),
+        }
+    }
+}
+
+/// A parsed five field cron expression. Each field is kept as a bit set of the values it
+/// matches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    /// Sunday is 0
+    days_of_week: u64,
+    /// Whether the day of month or day of week field is restricted, since a day matches
+    /// either one when both are. Like cron, fields starting with `*` (such as `*/2`) don't
+    /// count as restricted.
+    days_of_month_restricted: bool,
+    days_of_week_restricted: bool,
+}
+
+/// Why a cron expression couldn't be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronError {
+    pub expression: String,
+    pub reason: String,
+}
+
+impl fmt::Display for CronError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid cron expression {:?}: {}", self.expression, self.reason)
+    }
+}
+
+impl std::error::Error for CronError {}
+
+impl CronSchedule {
+    /// Parses a cron expression such as `30 3 * * 1-5`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use chrono::{TimeZone, Utc};
+    /// use web::scheduler::CronSchedule;
+    ///
+    /// let nightly = CronSchedule::parse("15 3 * * *").unwrap();
+    /// let after = Utc.with_ymd_and_hms(2024, 6, 5, 12, 0, 0).unwrap();
+    /// assert_eq!(nightly.next_after(after), Utc.with_ymd_and_hms(2024, 6, 6, 3, 15, 0).single());
+    /// assert!(CronSchedule::parse("15 3 * *").is_err());
+    /// ```
+    pub fn parse(expression: &str) -> Result<Self, CronError> {
+        let error = |reason: String| CronError {
+            expression: expression.to_string(),
+            reason,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            return Err(error(format!("expected 5 fields, found {}", fields.len())));
+        };
+
+        let mut days_of_week_set = parse_cron_field(days_of_week, 0, 7).map_err(error)?;
+        // Both 0 and 7 mean Sunday
+        if days_of_week_set & (1 << 7) != 0 {
+            days_of_week_set = (days_of_week_set | 1) & !(1 << 7);
+        }
+
+        Ok(CronSchedule {
+            expression: fields.join(" "),
+            minutes: parse_cron_field(minutes, 0, 59).map_err(error)?,
+            hours: parse_cron_field(hours, 0, 23).map_err(error)?,
+            days_of_month: parse_cron_field(days_of_month, 1, 31).map_err(error)?,
+            months: parse_cron_field(months, 1, 12).map_err(error)?,
+            days_of_week: days_of_week_set,
+            days_of_month_restricted: !days_of_month.starts_with('*'),
+            days_of_week_restricted: !days_of_week.starts_with('*'),
+        })
+    }
+
+    /// Returns the first whole minute after `after` that matches the expression
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let give_up_at = after + Duration::days(CRON_SEARCH_DAYS);
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+
+        // Skip whole months, days and hours that can't match before trying minutes
+        while time < give_up_at {
+            if !has_bit(self.months, time.month()) {
+                let (year, month) = if time.month() == 12 {
+                    (time.year() + 1, 1)
+                } else {
+                    (time.year(), time.month() + 1)
+                };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?.and_utc();
+            } else if !self.matches_day(time) {
+                time = (time.date_naive() + Duration::days(1)).and_hms_opt(0, 0, 0)?.and_utc();
+            } else if !has_bit(self.hours, time.hour()) {
+                time = time.with_minute(0)? + Duration::hours(1);
+            } else if !has_bit(self.minutes, time.minute()) {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    /// Whether the day of `time` matches the day of month and day of week fields
+    fn matches_day(&self, time: DateTime<Utc>) -> bool {
+        let day_of_month = has_bit(self.days_of_month, time.day());
+        let day_of_week = has_bit(self.days_of_week, time.weekday().num_days_from_sunday());
+        if self.days_of_month_restricted && self.days_of_week_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+}
+
+/// Parses one field of a cron expression into the set of values it matches
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut values = 0_u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("invalid step in {part:?}")),
+            },
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_cron_value(start, min, max)?, parse_cron_value(end, min, max)?)
+        } else {
+            let value = parse_cron_value(range, min, max)?;
+            // "5/15" means every 15 starting at 5
+            (value, if part.contains('/') { max } else { value })
+        };
+        if start > end {
+            return Err(format!("range {range:?} runs backwards"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            values |= 1 << value;
+        }
+    }
+    Ok(values)
+}
+
+/// Parses a single number of a cron field, checking it is within the field's bounds
+fn parse_cron_value(value: &str, min: u32, max: u32) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(number) if (min..=max).contains(&number) => Ok(number),
+        _ => Err(format!("{value:?} is not a number from {min} to {max}")),
+    }
+}
+
+fn has_bit(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// A job the scheduler runs
+pub struct Job {
+    name: &'static str,
+    description: &'static str,
+    schedule: Schedule,
+    run: fn() -> JobFuture,
+    /// Set while a run is in progress, so scheduled and manual runs can't overlap
+    running: AtomicBool,
+}
+
+impl Job {
+    /// A job run repeatedly, `interval` after the previous run started
+    pub fn every(name: &'static str, description: &'static str, interval: Duration, run: fn() -> JobFuture) -> Self {
+        Job {
+            name,
+            description,
+            schedule: Schedule::Interval(interval),
+            run,
+            running: AtomicBool::new(false),
+        }
+    }
+
+    /// A job run whenever the cron expression matches
+    pub fn cron(
+        name: &'static str,
+        description: &'static str,
+        expression: &str,
+        run: fn() -> JobFuture,
+    ) -> Result<Self, CronError> {
+        Ok(Job {
+            name,
+            description,
+            schedule: Schedule::Cron(CronSchedule::parse(expression)?),
+            run,
+            running: AtomicBool::new(false),
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn description(&self) -> &'static str {
+        self.description
+    }
+
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    /// Whether the job is running right now
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+}
+
+/// Why a job run didn't succeed
+#[derive(Debug)]
+pub enum JobError {
+    /// The job was still running from an earlier start
+    AlreadyRunning(&'static str),
+    /// The job ran and failed with the given message
+    Failed(String),
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobError::AlreadyRunning(name) => write!(f, "The {name} job is already running"),
+            JobError::Failed(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for JobError {}
+
+/// Returns every job known to the scheduler. New periodic work is added here.
+pub fn registered_jobs() -> &'static [Job] {
+    static JOBS: OnceLock<Vec<Job>> = OnceLock::new();
+    JOBS.get_or_init(|| {
+        let mut jobs = Vec::new();
+        if SessionConfig::default().cleanup_expired {
+            jobs.push(Job::every(
+                "session_cleanup",
+                "Deletes expired and signed out sessions",
+                Duration::minutes(SESSION_CLEANUP_INTERVAL_MINUTES),
+                || {
+                    Box::pin(async {
+                        let deleted = cleanup_expired_sessions().await?;
+                        Ok(format!("Deleted {deleted} sessions"))
+                    })
+                },
+            ));
+        }
+
+        let cron_jobs = [
+            Job::cron(
+                "password_reset_cleanup",
+                "Deletes password reset links that were used or have expired",
+                "15 3 * * *",
+                || {
+                    Box::pin(async {
+                        let deleted = delete_stale_password_resets().await?;
+                        Ok(format!("Deleted {deleted} password reset tokens"))
+                    })
+                },
+            ),
+            Job::cron(
+                "login_attempt_pruning",
+                "Deletes login attempts older than 90 days",
+                "45 3 * * *",
+                || {
+                    Box::pin(async {
+                        let deleted = prune_login_attempts().await?;
+                        Ok(format!("Deleted {deleted} login attempts"))
+                    })
+                },
+            ),
+        ];
+        for job in cron_jobs {
+            match job {
+                Ok(job) => jobs.push(job),
+                Err(e) => error!("Not scheduling job: {e}"),
+            }
+        }
+        jobs
+    })
+}
+
+/// Finds a registered job by its name
+pub fn find_job(name: &str) -> Option<&'static Job> {
+    registered_jobs().iter().find(|job| job.name == name)
+}
+
+/// Starts running every registered job on its schedule. Must be called from within the
+/// Tokio runtime, once at launch.
+///
+/// # Examples
+/// ```ignore
+/// scheduler::start_scheduler();
+/// axum::serve(listener, router).await?;
+/// ```
+pub fn start_scheduler() {
+    for job in registered_jobs() {
+        info!("Scheduling the {} job {}", job.name, job.schedule);
+        tokio::spawn(run_on_schedule(job));
+    }
+}
+
+/// Runs a job right away and records the run. Fails without running the job if it is
+/// already running.
+///
+/// # Returns
+/// A `Result` containing the summary of what the run did.
+pub async fn run_job(job: &Job) -> Result<String, JobError> {
+    let mut conn = create_connection().await.map_err(|e| {
+        error!("Database connection error: {e}");
+        JobError::Failed(format!("Database connection error: {e}"))
+    })?;
+    run_and_record(job, &mut conn).await
+}
+
+/// Runs a job and records the run using the given connection
+async fn run_and_record(job: &Job, conn: &mut SqliteConnection) -> Result<String, JobError> {
+    if job.running.swap(true, Ordering::SeqCst) {
+        return Err(JobError::AlreadyRunning(job.name));
+    }
+
+    let _running = RunningGuard(&job.running);
+
+    let started_at = Utc::now();
+    record_job_start(conn, job, started_at).await;
+    // Run in its own task so a panicking job fails this run rather than the scheduler
+    let result = match tokio::spawn((job.run)()).await {
+        Ok(Ok(message)) => Ok(message),
+        Ok(Err(ServerFnError::ServerError(message))) => Err(message),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(e) => Err(format!("The job panicked: {e}")),
+    };
+
+    let (outcome, message) = match &result {
+        Ok(message) => {
+            info!("The {} job succeeded: {message}", job.name);
+            (JobOutcome::Success, message)
+        }
+        Err(message) => {
+            error!("The {} job failed: {message}", job.name);
+            (JobOutcome::Failure, message)
+        }
+    };
+    record_job_finish(conn, job, outcome, message).await;
+    result.map_err(JobError::Failed)
+}
+
+/// Clears a job's running flag when dropped, so it is cleared even when the run is
+/// cancelled part way through
+struct RunningGuard<'a>(&'a AtomicBool);
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Runs a job every time its schedule comes round, for as long as the server runs
+async fn run_on_schedule(job: &'static Job) {
+    let mut next_run = first_run(job).await;
+    loop {
+        let Some(run_at) = next_run else {
+            warn!("The {} job has no upcoming runs", job.name);
+            return;
+        };
+        record_next_run(job, run_at).await;
+        let wait = (run_at - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        // Failures are recorded and logged by run_job, and a job that is already running
+        // was started by hand, so the schedule carries on either way
+        let started_at = Utc::now();
+        let _ = run_job(job).await;
+        next_run = match job.schedule {
+            // Counted from the start so the runs don't drift by how long each one takes
+            Schedule::Interval(_) => job.schedule.next_after(started_at),
+            Schedule::Cron(_) => job.schedule.next_after(Utc::now()),
+        };
+    }
+}
+
+/// When a job should first run after the server starts. Interval jobs continue from their
+/// last recorded start.
+async fn first_run(job: &Job) -> Option<DateTime<Utc>> {
+    let now = Utc::now();
+    let Schedule::Interval(interval) = job.schedule else {
+        return job.schedule.next_after(now);
+    };
+
+    let last_started_at = match create_connection().await {
+        Ok(mut conn) => sqlx::query_scalar!(
+            r#"SELECT last_started_at AS "last_started_at: DateTime<Utc>" FROM scheduled_jobs WHERE name = ?1"#,
+            job.name
+        )
+        .fetch_optional(&mut conn)
+        .await
+        .unwrap_or_else(|e| {
+            error!("Error loading the last run of the {} job: {e}", job.name);
+            None
+        })
+        .flatten(),
+        Err(e) => {
+            error!("Database connection error: {e}");
+            None
+        }
+    };
+    Some(last_started_at.map_or(now, |last_started_at| (last_started_at + interval).max(now)))
+}
+
+/// Records when a job will next run
+async fn record_next_run(job: &Job, next_run_at: DateTime<Utc>) {
+    let schedule = job.schedule.to_string();
+    let result = match create_connection().await {
+        Ok(mut conn) => sqlx::query!(
+            "INSERT INTO scheduled_jobs (name, schedule, next_run_at) VALUES (?1, ?2, ?3)
+            ON CONFLICT(name) DO UPDATE SET schedule = excluded.schedule, next_run_at = excluded.next_run_at",
+            job.name,
+            schedule,
+            next_run_at
+        )
+        .execute(&mut conn)
+        .await
+        .map(|_| ()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        error!("Error recording the next run of the {} job: {e}", job.name);
+    }
+}
+
+/// Records that a job has started running
+async fn record_job_start(conn: &mut SqliteConnection, job: &Job, started_at: DateTime<Utc>) {
+    let schedule = job.schedule.to_string();
+    let result = sqlx::query!(
+        "INSERT INTO scheduled_jobs (name, schedule, last_started_at) VALUES (?1, ?2, ?3)
+        ON CONFLICT(name) DO UPDATE SET schedule = excluded.schedule, last_started_at = excluded.last_started_at",
+        job.name,
+        schedule,
+        started_at
+    )
+    .execute(conn)
+    .await;
+    if let Err(e) = result {
+        error!("Error recording the start of the {} job: {e}", job.name);
+    }
+}
+
+/// Records how a job's run ended
+async fn record_job_finish(conn: &mut SqliteConnection, job: &Job, outcome: JobOutcome, message: &str) {
+    let finished_at = Utc::now();
+    let outcome_value = outcome.as_str();
+    let failed = i64::from(outcome == JobOutcome::Failure);
+    let result = sqlx::query!(
+        "UPDATE scheduled_jobs
+        SET last_finished_at = ?1, last_outcome = ?2, last_message = ?3,
+            run_count = run_count + 1, failure_count = failure_count + ?4
+        WHERE name = ?5",
+        finished_at,
+        outcome_value,
+        message,
+        failed,
+        job.name
+    )
+    .execute(conn)
+    .await;
+    if let Err(e) = result {
+        error!("Error recording the outcome of the {} job: {e}", job.name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use sqlx::Connection;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    fn next(expression: &str, after: DateTime<Utc>) -> DateTime<Utc> {
+        CronSchedule::parse(expression).unwrap().next_after(after).unwrap()
+    }
+
+    #[test]
+    fn parses_steps() {
+        assert_eq!(parse_cron_field("*/15", 0, 59), Ok(1 << 0 | 1 << 15 | 1 << 30 | 1 << 45));
+        assert_eq!(parse_cron_field("5/20", 0, 59), Ok(1 << 5 | 1 << 25 | 1 << 45));
+        assert_eq!(parse_cron_field("*/2", 1, 31).map(|days| days.count_ones()), Ok(16));
+        assert!(parse_cron_field("*/0", 0, 59).is_err());
+        assert!(parse_cron_field("*/x", 0, 59).is_err());
+    }
+
+    #[test]
+    fn parses_ranges_and_lists() {
+        assert_eq!(parse_cron_field("1-5", 0, 6), Ok(0b11_1110));
+        assert_eq!(parse_cron_field("10-20/5", 0, 59), Ok(1 << 10 | 1 << 15 | 1 << 20));
+        assert_eq!(parse_cron_field("1,3,5-6", 0, 6), Ok(0b110_1010));
+        assert!(parse_cron_field("5-1", 0, 6).is_err());
+        assert!(parse_cron_field("0-60", 0, 59).is_err());
+        assert!(parse_cron_field("1,,2", 0, 59).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_expressions() {
+        let expressions = ["", "* * * *", "* * * * * *", "60 * * * *", "* 24 * * *", "* * 0 * *", "* * * 13 *", "* * * * 8"];
+        for expression in expressions {
+            assert!(CronSchedule::parse(expression).is_err(), "{expression}");
+        }
+    }
+
+    #[test]
+    fn sunday_is_0_or_7() {
+        let sunday_as_7 = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(sunday_as_7.days_of_week, 1);
+        let sunday_as_0 = CronSchedule::parse("0 0 * * 0").unwrap();
+        assert_eq!(sunday_as_7.days_of_week, sunday_as_0.days_of_week);
+        // 2024-06-05 is a Wednesday
+        assert_eq!(next("0 0 * * 7", at(2024, 6, 5, 12, 0)), at(2024, 6, 9, 0, 0));
+        assert_eq!(CronSchedule::parse("0 0 * * 5-7").unwrap().days_of_week, 0b110_0001);
+    }
+
+    #[test]
+    fn finds_the_next_matching_minute() {
+        assert_eq!(next("*/15 * * * *", at(2024, 6, 5, 12, 7)), at(2024, 6, 5, 12, 15));
+        // The current minute has already started, so it doesn't count
+        assert_eq!(next("*/15 * * * *", at(2024, 6, 5, 12, 15)), at(2024, 6, 5, 12, 30));
+        assert_eq!(next("15 3 * * *", at(2024, 6, 5, 3, 20)), at(2024, 6, 6, 3, 15));
+        assert_eq!(next("0 9 * * 1-5", at(2024, 6, 7, 10, 0)), at(2024, 6, 10, 9, 0));
+        assert_eq!(next("0 0 1 1 *", at(2024, 6, 5, 0, 0)), at(2025, 1, 1, 0, 0));
+        assert_eq!(next("0 0 29 2 *", at(2025, 3, 1, 0, 0)), at(2028, 2, 29, 0, 0));
+        assert_eq!(CronSchedule::parse("0 0 31 2 *").unwrap().next_after(at(2024, 1, 1, 0, 0)), None);
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either_day() {
+        // The 13th of the month or any Friday. 2024-06-05 is a Wednesday, the 7th a Friday
+        let schedule = CronSchedule::parse("0 0 13 * 5").unwrap();
+        assert_eq!(schedule.next_after(at(2024, 6, 5, 12, 0)), Some(at(2024, 6, 7, 0, 0)));
+        assert_eq!(schedule.next_after(at(2024, 6, 7, 12, 0)), Some(at(2024, 6, 13, 0, 0)));
+    }
+
+    #[test]
+    fn a_single_restricted_day_field_must_match() {
+        assert_eq!(next("0 0 13 * *", at(2024, 6, 5, 12, 0)), at(2024, 6, 13, 0, 0));
+        assert_eq!(next("0 0 * * 5", at(2024, 6, 8, 12, 0)), at(2024, 6, 14, 0, 0));
+    }
+
+    #[test]
+    fn stepped_wildcards_are_not_restricted() {
+        // Odd days of the month that are also Mondays, not odd days or Mondays
+        let schedule = CronSchedule::parse("0 0 */2 * 1").unwrap();
+        assert!(!schedule.days_of_month_restricted);
+        assert!(schedule.days_of_week_restricted);
+        // 2024-06-10 is an even Monday, the 17th the next odd one
+        assert_eq!(schedule.next_after(at(2024, 6, 4, 0, 0)), Some(at(2024, 6, 17, 0, 0)));
+
+        let schedule = CronSchedule::parse("0 0 1 * */2").unwrap();
+        assert!(!schedule.days_of_week_restricted);
+    }
+
+    #[test]
+    fn intervals_count_from_the_start() {
+        let schedule = Schedule::Interval(Duration::minutes(60));
+        assert_eq!(schedule.next_after(at(2024, 6, 5, 12, 0)), Some(at(2024, 6, 5, 13, 0)));
+        assert_eq!(schedule.to_string(), "every 1h");
+        assert_eq!(Schedule::Interval(Duration::seconds(90)).to_string(), "every 90s");
+    }
+
+    #[tokio::test]
+    async fn panicking_job_fails_and_can_run_again() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&mut conn).await.unwrap();
+        let job = Job::every("panics", "Panics every time", Duration::minutes(1), || {
+            Box::pin(async { panic!("the job broke") })
+        });
+        for _ in 0..2 {
+            assert!(matches!(run_and_record(&job, &mut conn).await, Err(JobError::Failed(_))));
+            assert!(!job.is_running());
+        }
+
+        let (outcome, message, run_count, failure_count) = sqlx::query_as::<_, (String, String, i64, i64)>(
+            "SELECT last_outcome, last_message, run_count, failure_count FROM scheduled_jobs WHERE name = 'panics'",
+        )
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+        assert_eq!(outcome, JobOutcome::Failure.as_str());
+        assert!(message.starts_with("The job panicked"), "{message}");
+        assert_eq!((run_count, failure_count), (2, 2));
+    }
+}
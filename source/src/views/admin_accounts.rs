@@ -0,0 +1,12 @@
+use crate::components::{AccountManagement, AdminGuard};
+use dioxus::prelude::*;
+
+/// The accounts console that will be rendered when the current route is `[Route::AdminAccounts]`
+#[component]
+pub fn AdminAccounts() -> Element {
+    rsx! {
+        AdminGuard {
+            AccountManagement {}
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use crate::components::{AdminGuard, ApiTokens};
+use dioxus::prelude::*;
+
+/// The API token console that will be rendered when the current route is `[Route::AdminApiTokens]`
+#[component]
+pub fn AdminApiTokens() -> Element {
+    rsx! {
+        AdminGuard {
+            ApiTokens {}
+        }
+    }
+}
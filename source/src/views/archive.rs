@@ -0,0 +1,26 @@
+use crate::{
+    components::{month_name, PostSummaryTable},
+    route::Route,
+};
+use dioxus::prelude::*;
+
+/// The archive page component that will be rendered when the current route is `[Route::Archive]`
+///
+/// Lists the visible posts published in the given month.
+#[component]
+pub fn Archive(year: i32, month: u32) -> Element {
+    rsx! {
+        div {
+            class: "blog-archive",
+            h2 {
+                style: "color: #ffffff; margin-bottom: 20px;",
+                "Posts from {month_name(month)} {year}"
+            }
+            Link {
+                to: Route::BlogTableOfContents {},
+                "All posts"
+            }
+            PostSummaryTable { archive_month: Some((year, month)) }
+        }
+    }
+}
@@ -1,8 +1,7 @@
-use std::vec;
-
 use crate::{
-    components::{ResourceNotFound},
-    models::{get_post_by_id, BlogPostModel},
+    components::{CommentSection, ResourceNotFound, TagChips},
+    models::{get_post_by_slug, BlogPostModel, PostLookup},
+    route::Route,
 };
 
 use dioxus::{
@@ -13,17 +12,23 @@ use dioxus::{
 //TODO: Should revisit this later
 // const BLOG_CSS: Asset = asset!("/assets/styling/blog.css");
 
-pub async fn get_blog_model(post_id: i32) -> Option<BlogPostModel> {
-    match get_post_by_id(post_id).await {
-        Ok(Some(post)) => {
+/// Loads the blog post for a permalink, following redirects from legacy numeric IDs and
+/// previous slugs by replacing the current route with the post's canonical URL
+pub async fn get_blog_model(slug: String, navigator: Navigator) -> Option<BlogPostModel> {
+    match get_post_by_slug(slug.clone()).await {
+        Ok(PostLookup::Found(post)) => {
             Some(post.to_model())
         }
-        Ok(None) => {
-            warn!("Blog post with id {post_id} not found");
+        Ok(PostLookup::Redirect(current_slug)) => {
+            navigator.replace(Route::Blog { slug: current_slug });
+            None
+        }
+        Ok(PostLookup::NotFound) => {
+            warn!("Blog post with slug {slug} not found");
             None
         }
         Err(e) => {
-            error!("Error fetching blog post with id {post_id}: {e}");
+            error!("Error fetching blog post with slug {slug}: {e}");
             None
         }
     }
@@ -31,35 +36,26 @@ pub async fn get_blog_model(post_id: i32) -> Option<BlogPostModel> {
 
 /// The Blog page component that will be rendered when the current route is `[Route::Blog]`
 ///
-/// The component takes a `id` prop of type `i32` from the route enum. Whenever the id changes, the component function will be
+/// The component takes a `slug` prop from the route enum. Whenever the slug changes, the component function will be
 /// re-run and the rendered HTML will be updated.
 #[component]
-pub fn Blog(id: i32) -> Element {
-    let post_resource = use_resource(move || async move { get_blog_model(id).await });
+pub fn Blog(slug: String) -> Element {
+    let navigator = navigator();
+    let post_resource = use_resource(use_reactive!(|slug| async move {
+        get_blog_model(slug, navigator).await
+    }));
 
     let x = match &*post_resource.read() {
         Some(Some(post)) => {
             rsx! {
-                // div { id: "blog-post", dangerously_set_inner_html: post.to_html() }
-                div {
-                    class: "blog-post",
-                    id: "blog-post-{id}",
-                    h1 {
-                        class: "blog-post-title",
-                        id: "blog-post-title-{id}",
-                        "{post.title}"
-                    }
-                    //TODO: Maybe don't use danger_inner_html but eh what's the worst that could
-                    //happen
-                    p {
-                        class: "blog-post-content",
-                        dangerous_inner_html:post.content.to_string()
-                    }
+                BlogArticle { post: post.clone() }
+                if let Some(post_id) = post.id {
+                    CommentSection { post_id }
                 }
             }
         }
         Some(None) => {
-            warn!("Blog post with id {id} not found");
+            warn!("Blog post with slug {slug} not found");
             rsx! {
                 ResourceNotFound {}
             }
@@ -72,3 +68,37 @@ pub fn Blog(id: i32) -> Element {
     };
     x
 }
+
+/// Renders the title, publication date and content of a single blog post
+#[component]
+pub fn BlogArticle(post: BlogPostModel) -> Element {
+    let id = post.id.unwrap_or(-1);
+    rsx! {
+        // div { id: "blog-post", dangerously_set_inner_html: post.to_html() }
+        div {
+            class: "blog-post",
+            id: "blog-post-{id}",
+            h1 {
+                class: "blog-post-title",
+                id: "blog-post-title-{id}",
+                "{post.title}"
+            }
+            if let Some(published_at) = post.published_at {
+                p {
+                    class: "blog-post-date",
+                    style: "color: #888;",
+                    "{published_at.format(\"%d/%m/%Y\")}"
+                }
+            }
+            if !post.tags.is_empty() {
+                TagChips { tags: post.tags.clone() }
+            }
+            // content_html is rendered and sanitized against an allow-list on the
+            // server, so it is safe to inject directly
+            div {
+                class: "blog-post-content",
+                dangerous_inner_html: post.content_html.to_string()
+            }
+        }
+    }
+}
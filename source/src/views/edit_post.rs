@@ -0,0 +1,12 @@
+use crate::components::{AdminGuard, NewEditBlog};
+use dioxus::prelude::*;
+
+/// The admin page for editing an existing blog post, rendered for `[Route::EditPost]`
+#[component]
+pub fn EditPost(id: i32) -> Element {
+    rsx! {
+        AdminGuard {
+            NewEditBlog { post_id: id }
+        }
+    }
+}
@@ -12,5 +12,38 @@ pub use projects::Projects;
 mod admin;
 pub use admin::Admin;
 
+mod admin_accounts;
+pub use admin_accounts::AdminAccounts;
+
+mod admin_api_tokens;
+pub use admin_api_tokens::AdminApiTokens;
+
+mod edit_post;
+pub use edit_post::EditPost;
+
+mod post_revisions;
+pub use post_revisions::PostRevisions;
+
+mod preview_post;
+pub use preview_post::PreviewPost;
+
+mod tag_posts;
+pub use tag_posts::TagPosts;
+
+mod search;
+pub use search::Search;
+
+mod sessions;
+pub use sessions::Sessions;
+
+mod reset_password;
+pub use reset_password::ResetPassword;
+
+mod setup;
+pub use setup::Setup;
+
+mod archive;
+pub use archive::Archive;
+
 mod blog_table_contents;
 pub use blog_table_contents::BlogTableOfContents;
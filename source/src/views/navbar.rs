@@ -10,8 +10,14 @@ const NAVBAR_CSS: Asset = asset!("/assets/styling/navbar.css");
 /// routes will be rendered under the outlet inside this component
 #[component]
 pub fn Navbar() -> Element {
+    let mut search_query = use_signal(|| "".to_string());
+
     rsx! {
         document::Link { rel: "stylesheet", href: NAVBAR_CSS }
+        // Let feed readers discover the blog's feeds from any page
+        document::Link { rel: "alternate", r#type: "application/rss+xml", title: "RSS", href: "/feed.xml" }
+        document::Link { rel: "alternate", r#type: "application/atom+xml", title: "Atom", href: "/atom.xml" }
+        document::Link { rel: "alternate", r#type: "application/feed+json", title: "JSON Feed", href: "/feed.json" }
 
         div {
             id: "navbar",
@@ -34,6 +40,21 @@ pub fn Navbar() -> Element {
             div{
                 id: "right-nav",
                 class: "right-nav",
+                form {
+                    id: "navbar-search",
+                    onsubmit: move |_| {
+                        navigator().push(Route::Search { q: search_query.read().trim().to_string(), page: 1 });
+                    },
+                    input {
+                        r#type: "search",
+                        name: "q",
+                        placeholder: "Search posts",
+                        value: "{search_query}",
+                        oninput: move |input_event| {
+                            search_query.set(input_event.value().clone());
+                        }
+                    }
+                }
                 Link {
                     to: Route::Admin {},
                     "Admin"
@@ -0,0 +1,12 @@
+use crate::components::{AdminGuard, RevisionHistory};
+use dioxus::prelude::*;
+
+/// The revision history page that will be rendered when the current route is `[Route::PostRevisions]`
+#[component]
+pub fn PostRevisions(id: i32) -> Element {
+    rsx! {
+        AdminGuard {
+            RevisionHistory { post_id: id }
+        }
+    }
+}
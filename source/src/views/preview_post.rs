@@ -0,0 +1,66 @@
+use super::blog::BlogArticle;
+use crate::{
+    components::{AdminGuard, ResourceNotFound},
+    models::{preview_post, PostStatus},
+    route::Route,
+};
+use dioxus::{
+    logger::tracing::error,
+    prelude::*,
+};
+
+/// The admin page for previewing a blog post in any status, rendered for `[Route::PreviewPost]`
+#[component]
+pub fn PreviewPost(id: i32) -> Element {
+    rsx! {
+        AdminGuard {
+            PostPreview { id }
+        }
+    }
+}
+
+/// Loads a post through the admin preview so drafts and scheduled posts can be viewed
+/// exactly as readers will see them once published
+#[component]
+fn PostPreview(id: i32) -> Element {
+    let post_resource = use_resource(move || async move { preview_post(id).await });
+
+    // Bind the match result so the resource read guard is dropped before returning
+    let preview = match &*post_resource.read() {
+        Some(Ok(Some(post))) => {
+            let is_visible = post.is_visible();
+            let status = post.status;
+            rsx! {
+                div {
+                    class: "preview-banner",
+                    style: "background: #2a2a2a; padding: 10px; border-radius: 5px; margin-bottom: 20px;",
+                    if is_visible {
+                        "Previewing a {status:?} post that is visible to readers. "
+                    } else if status == PostStatus::Scheduled {
+                        "Previewing a scheduled post that is not visible to readers yet. "
+                    } else {
+                        "Previewing a {status:?} post that is not visible to readers. "
+                    }
+                    Link {
+                        to: Route::EditPost { id },
+                        "Edit"
+                    }
+                }
+                BlogArticle { post: post.to_model() }
+            }
+        }
+        Some(Ok(None)) => rsx! {
+            ResourceNotFound {}
+        },
+        Some(Err(e)) => {
+            error!("Error loading post {id} for preview: {e}");
+            rsx! {
+                p { "Error loading blog post: {e}" }
+            }
+        }
+        None => rsx! {
+            p { "Loading blog post..." }
+        },
+    };
+    preview
+}
@@ -0,0 +1,20 @@
+use crate::components::ResetPasswordForm;
+use dioxus::prelude::*;
+
+/// The password reset page that will be rendered when the current route is `[Route::ResetPassword]`
+///
+/// Reached through a reset link issued by an admin, so it doesn't need a session.
+#[component]
+pub fn ResetPassword(token: String) -> Element {
+    rsx! {
+        div {
+            style: "max-width: 400px; margin: 0 auto; padding: 20px;",
+            h1 { "Reset Password" }
+            if token.is_empty() {
+                p { "This reset link is incomplete, please check that you copied all of it." }
+            } else {
+                ResetPasswordForm { token }
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{
+    models::{search_posts, SearchResults},
+    route::Route,
+};
+use dioxus::{logger::tracing::error, prelude::*};
+
+/// The search page component that will be rendered when the current route is `[Route::Search]`
+///
+/// Shows the visible posts matching `q`, best matches first, `SEARCH_PAGE_SIZE` at a time.
+/// Queries are entered through the search box in the [`super::Navbar`].
+#[component]
+pub fn Search(q: String, page: u32) -> Element {
+    let page = page.max(1);
+    let search_results = use_resource(use_reactive!(|q, page| async move {
+        search_posts(q, page).await
+    }));
+
+    let results = match &*search_results.read() {
+        Some(Ok(_)) if q.trim().is_empty() => {
+            rsx! {
+                p {
+                    style: "color: #888;",
+                    "Enter some words in the search box to find blog posts."
+                }
+            }
+        }
+        Some(Ok(results)) => {
+            rsx! {
+                SearchResultList { q: q.clone(), results: results.clone() }
+            }
+        }
+        Some(Err(e)) => {
+            error!("Error searching for {q}: {e}");
+            rsx! {
+                p { "Error searching posts. Please try again later." }
+            }
+        }
+        None => {
+            rsx! {
+                p { "Searching..." }
+            }
+        }
+    };
+
+    rsx! {
+        div {
+            class: "search-page",
+            h2 {
+                style: "color: #ffffff; margin-bottom: 20px;",
+                "Search"
+            }
+            {results}
+        }
+    }
+}
+
+/// Renders a page of search results with links to the previous and next pages
+#[component]
+fn SearchResultList(q: String, results: SearchResults) -> Element {
+    let page_count = results.page_count();
+    rsx! {
+        p {
+            style: "color: #888;",
+            "{results.total} posts found for \"{q}\""
+        }
+        for result in results.results.iter() {
+            div {
+                key: "{result.id}",
+                class: "search-result",
+                style: "margin-bottom: 20px;",
+                Link {
+                    to: Route::Blog { slug: result.slug.clone() },
+                    style: "color: #4a9eff; text-decoration: none; font-weight: 500; font-size: 1.1em;",
+                    "{result.title}"
+                }
+                if let Some(published_at) = result.published_at {
+                    span {
+                        style: "color: #888; margin-left: 10px;",
+                        "{published_at.format(\"%d/%m/%Y\")}"
+                    }
+                }
+                // snippet_html is escaped on the server, only the <mark> highlights are markup
+                p {
+                    class: "search-result-snippet",
+                    style: "margin: 4px 0 0 0;",
+                    dangerous_inner_html: result.snippet_html.clone()
+                }
+            }
+        }
+        if page_count > 1 {
+            div {
+                class: "search-pagination",
+                style: "display: flex; gap: 20px; align-items: center;",
+                if results.page > 1 {
+                    Link {
+                        to: Route::Search { q: q.clone(), page: results.page - 1 },
+                        "Previous"
+                    }
+                }
+                span {
+                    style: "color: #888;",
+                    "Page {results.page} of {page_count}"
+                }
+                if results.page < page_count {
+                    Link {
+                        to: Route::Search { q: q.clone(), page: results.page + 1 },
+                        "Next"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use crate::{
+    components::{ActiveSessions, AdminGuard},
+    models::Role,
+};
+use dioxus::prelude::*;
+
+/// The active sessions page that will be rendered when the current route is `[Route::Sessions]`
+///
+/// Open to every logged in account, not just admins.
+#[component]
+pub fn Sessions() -> Element {
+    rsx! {
+        AdminGuard {
+            role: Role::Guest,
+            ActiveSessions {}
+        }
+    }
+}
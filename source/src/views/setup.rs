@@ -0,0 +1,21 @@
+use crate::components::FirstRunSetup;
+use dioxus::prelude::*;
+
+/// The first-run setup page that will be rendered when the current route is `[Route::Setup]`
+///
+/// Reached through the setup link logged when the server starts without any accounts, so
+/// it doesn't need a session.
+#[component]
+pub fn Setup(token: String) -> Element {
+    rsx! {
+        div {
+            style: "max-width: 400px; margin: 0 auto; padding: 20px;",
+            h1 { "Set Up" }
+            if token.is_empty() {
+                p { "This setup link is incomplete, please copy all of it from the server log." }
+            } else {
+                FirstRunSetup { token }
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{
+    components::ResourceNotFound,
+    models::get_tag_page,
+    route::Route,
+};
+use dioxus::{
+    logger::tracing::{error, warn},
+    prelude::*,
+};
+
+/// The tag page component that will be rendered when the current route is `[Route::TagPosts]`
+///
+/// Lists the visible posts filed under the tag with the given slug, newest first.
+#[component]
+pub fn TagPosts(tag: String) -> Element {
+    let tag_page = use_resource(use_reactive!(|tag| async move { get_tag_page(tag).await }));
+
+    let page = match &*tag_page.read() {
+        Some(Ok(Some(page))) => {
+            rsx! {
+                div {
+                    class: "tag-posts",
+                    h2 {
+                        style: "color: #ffffff; margin-bottom: 20px;",
+                        "Posts tagged \"{page.tag.name}\""
+                    }
+                    if page.posts.is_empty() {
+                        p {
+                            style: "color: #888;",
+                            "No posts with this tag yet."
+                        }
+                    }
+                    ul {
+                        for post in page.posts.iter() {
+                            li {
+                                key: "{post.slug}",
+                                style: "margin-bottom: 8px;",
+                                Link {
+                                    to: Route::Blog { slug: post.slug.clone() },
+                                    style: "color: #4a9eff; text-decoration: none; font-weight: 500;",
+                                    "{post.title}"
+                                }
+                                if let Some(published_at) = post.published_at {
+                                    span {
+                                        style: "color: #888; margin-left: 10px;",
+                                        "{published_at.format(\"%d/%m/%Y\")}"
+                                    }
+                                }
+                                p {
+                                    style: "margin: 4px 0 0 0; color: #aaa; font-size: 0.9em;",
+                                    "{post.excerpt}"
+                                }
+                            }
+                        }
+                    }
+                    p {
+                        style: "color: #888;",
+                        "Subscribe to this tag: "
+                        // Feeds are served by the server outside of the router
+                        a { href: "/tags/{page.tag.slug}/feed.xml", "RSS" }
+                        " · "
+                        a { href: "/tags/{page.tag.slug}/atom.xml", "Atom" }
+                        " · "
+                        a { href: "/tags/{page.tag.slug}/feed.json", "JSON Feed" }
+                    }
+                    Link {
+                        to: Route::BlogTableOfContents {},
+                        "All posts"
+                    }
+                }
+            }
+        }
+        Some(Ok(None)) => {
+            warn!("Tag {tag} not found");
+            rsx! {
+                ResourceNotFound {}
+            }
+        }
+        Some(Err(e)) => {
+            error!("Error loading posts for tag {tag}: {e}");
+            rsx! {
+                p { "Error loading posts. Please try again later." }
+            }
+        }
+        None => {
+            rsx! {
+                p { "Loading posts..." }
+            }
+        }
+    };
+    page
+}